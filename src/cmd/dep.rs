@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+use anyhow::anyhow;
+use clap::arg;
+use crate::cmd::{Cmd, CmdResult, Args, Conf};
+use crate::lib::dep::{self, cargo, gemfile, DepGraph, Ecosystem, Format, TravelMode};
+use crate::lib::dep::pattern::match_names;
+use crate::lib::pod::dep::source_of;
+use crate::lib::pod::project::Project;
+
+// `rubo dep` 和 `rubo rdep`，按文件名选择 lockfile 的格式
+pub(super) enum Command { Dep, Rdep }
+
+impl Command {
+    const NAME_DEP: &'static str = "dep";
+    const NAME_RDEP: &'static str = "rdep";
+}
+
+impl Cmd for Command {
+    fn key(&self) -> String {
+        match self {
+            Command::Dep => Command::NAME_DEP.to_string(),
+            Command::Rdep => Command::NAME_RDEP.to_string(),
+        }
+    }
+
+    fn conf(&self) -> Conf {
+        let about = match self {
+            Command::Dep => "Find dependencies in Podfile.lock, Package.resolved, Gemfile.lock or Cargo.lock",
+            Command::Rdep => "Find reverse dependencies in Podfile.lock, Package.resolved, Gemfile.lock or Cargo.lock",
+        };
+        Conf::new(if let Command::Dep = self { Command::NAME_DEP } else { Command::NAME_RDEP })
+            .arg_required_else_help(true)
+            .args(&[
+                arg!(-n --name <PATTERN> "Name, glob (e.g. 'serde*') or regex wrapped in slashes (e.g. '/^tokio/'), can be repeated")
+                    .multiple_occurrences(true)
+                    .required(true),
+                arg!(-p --path <FILE> "Path to lockfile, the format is picked by file name. Defaults to the first one found in current directory")
+                    .required(false),
+                arg!(-d --depth <DEPTH> "Max display depth").required(false),
+                arg!(--combined "Print one combined list for all matched names instead of a tree for each").required(false),
+            ])
+            .about(about)
+    }
+
+    fn process(&self, args: &Args) -> CmdResult {
+        let path = match args.value_of("path") {
+            Some(p) => PathBuf::from(p),
+            None => {
+                let dir = std::env::current_dir().unwrap_or(PathBuf::from("."));
+                Format::FILE_NAMES.iter()
+                    .map(|n| dir.join(n))
+                    .find(|p| p.is_file())
+                    .ok_or_else(|| anyhow!("cannot find any of {} in {:?}", Format::FILE_NAMES.join(", "), dir))?
+            },
+        };
+        if !path.exists() {
+            return Err(anyhow!("{:?} is not exists", &path));
+        }
+        let format = Format::from_path(&path)
+            .ok_or_else(|| anyhow!("unknown lockfile {:?}, expected one of {}", path, Format::FILE_NAMES.join(", ")))?;
+        let patterns = args.values_of("name")
+            .unwrap()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>();
+        let max_depth = args.value_of("depth")
+            .map(|d| d.parse::<usize>().map_err(|_| anyhow!("invalid depth: {}", d)))
+            .transpose()?
+            .unwrap_or(999_999_999);
+        let combined = args.is_present("combined");
+        let reverse = matches!(self, Command::Rdep);
+
+        let file = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let patterns = patterns.iter().map(|p| p.as_str()).collect::<Vec<&str>>();
+        let mode = if reverse { TravelMode::Parents } else { TravelMode::Children };
+        // 各种 lockfile 都读成 `DepGraph`，按同样的方式挑选节点和展示
+        let print = |graph: &dyn DepGraph, source_of: &dyn Fn(&str) -> Option<&'static str>, unit: &str| -> CmdResult {
+            let targets = match_names(&graph.names(), &patterns, &file)?;
+            dep::print_deps(graph, &targets, max_depth, combined, mode, source_of, unit);
+            Ok(())
+        };
+        match format {
+            Format::Podfile => {
+                let project = Project::load(Some(&path), false, None, &[])?;
+                let source_of = |n: &str| source_of(&project.pods, &project.external_sources, n);
+                print(&project.pods, &source_of, Ecosystem::CocoaPods.unit())
+            },
+            Format::PackageResolved => {
+                let project = Project::load(None, false, Some(&path), &[])?;
+                print(&project.pods, &|_| None, Ecosystem::SwiftPM.unit())
+            },
+            Format::Gemfile | Format::Cargo => {
+                let graph = if format == Format::Gemfile { gemfile::read(&path)? } else { cargo::read(&path)? };
+                print(&graph, &|n| graph.source(n), graph.ecosystem.unit())
+            },
+        }
+    }
+}
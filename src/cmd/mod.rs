@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::boxed::Box;
+use std::fmt;
 use clap::{Command, ArgMatches};
 use ansi_term::{Colour, Style};
 
@@ -15,9 +16,22 @@ trait Cmd {
     fn process(&self, args: &Args) -> anyhow::Result<()>;
 }
 
+// 以指定的退出码结束，不输出错误信息，譬如 `pod check` 发现不满足的版本要求时
+#[derive(Debug)]
+struct Exit(i32);
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exit with status {}", self.0)
+    }
+}
+
+impl std::error::Error for Exit {}
+
 // commands
 mod cd;
 mod ls;
+mod dep;
 mod cat;
 mod day;
 mod git;
@@ -30,6 +44,7 @@ mod open;
 mod todo;
 mod tree;
 mod work;
+mod xcode;
 mod count;
 mod strip;
 
@@ -45,7 +60,10 @@ impl Container {
     pub fn commands(&mut self) -> Vec<Conf> {
         let mut vec = Vec::new();
         self.add_cmd(pod::Command, &mut vec);
+        self.add_cmd(dep::Command::Dep, &mut vec);
+        self.add_cmd(dep::Command::Rdep, &mut vec);
         self.add_cmd(tree::Command, &mut vec);
+        self.add_cmd(xcode::Command, &mut vec);
         vec
     }
 
@@ -61,11 +79,15 @@ impl Container {
             let key = sub_cmd.to_string();
             let cmd = self.commands.get(&key).unwrap();
             if let Err(e) = cmd.process(sub_args) {
+                if let Some(Exit(code)) = e.downcast_ref::<Exit>() {
+                    std::process::exit(*code);
+                }
                 println!(
                     "Process {} failed: {}",
                     &sub_cmd,
                     Style::from(Colour::Red).bold().paint(e.to_string()).to_string()
                 );
+                std::process::exit(1);
             }
         }
     }
@@ -1,8 +1,17 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use anyhow::anyhow;
 use clap::arg;
-use crate::cmd::{Cmd, CmdResult, Args, Conf};
-use crate::lib::pod::dep;
+use crate::cmd::{Cmd, CmdResult, Args, Conf, Exit};
+use regex::Regex;
+use crate::lib::pod::{affected, check, clean, dep, diff, graph, imports, info, licenses, lint, merge, path, search, size, stats, status, unused};
+use crate::lib::pod::lockfile::{collapse_subspecs, root_name, Lockfile};
+use crate::lib::pod::search::Searcher;
+use crate::lib::pod::clean::Cleaner;
+use crate::lib::pod::version::Version;
+use crate::lib::pod::graph::GraphBuilder;
+use crate::lib::pod::pattern::match_pods;
+use crate::lib::pod::project::Project;
 
 pub(super) struct Command;
 
@@ -13,32 +22,228 @@ impl Command {
     const SUB_RDEP: &'static str = "rdep";
     const SUB_SEARCH: &'static str = "search";
     const SUB_CLEAN: &'static str = "clean";
+    const SUB_DIFF: &'static str = "diff";
+    const SUB_WHY: &'static str = "why";
+    const SUB_INFO: &'static str = "info";
+    const SUB_CHECK: &'static str = "check";
+    const SUB_GRAPH: &'static str = "graph";
+    const SUB_STATS: &'static str = "stats";
+    const SUB_PATH: &'static str = "path";
+    const SUB_AFFECTED: &'static str = "affected";
+    const SUB_MERGE: &'static str = "merge";
+    const SUB_STATUS: &'static str = "status";
+    const SUB_SIZE: &'static str = "size";
+    const SUB_LICENSES: &'static str = "licenses";
+    const SUB_LINT: &'static str = "lint";
+    const SUB_IMPORTS: &'static str = "imports";
+    const SUB_UNUSED: &'static str = "unused";
 
     fn sub_cmd_conf_list(&self) -> Vec<Conf> {
         let arg_name = || { arg!(-n --name <NAME> "Pod name").required(true) };
         let arg_path = || { arg!(-p --path <PATH> "Path to Podfile.lock").required(false) };
         let arg_depth = || { arg!(-d --depth <DEPTH> "Max display depth").required(false) };
+        let arg_collapse = || { arg!(--"collapse-subspecs" "Merge subspecs into their root spec").required(false) };
+        let arg_subspecs_of = || {
+            arg!(--"subspecs-of" <ROOT> "Show how subspecs of specified pod depend on each other")
+                .required(false)
+                .conflicts_with("name")
+        };
+        let arg_dep_name = || {
+            arg!(-n --name <PATTERN> "Pod name, glob (e.g. 'Firebase*') or regex wrapped in slashes (e.g. '/^AF/'), can be repeated")
+                .multiple_occurrences(true)
+                .required_unless_present("subspecs-of")
+        };
+        let arg_combined = || {
+            arg!(--combined "Print one combined list for all matched pods instead of a tree for each").required(false)
+        };
+        let arg_resolved = || {
+            arg!(--resolved <FILE> "Path to SwiftPM Package.resolved, merged into the graph. Package.swift next to it is read as the root package")
+                .required(false)
+        };
+        let arg_manifest = || {
+            arg!(--manifest <FILE> "Path to Package.swift of a local Swift package, or its directory, can be repeated")
+                .multiple_occurrences(true)
+                .required(false)
+        };
         let dep = Conf::new(Command::SUB_DEP)
-            .args(&[arg_name(), arg_path(), arg_depth()])
-            .about("Find dependencies for specified pod");
+            .args(&[
+                arg_dep_name(), arg_path(), arg_depth(), arg_collapse(), arg_subspecs_of(), arg_combined(),
+                arg_resolved(), arg_manifest(),
+            ])
+            .about("Find dependencies for specified pods");
 
         let rdep = Conf::new(Command::SUB_RDEP)
-            .args(&[arg_name(), arg_path(), arg_depth()])
-            .about("Find reserve dependencies for specified pod");
-
-        // let arg_text = || { arg!(-t --text <TEXT> "Search pattern").required(true) };
-        // let arg_exclude = || { arg!(-e --excludes <POD_NAMES> "exclude pod names").required(false) };
-        // let arg_a = || { arg!(-A --"after-context" <NUM> "show NUM lines after each match").required(false) };
-        // let arg_b = || { arg!(-B --"before-context" <NUM> "Show NUM lines before each match").required(false) };
-        // let arg_name_only = || { arg!(--"name-only" "only display pod name").required(false) };
-        // let search = Conf::new(Command::SUB_SEARCH)
-        //     .args(&[arg_text(), arg_exclude(), arg_a(), arg_b(), arg_name_only()])
-        //     .about("Search in Pods/ directory");
-        //
-        // let clean = Conf::new(Command::SUB_CLEAN)
-        //     .about("Clean pods and free disk");
-        // vec![dep, rdep, search, clean]
-        vec![dep, rdep]
+            .args(&[
+                arg_dep_name(), arg_path(), arg_depth(), arg_collapse(), arg_subspecs_of(), arg_combined(),
+                arg_resolved(), arg_manifest(),
+            ])
+            .about("Find reserve dependencies for specified pods");
+
+        let arg_text = || { arg!(-t --text <TEXT> "Search pattern (regex)").required(true) };
+        let arg_exclude = || { arg!(-e --excludes <POD_NAMES> "Exclude pod names, separated by comma").required(false) };
+        let arg_a = || { arg!(-A --"after-context" <NUM> "Show NUM lines after each match").required(false) };
+        let arg_b = || { arg!(-B --"before-context" <NUM> "Show NUM lines before each match").required(false) };
+        let arg_name_only = || { arg!(--"name-only" "Only display pod name").required(false) };
+        let arg_within = || { arg!(-n --name <NAME> "Only search in dependencies of specified pod").required(false) };
+        let search = Conf::new(Command::SUB_SEARCH)
+            .args(&[arg_text(), arg_exclude(), arg_a(), arg_b(), arg_name_only(), arg_within(), arg_path()])
+            .about("Search in Pods/ directory");
+
+        let clean = Conf::new(Command::SUB_CLEAN)
+            .args(&[
+                arg!(-c --cache <DIR> "Path to CocoaPods pods cache. Defaults to ~/Library/Caches/CocoaPods/Pods").required(false),
+                arg!(-n --names <POD_NAMES> "Only clean specified pods, separated by comma").required(false),
+                arg!(--days <DAYS> "Select entries not accessed in DAYS days").required(false),
+                arg!(--below <VERSION> "Select entries whose version is lower than VERSION, external entries have no version and are never selected").required(false),
+                arg!(--projects <DIRS> "Select entries not used by any Podfile.lock under DIRS, separated by comma").required(false),
+                arg!(--delete "Delete selected entries. By default only a report is printed").required(false),
+            ])
+            .about("Clean pods cache and free disk");
+
+        let diff = Conf::new(Command::SUB_DIFF)
+            .args(&[
+                arg!(<OLD> "Old Podfile.lock, either a path or `rev:path` in git"),
+                arg!(<NEW> "New Podfile.lock, either a path or `rev:path` in git"),
+                arg!(--json "Print as JSON").required(false),
+            ])
+            .about("Compare two Podfile.lock");
+
+        let why = Conf::new(Command::SUB_WHY)
+            .args(&[
+                arg_name(),
+                arg_path(),
+                arg!(-l --limit <NUM> "Only show the first NUM paths, 0 for all").required(false).default_value("20"),
+            ])
+            .about("List every dependency path from Podfile to specified pod");
+
+        let info = Conf::new(Command::SUB_INFO)
+            .args(&[arg_name(), arg_path()])
+            .about("Show everything Podfile.lock knows about specified pod");
+
+        let check = Conf::new(Command::SUB_CHECK)
+            .args(&[arg_path()])
+            .about("Check that resolved versions satisfy every requirement in Podfile.lock");
+
+        let graph = Conf::new(Command::SUB_GRAPH)
+            .args(&[
+                arg_path(),
+                arg!(-f --format <FORMAT> "Output format").required(false)
+                    .possible_values(graph::Format::NAMES)
+                    .default_value("dot"),
+                arg!(-n --name <PATTERN> "Only export the subgraph around matched pods, same patterns as dep, can be repeated")
+                    .multiple_occurrences(true)
+                    .required(false),
+                arg!(-r --reverse "Expand reverse dependencies of --name instead of dependencies").required(false),
+                arg_depth(),
+                arg!(-i --include <PATTERNS> "Only keep pods matching glob patterns, separated by comma").required(false),
+                arg!(-e --exclude <PATTERNS> "Drop pods matching glob patterns, separated by comma").required(false),
+                arg!(--versions "Show versions on nodes").required(false),
+                arg_collapse(),
+                arg!(-o --output <FILE> "Write to FILE instead of stdout").required(false),
+                arg_resolved(),
+                arg_manifest(),
+            ])
+            .about("Export pod dependency graph as DOT, Mermaid or JSON");
+
+        let stats = Conf::new(Command::SUB_STATS)
+            .args(&[
+                arg_path(),
+                arg!(-t --top <NUM> "Show top NUM pods in rankings").required(false).default_value("10"),
+                arg_collapse(),
+                arg!(--json "Print as JSON").required(false),
+            ])
+            .about("Find dependency cycles and rank pods by fan-in and fan-out");
+
+        let path = Conf::new(Command::SUB_PATH)
+            .args(&[
+                arg!(<FROM> "Pod to start from, glob or regex wrapped in slashes are accepted"),
+                arg!(<TO> "Pod to reach, glob or regex wrapped in slashes are accepted"),
+                arg_path(),
+                arg!(-a --all "List all simple paths instead of the shortest one").required(false),
+                arg!(--"max-length" <NUM> "Max hops of each path when --all is given").required(false).default_value("10"),
+                arg_collapse(),
+            ])
+            .about("Find how one pod depends on another. Exits with status 2 when no path exists");
+
+        let affected = Conf::new(Command::SUB_AFFECTED)
+            .args(&[
+                arg_path(),
+                arg!(-b --base <REV> "Use `git diff --name-only REV` as changed files. By default they are read from stdin, one per line")
+                    .required(false),
+                arg!(--"relative-to-cwd" "Resolve paths read from stdin against the current directory instead of the repository root")
+                    .required(false),
+                arg!(-s --separator <SEP> "Separator between pod names. Defaults to newline").required(false),
+                arg!(--json "Print as JSON").required(false),
+            ])
+            .about("Find development pods touched by changed files and every pod depending on them");
+
+        let merge = Conf::new(Command::SUB_MERGE)
+            .args(&[
+                arg!(<BASE> "Common ancestor, `%O` of git merge driver"),
+                arg!(<OURS> "Current version, `%A` of git merge driver"),
+                arg!(<THEIRS> "Other branch's version, `%B` of git merge driver"),
+                arg!(-o --output <FILE> "Write result to FILE, `-` for stdout. Defaults to OURS as git merge driver expects")
+                    .required(false),
+            ])
+            .about("Three-way merge Podfile.lock entry by entry. Exits with status 1 on conflicts");
+
+        let status = Conf::new(Command::SUB_STATUS)
+            .args(&[
+                arg_path(),
+                arg!(--pods <DIR> "Path to Pods/ directory. Defaults to Pods/ next to Podfile.lock").required(false),
+            ])
+            .about("Check that Podfile.lock, Pods/Manifest.lock and Pods/ directory are in sync. Exits with status 1 when they are not");
+
+        let size = Conf::new(Command::SUB_SIZE)
+            .args(&[
+                arg_path(),
+                arg!(--pods <DIR> "Path to Pods/ directory. Defaults to Pods/ next to Podfile.lock").required(false),
+                arg!(-s --sort <KEY> "Sort by own size, transitive size or size freed by removing the pod").required(false)
+                    .possible_values(size::SortKey::NAMES)
+                    .default_value("transitive"),
+                arg!(-t --top <NUM> "Show top NUM pods").required(false).default_value("20"),
+                arg!(--json "Print as JSON").required(false),
+            ])
+            .about("Measure disk usage of each pod in Pods/ along with its dependencies");
+
+        let licenses = Conf::new(Command::SUB_LICENSES)
+            .args(&[
+                arg_path(),
+                arg!(--pods <DIR> "Path to Pods/ directory. Defaults to Pods/ next to Podfile.lock").required(false),
+                arg!(-f --format <FORMAT> "Output format").required(false)
+                    .possible_values(licenses::Format::NAMES)
+                    .default_value("markdown"),
+                arg!(--development "Include development pods introduced by `:path`").required(false),
+                arg!(--strict "Exit with status 1 when any pod has no license or a copyleft license").required(false),
+                arg!(-o --output <FILE> "Write to FILE instead of stdout").required(false),
+            ])
+            .about("Generate acknowledgements from licenses of pods, flag missing and copyleft ones");
+
+        let lint = Conf::new(Command::SUB_LINT)
+            .args(&[
+                arg_path(),
+                arg!(-r --rules <FILE> "Path to layering rules. Defaults to rubo-layers.yaml next to Podfile.lock").required(false),
+                arg_collapse(),
+            ])
+            .about("Check dependencies between pods against layering rules. Exits with status 1 on violations");
+
+        let arg_module = || {
+            arg!(-m --module <MAPPING> "Map a module name to the pod providing it, e.g. `Lottie=lottie-ios`, can be repeated")
+                .multiple_occurrences(true)
+                .required(false)
+        };
+        let imports = Conf::new(Command::SUB_IMPORTS)
+            .args(&[arg_path(), arg_module()])
+            .about("Compare imports in sources of development pods with their declared dependencies");
+
+        let unused = Conf::new(Command::SUB_UNUSED)
+            .args(&[
+                arg!(<ROOTS> ... "Source directories of the app"),
+                arg_path(),
+                arg_module(),
+            ])
+            .about("Find pods neither imported by the app or the development pods it uses nor required by imported pods");
+        vec![dep, rdep, search, clean, diff, why, info, check, graph, stats, path, affected, merge, status, size, licenses, lint, imports, unused]
     }
 }
 
@@ -50,69 +255,432 @@ impl Cmd for Command {
     fn conf(&self) -> Conf {
         Conf::new(Command::NAME)
             .arg_required_else_help(true)
+            .arg(
+                arg!(--lenient "Skip malformed entries of Podfile.lock with warnings instead of failing")
+                    .required(false)
+                    .global(true)
+            )
             .subcommands(self.sub_cmd_conf_list())
             .about("Pod Utilities")
     }
 
     fn process(&self, args: &Args) -> CmdResult {
         let (sub_cmd, sub_args) = args.subcommand().unwrap();
+        // `--lenient` 是 pod 的全局参数，各个子命令都可以用
+        let lenient = sub_args.is_present("lenient");
         match sub_cmd {
             Command::SUB_DEP | Command::SUB_RDEP => {
-                let path =
-                    if let Some(p) = sub_args.value_of("path") {
-                        PathBuf::from(p).to_path_buf()
-                    } else {
-                        let mut p = std::env::current_dir()
-                            .unwrap_or(PathBuf::from("."));
-                        p.push("Podfile.lock");
-                        p
-                    };
-                if !path.exists() {
-                    return Err(anyhow!("{:?} is not exists", &path));
+                if let Some(root) = sub_args.value_of("subspecs-of") {
+                    return dep::print_subspec_deps(lockfile_path(sub_args)?, lenient, root, sub_cmd == Command::SUB_RDEP)
                 }
-                let target= sub_args.value_of("name").unwrap();
+                let project = load_project(sub_args, lenient)?;
+                let patterns = sub_args.values_of("name")
+                    .unwrap()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<String>>();
                 let max_depth = sub_args.value_of("depth")
                     .and_then(|d| d.to_string().parse::<usize>().ok())
                     .unwrap_or(999_999_999);
+                let collapse = sub_args.is_present("collapse-subspecs");
+                let combined = sub_args.is_present("combined");
                 if let Command::SUB_DEP = sub_cmd {
-                    dep::print_deps(path, target, max_depth)
+                    dep::print_deps(&project, &patterns, max_depth, collapse, combined)
                 } else {
-                    dep::print_reserve_deps(path, target, max_depth)
+                    dep::print_reserve_deps(&project, &patterns, max_depth, collapse, combined)
                 }
             },
             Command::SUB_SEARCH => {
-                // rob.arg(Command::SUB_SEARCH);
-                // if let Some(text) = sub_args.value_of("text") {
-                //     rob.arg("--text").arg(text);
-                // }
-                // if let Some(excludes) = sub_args.value_of("excludes") {
-                //     rob.arg("--excludes").arg(excludes);
-                // }
-                // if let Some(before) = sub_args.value_of("before-context") {
-                //     rob.arg("-B").arg(before);
-                // }
-                // if let Some(after) = sub_args.value_of("after-context") {
-                //     rob.arg("-A").arg(after);
-                // }
-                // if sub_args.occurrences_of("name-only") > 0 {
-                //     rob.arg("--name-only");
-                // }
-                // // 中转命令到 `rob pod`
-                // let mut rob = process::Command::new("rob");
-                // rob.arg("pod");
-                // rob.spawn().unwrap().wait().unwrap();
-                println!("暂时未实现");
+                let path = resolve_lockfile_path(sub_args);
+                let pods_dir = path.parent()
+                    .map(|p| p.join("Pods"))
+                    .unwrap_or(PathBuf::from("Pods"));
+                if !pods_dir.is_dir() {
+                    return Err(anyhow!("{:?} is not exists", &pods_dir));
+                }
+                let text = sub_args.value_of("text").unwrap();
+                let pattern = Regex::new(text)?;
+                let context = |name: &str| -> anyhow::Result<usize> {
+                    sub_args.value_of(name)
+                        .map(|n| n.parse::<usize>().map_err(|_| anyhow!("invalid {}: {}", name, n)))
+                        .unwrap_or(Ok(0))
+                };
+                let excludes = sub_args.value_of("excludes")
+                    .map(|s| s.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()).collect())
+                    .unwrap_or_else(Vec::new);
+                let includes =
+                    if let Some(name) = sub_args.value_of("name") {
+                        let lockfile = Lockfile::from_file(lockfile_path(sub_args)?, lenient)?;
+                        Some(dep::dep_closure(&lockfile.pods()?, name)?)
+                    } else {
+                        None
+                    };
+                let mut searcher = Searcher::new(pods_dir, pattern.clone());
+                searcher
+                    .excludes(excludes)
+                    .includes(includes)
+                    .context(context("before-context")?, context("after-context")?);
+                let matches = searcher.search()?;
+                search::printer::print_matches(&matches, &pattern, sub_args.is_present("name-only"));
                 Ok(())
             },
             Command::SUB_CLEAN => {
-                // rob.arg(Command::SUB_CLEAN);
-                // 策略一：基于 last-access 处理
-                // 策略二：基于版本号（譬如低于 5.11 版本）
-                // 策略三：基于当前项目中使用的使用状况
-                println!("暂时未实现");
+                let root = sub_args.value_of("cache")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(Cleaner::default_root);
+                if !root.is_dir() {
+                    return Err(anyhow!("{:?} is not exists", &root));
+                }
+                let split = |s: &str| -> Vec<String> {
+                    s.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()).map(|n| n.to_string()).collect()
+                };
+                let days = sub_args.value_of("days")
+                    .map(|d| d.parse::<u64>().map_err(|_| anyhow!("invalid days: {}", d)))
+                    .transpose()?;
+                let below = sub_args.value_of("below")
+                    .map(|v| Version::parse(v).ok_or_else(|| anyhow!("invalid version: {}", v)))
+                    .transpose()?;
+                let projects = sub_args.value_of("projects")
+                    .map(|s| split(s).into_iter().map(PathBuf::from).collect::<Vec<PathBuf>>());
+                if days.is_none() && below.is_none() && projects.is_none() {
+                    return Err(anyhow!("at least one of --days, --below and --projects is required"));
+                }
+                let mut cleaner = Cleaner::new(root);
+                cleaner
+                    .names(sub_args.value_of("names").map(|s| split(s).into_iter().collect()))
+                    .unused_days(days)
+                    .below(below)
+                    .projects(projects);
+                let candidates = cleaner.plan()?;
+                let freed =
+                    if sub_args.is_present("delete") {
+                        Some(clean::remove(&candidates)?)
+                    } else {
+                        None
+                    };
+                clean::printer::print_report(&candidates, freed);
+                Ok(())
+            },
+            Command::SUB_DIFF => {
+                let old = diff::load_lockfile(sub_args.value_of("OLD").unwrap(), lenient)?;
+                let new = diff::load_lockfile(sub_args.value_of("NEW").unwrap(), lenient)?;
+                let result = diff::diff(&old, &new)?;
+                if sub_args.is_present("json") {
+                    println!("{}", serde_json::to_string_pretty(&diff::to_json(&result))?);
+                } else {
+                    diff::printer::print_diff(&result);
+                }
+                Ok(())
+            },
+            Command::SUB_WHY => {
+                let path = lockfile_path(sub_args)?;
+                let target = sub_args.value_of("name").unwrap();
+                let limit = sub_args.value_of("limit")
+                    .map(|n| n.parse::<usize>().map_err(|_| anyhow!("invalid limit: {}", n)))
+                    .transpose()?
+                    .filter(|n| *n > 0);
+                dep::print_why(path, lenient, target, limit)
+            },
+            Command::SUB_INFO => {
+                let path = lockfile_path(sub_args)?;
+                let result = info::info(&Lockfile::from_file(path, lenient)?, sub_args.value_of("name").unwrap())?;
+                info::printer::print_info(&result);
+                Ok(())
+            },
+            Command::SUB_CHECK => {
+                let (checked, violations) = check::check(&Lockfile::from_file(lockfile_path(sub_args)?, lenient)?)?;
+                check::printer::print_violations(checked, &violations);
+                if !violations.is_empty() {
+                    return Err(Exit(1).into())
+                }
+                Ok(())
+            },
+            Command::SUB_GRAPH => {
+                let project = load_project(sub_args, lenient)?;
+                let mut pods = project.pods;
+                let mut roots = project.dependencies;
+                let collapse = sub_args.is_present("collapse-subspecs");
+                if collapse {
+                    pods = collapse_subspecs(&pods);
+                    for r in roots.iter_mut() {
+                        r.name = root_name(&r.name).to_string();
+                    }
+                }
+                let patterns = sub_args.values_of("name")
+                    .map(|v| v.map(|n| n.to_string()).collect::<Vec<String>>())
+                    .unwrap_or_default();
+                let centers =
+                    if patterns.is_empty() { Vec::new() } else { match_pods(&pods, &patterns, collapse)? };
+                let depth = sub_args.value_of("depth")
+                    .map(|d| d.parse::<usize>().map_err(|_| anyhow!("invalid depth: {}", d)))
+                    .transpose()?;
+                let patterns = |name: &str| -> Vec<String> {
+                    sub_args.value_of(name)
+                        .map(|s| s.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+                        .unwrap_or_default()
+                };
+                let mut builder = GraphBuilder::new(&pods);
+                builder
+                    .centers(centers, sub_args.is_present("reverse"))
+                    .max_depth(depth)
+                    .includes(&patterns("include"))?
+                    .excludes(&patterns("exclude"))?;
+                let format = graph::Format::from_name(sub_args.value_of("format").unwrap()).unwrap();
+                let text = graph::render(&builder.build(&roots)?, format, sub_args.is_present("versions"));
+                if let Some(output) = sub_args.value_of("output") {
+                    std::fs::write(output, text)?;
+                } else {
+                    print!("{}", text);
+                }
+                Ok(())
+            },
+            Command::SUB_STATS => {
+                let lockfile = Lockfile::from_file(lockfile_path(sub_args)?, lenient)?;
+                let mut pods = lockfile.pods()?;
+                if sub_args.is_present("collapse-subspecs") {
+                    pods = collapse_subspecs(&pods);
+                }
+                let top = sub_args.value_of("top").unwrap();
+                let top = top.parse::<usize>().map_err(|_| anyhow!("invalid top: {}", top))?;
+                let result = stats::stats(&pods);
+                if sub_args.is_present("json") {
+                    println!("{}", serde_json::to_string_pretty(&stats::to_json(&result))?);
+                } else {
+                    stats::printer::print_stats(&result, top);
+                }
+                Ok(())
+            },
+            Command::SUB_PATH => {
+                let all = if sub_args.is_present("all") {
+                    let max_len = sub_args.value_of("max-length").unwrap();
+                    Some(max_len.parse::<usize>().map_err(|_| anyhow!("invalid max length: {}", max_len))?)
+                } else {
+                    None
+                };
+                let found = path::print_paths(
+                    lockfile_path(sub_args)?,
+                    lenient,
+                    sub_args.value_of("FROM").unwrap(),
+                    sub_args.value_of("TO").unwrap(),
+                    all,
+                    sub_args.is_present("collapse-subspecs")
+                )?;
+                if !found {
+                    return Err(Exit(2).into())
+                }
+                Ok(())
+            },
+            Command::SUB_AFFECTED => {
+                let path = lockfile_path(sub_args)?;
+                let lockfile = Lockfile::from_file(&path, lenient)?;
+                // `:path` 相对于 Podfile.lock 所在的目录
+                let dir = path.parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or_else(|| Path::new("."));
+                let dir = std::fs::canonicalize(dir)?;
+                let files = match sub_args.value_of("base") {
+                    Some(base) => affected::changed_files_from_git(&dir, base)?,
+                    None => {
+                        // 和 `git diff --name-only` 的输出保持一致，默认相对于仓库根目录
+                        let base_dir = if sub_args.is_present("relative-to-cwd") {
+                            std::env::current_dir()?
+                        } else {
+                            affected::repo_root(&dir)?
+                        };
+                        affected::changed_files_from_reader(std::io::stdin().lock(), &base_dir)?
+                    },
+                };
+                let result = affected::affected(&lockfile, &dir, &files)?;
+                if sub_args.is_present("json") {
+                    println!("{}", serde_json::to_string_pretty(&affected::to_json(&result))?);
+                } else {
+                    affected::printer::print_affected(&result, sub_args.value_of("separator").unwrap_or("\n"));
+                }
+                Ok(())
+            },
+            Command::SUB_MERGE => {
+                let ours_path = sub_args.value_of("OURS").unwrap();
+                let base = merge::load(sub_args.value_of("BASE").unwrap())?;
+                let ours = merge::load(ours_path)?;
+                let theirs = merge::load(sub_args.value_of("THEIRS").unwrap())?;
+                let result = merge::merge(&base, &ours, &theirs)?;
+                let content = result.lockfile.to_yaml_string();
+                match sub_args.value_of("output").unwrap_or(ours_path) {
+                    "-" => print!("{}", content),
+                    output => std::fs::write(output, content)?,
+                }
+                merge::printer::print_conflicts(&result.conflicts);
+                if !result.conflicts.is_empty() {
+                    return Err(Exit(1).into())
+                }
+                Ok(())
+            },
+            Command::SUB_STATUS => {
+                let path = lockfile_path(sub_args)?;
+                let pods_dir = match sub_args.value_of("pods") {
+                    Some(dir) => PathBuf::from(dir),
+                    None => path.with_file_name("Pods"),
+                };
+                if !status::print_status(&path, &pods_dir, lenient)? {
+                    return Err(Exit(1).into())
+                }
+                Ok(())
+            },
+            Command::SUB_SIZE => {
+                let path = lockfile_path(sub_args)?;
+                let pods_dir = match sub_args.value_of("pods") {
+                    Some(dir) => PathBuf::from(dir),
+                    None => path.with_file_name("Pods"),
+                };
+                if !pods_dir.is_dir() {
+                    return Err(anyhow!("{:?} is not exists, run `pod install` first", pods_dir));
+                }
+                let top = sub_args.value_of("top").unwrap();
+                let top = top.parse::<usize>().map_err(|_| anyhow!("invalid top: {}", top))?;
+                let key = size::SortKey::from_name(sub_args.value_of("sort").unwrap()).unwrap();
+                let mut result = size::sizes(&Lockfile::from_file(&path, lenient)?, &pods_dir)?;
+                size::sort(&mut result, key);
+                if sub_args.is_present("json") {
+                    println!("{}", serde_json::to_string_pretty(&size::to_json(&result))?);
+                } else {
+                    size::printer::print_sizes(&result, top);
+                }
+                Ok(())
+            },
+            Command::SUB_LICENSES => {
+                let path = lockfile_path(sub_args)?;
+                let pods_dir = match sub_args.value_of("pods") {
+                    Some(dir) => PathBuf::from(dir),
+                    None => path.with_file_name("Pods"),
+                };
+                if !pods_dir.is_dir() {
+                    return Err(anyhow!("{:?} is not exists, run `pod install` first", pods_dir));
+                }
+                let dir = path.parent().unwrap_or_else(|| Path::new("."));
+                let result = licenses::licenses(
+                    &Lockfile::from_file(&path, lenient)?,
+                    dir,
+                    &pods_dir,
+                    sub_args.is_present("development")
+                )?;
+                let format = licenses::Format::from_name(sub_args.value_of("format").unwrap()).unwrap();
+                let text = licenses::render(&result, format);
+                if let Some(output) = sub_args.value_of("output") {
+                    std::fs::write(output, text)?;
+                } else {
+                    print!("{}", text);
+                }
+                let flagged = licenses::printer::print_flags(&result);
+                if flagged > 0 && sub_args.is_present("strict") {
+                    return Err(Exit(1).into())
+                }
+                Ok(())
+            },
+            Command::SUB_LINT => {
+                let path = lockfile_path(sub_args)?;
+                let rules_path = match sub_args.value_of("rules") {
+                    Some(p) => PathBuf::from(p),
+                    None => path.with_file_name(lint::DEFAULT_RULES),
+                };
+                if !rules_path.exists() {
+                    return Err(anyhow!("{:?} is not exists", rules_path));
+                }
+                let rules = lint::Rules::from_file(&rules_path)?;
+                let mut pods = Lockfile::from_file(&path, lenient)?.pods()?;
+                if sub_args.is_present("collapse-subspecs") {
+                    pods = collapse_subspecs(&pods);
+                }
+                let result = lint::lint(&rules, &pods)?;
+                lint::printer::print_lint(&result);
+                if !result.violations.is_empty() {
+                    return Err(Exit(1).into())
+                }
+                Ok(())
+            },
+            Command::SUB_IMPORTS => {
+                let path = lockfile_path(sub_args)?;
+                let modules = module_mappings(sub_args)?;
+                // `:path` 相对于 Podfile.lock 所在的目录
+                let dir = path.parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or_else(|| Path::new("."));
+                let dir = std::fs::canonicalize(dir)?;
+                let result = imports::imports(&Lockfile::from_file(&path, lenient)?, &dir, &modules)?;
+                imports::printer::print_imports(&result);
+                Ok(())
+            },
+            Command::SUB_UNUSED => {
+                let path = lockfile_path(sub_args)?;
+                let modules = module_mappings(sub_args)?;
+                let mut roots = Vec::new();
+                for r in sub_args.values_of("ROOTS").unwrap() {
+                    let root = PathBuf::from(r);
+                    if !root.is_dir() {
+                        return Err(anyhow!("{:?} is not exists", root));
+                    }
+                    roots.push(root);
+                }
+                // `:path` 相对于 Podfile.lock 所在的目录
+                let dir = path.parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or_else(|| Path::new("."));
+                let dir = std::fs::canonicalize(dir)?;
+                let base = std::fs::canonicalize(".")?;
+                let result = unused::unused(&Lockfile::from_file(&path, lenient)?, &roots, &dir, &base, &modules)?;
+                unused::printer::print_unused(&result);
                 Ok(())
             },
             _ => { Ok(()) }
         }
     }
-}
\ No newline at end of file
+}
+
+// 解析 `--path`，默认为当前目录下的 Podfile.lock
+fn resolve_lockfile_path(args: &Args) -> PathBuf {
+    if let Some(p) = args.value_of("path") {
+        PathBuf::from(p)
+    } else {
+        let mut p = std::env::current_dir()
+            .unwrap_or(PathBuf::from("."));
+        p.push("Podfile.lock");
+        p
+    }
+}
+
+fn lockfile_path(args: &Args) -> anyhow::Result<PathBuf> {
+    let path = resolve_lockfile_path(args);
+    if !path.exists() {
+        return Err(anyhow!("{:?} is not exists", &path));
+    }
+    Ok(path)
+}
+
+// `--module Module=Pod`
+fn module_mappings(args: &Args) -> anyhow::Result<HashMap<String, String>> {
+    let mut ret = HashMap::new();
+    for m in args.values_of("module").into_iter().flatten() {
+        let (module, pod) = m.split_once('=')
+            .filter(|(m, p)| !m.trim().is_empty() && !p.trim().is_empty())
+            .ok_or_else(|| anyhow!("invalid module mapping: {}, should be like `Module=Pod`", m))?;
+        ret.insert(module.trim().to_string(), pod.trim().to_string());
+    }
+    Ok(ret)
+}
+
+// Podfile.lock 加上 `--resolved`、`--manifest` 指定的 SwiftPM 包
+// 指定了 SwiftPM 包而当前目录下没有 Podfile.lock 时，只分析 SwiftPM 的依赖
+fn load_project(args: &Args, lenient: bool) -> anyhow::Result<Project> {
+    let resolved = args.value_of("resolved").map(PathBuf::from);
+    let manifests = args.values_of("manifest")
+        .map(|v| v.map(PathBuf::from).collect::<Vec<PathBuf>>())
+        .unwrap_or_default();
+    for p in resolved.iter().chain(manifests.iter()) {
+        if !p.exists() {
+            return Err(anyhow!("{:?} is not exists", p));
+        }
+    }
+    let spm_only = (resolved.is_some() || !manifests.is_empty())
+        && args.value_of("path").is_none()
+        && !resolve_lockfile_path(args).exists();
+    let lockfile = if spm_only { None } else { Some(lockfile_path(args)?) };
+    Project::load(lockfile.as_deref(), lenient, resolved.as_deref(), &manifests)
+}
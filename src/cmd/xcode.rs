@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use anyhow::anyhow;
+use clap::arg;
+use crate::cmd::{Cmd, CmdResult, Args, Conf};
+use crate::lib::xcode::files;
+use crate::lib::xcode::project::{self, PhaseKind, Project};
+
+pub(super) struct Command;
+
+impl Command {
+    const NAME: &'static str = "xcode";
+
+    const SUB_TARGETS: &'static str = "targets";
+    const SUB_FILES: &'static str = "files";
+    const SUB_ORPHANS: &'static str = "orphans";
+
+    fn sub_cmd_conf_list(&self) -> Vec<Conf> {
+        let arg_project = || {
+            arg!(-p --project <PATH> "Path to .xcodeproj or its project.pbxproj. Defaults to the only .xcodeproj in current directory")
+                .required(false)
+        };
+        let targets = Conf::new(Command::SUB_TARGETS)
+            .args(&[
+                arg_project(),
+                arg!(--json "Print as JSON, including build settings").required(false),
+            ])
+            .about("List targets with their build phases, dependencies and configurations");
+
+        let files = Conf::new(Command::SUB_FILES)
+            .args(&[
+                arg!(<TARGET> "Target name"),
+                arg_project(),
+                arg!(--phase <PHASE> "Only list files of the build phase").required(false)
+                    .possible_values(PhaseKind::NAMES),
+            ])
+            .about("List files referenced by build phases of a target");
+
+        let orphans = Conf::new(Command::SUB_ORPHANS)
+            .args(&[
+                arg_project(),
+                arg!(-e --extensions <EXTS> "File extensions to check, separated by comma").required(false)
+                    .default_value("swift,m,mm,c,cc,cpp,cxx"),
+            ])
+            .about("Find source files on disk that no target references");
+        vec![targets, files, orphans]
+    }
+}
+
+impl Cmd for Command {
+    fn key(&self) -> String {
+        Command::NAME.to_string()
+    }
+
+    fn conf(&self) -> Conf {
+        Conf::new(Command::NAME)
+            .arg_required_else_help(true)
+            .subcommands(self.sub_cmd_conf_list())
+            .about("Xcode Project Utilities")
+    }
+
+    fn process(&self, args: &Args) -> CmdResult {
+        let (sub_cmd, sub_args) = args.subcommand().unwrap();
+        let project = Project::open(&project_path(sub_args)?)?;
+        match sub_cmd {
+            Command::SUB_TARGETS => {
+                if sub_args.is_present("json") {
+                    println!("{}", serde_json::to_string_pretty(&project::to_json(&project))?);
+                } else {
+                    project::printer::print_targets(&project);
+                }
+                Ok(())
+            },
+            Command::SUB_FILES => {
+                let target = project.target(sub_args.value_of("TARGET").unwrap())?;
+                let phase = sub_args.value_of("phase").and_then(PhaseKind::from_name);
+                files::printer::print_files(&files::target_files(&project, target, phase));
+                Ok(())
+            },
+            Command::SUB_ORPHANS => {
+                let extensions = sub_args.value_of("extensions")
+                    .unwrap()
+                    .split(',')
+                    .map(|e| e.trim().trim_start_matches('.').to_string())
+                    .filter(|e| !e.is_empty())
+                    .collect::<Vec<String>>();
+                files::printer::print_orphans(&files::orphans(&project, &extensions)?);
+                Ok(())
+            },
+            _ => { Ok(()) }
+        }
+    }
+}
+
+// `--project`，默认为当前目录下唯一的 .xcodeproj
+fn project_path(args: &Args) -> anyhow::Result<PathBuf> {
+    if let Some(p) = args.value_of("project") {
+        let path = PathBuf::from(p);
+        if !path.exists() {
+            return Err(anyhow!("{:?} is not exists", &path));
+        }
+        return Ok(path)
+    }
+    let dir = std::env::current_dir().unwrap_or(PathBuf::from("."));
+    let mut found = std::fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "xcodeproj").unwrap_or(false))
+        .collect::<Vec<PathBuf>>();
+    found.sort();
+    match found.len() {
+        0 => Err(anyhow!("cannot find any .xcodeproj in {:?}", dir)),
+        1 => Ok(found.remove(0)),
+        _ => {
+            let names = found.iter()
+                .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+                .collect::<Vec<String>>();
+            Err(anyhow!("more than one .xcodeproj found, specify one with --project: {}", names.join(", ")))
+        },
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::anyhow;
+use crate::lib::dep::{Ecosystem, Graph};
+
+// Cargo.lock
+// 只读取 `[[package]]`（v1 还有 `[root]`）里的 name、version、source 和 dependencies，不依赖 TOML 库
+// 同名的 crate 有多个版本时，节点名为 `name@version`，与 `cargo tree` 的写法一致
+//
+// [[package]]
+// name = "regex"
+// version = "1.5.4"
+// source = "registry+https://github.com/rust-lang/crates.io-index"
+// dependencies = [
+//  "aho-corasick",
+//  "memchr 2.4.1",
+// ]
+
+#[derive(Default)]
+struct Package {
+    name: String,
+    version: Option<String>,
+    source: Option<String>,
+    // `name`、`name version` 或者 `name version (source)`
+    dependencies: Vec<String>,
+}
+
+pub(crate) fn read(path: &Path) -> anyhow::Result<Graph> {
+    let text = std::fs::read_to_string(path)?;
+    let packages = parse(&text).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    Ok(graph(&packages))
+}
+
+fn graph(packages: &[Package]) -> Graph {
+    let counts = packages.iter().fold(HashMap::<&str, usize>::new(), |mut m, p| {
+        *m.entry(p.name.as_str()).or_default() += 1;
+        m
+    });
+    let node_name = |name: &str, version: Option<&str>| -> String {
+        match version {
+            Some(v) if counts.get(name).copied().unwrap_or(0) > 1 => format!("{}@{}", name, v),
+            _ => name.to_string(),
+        }
+    };
+
+    let mut graph = Graph::new(Ecosystem::Cargo);
+    for p in packages {
+        let source = match p.source.as_deref() {
+            None => Some("path"),
+            Some(s) if s.starts_with("git+") => Some("git"),
+            Some(s) if s == CRATES_IO => None,
+            Some(_) => Some("registry"),
+        };
+        graph.add_node(&node_name(&p.name, p.version.as_deref()), p.version.clone(), source);
+    }
+    for p in packages {
+        let parent = node_name(&p.name, p.version.as_deref());
+        for d in &p.dependencies {
+            let mut parts = d.split_whitespace();
+            let name = parts.next().unwrap_or_default();
+            graph.add_edge(&parent, &node_name(name, parts.next()));
+        }
+    }
+    graph
+}
+
+const CRATES_IO: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+fn parse(text: &str) -> anyhow::Result<Vec<Package>> {
+    let mut ret = Vec::<Package>::new();
+    let mut in_package = false;
+    let mut in_dependencies = false;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue
+        }
+        if in_dependencies {
+            in_dependencies = !line.starts_with(']');
+            if let (Some(p), Some(d)) = (ret.last_mut(), unquote(line.trim_end_matches(','))) {
+                p.dependencies.push(d.to_string());
+            }
+            continue
+        }
+        if line.starts_with('[') {
+            in_package = line == "[[package]]" || line == "[root]";
+            if in_package {
+                ret.push(Package::default());
+            }
+            continue
+        }
+        if !in_package {
+            continue
+        }
+        let (key, value) = line.split_once('=')
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .ok_or_else(|| anyhow!("line {}: unexpected text: `{}`", i + 1, line))?;
+        let package = ret.last_mut().unwrap();
+        match key {
+            "name" => package.name = unquote(value).unwrap_or_default().to_string(),
+            "version" => package.version = unquote(value).map(|v| v.to_string()),
+            "source" => package.source = unquote(value).map(|v| v.to_string()),
+            "dependencies" => {
+                let inline = value.strip_prefix('[')
+                    .ok_or_else(|| anyhow!("line {}: unexpected text: `{}`", i + 1, line))?;
+                // `dependencies = [` 后面跟多行，也可能写在一行里
+                in_dependencies = !inline.trim_end().ends_with(']');
+                let items = inline.trim_end().trim_end_matches(']');
+                package.dependencies.extend(
+                    items.split(',').filter_map(|d| unquote(d.trim())).map(|d| d.to_string())
+                );
+            },
+            _ => {},
+        }
+    }
+    if let Some(p) = ret.iter().find(|p| p.name.is_empty()) {
+        return Err(anyhow!("package without name, version {:?}", p.version))
+    }
+    Ok(ret)
+}
+
+fn unquote(text: &str) -> Option<&str> {
+    text.strip_prefix('"')?.strip_suffix('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::dep::{DepGraph, Graph, TravelMode};
+    use super::{graph, parse};
+
+    fn read(text: &str) -> Graph {
+        graph(&parse(text).unwrap())
+    }
+
+    #[test]
+    fn v1_root_and_metadata() {
+        let g = read(r#"
+[root]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "log 0.4.14 (registry+https://github.com/rust-lang/crates.io-index)",
+ "util 0.1.0",
+]
+
+[[package]]
+name = "log"
+version = "0.4.14"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "util"
+version = "0.1.0"
+
+[metadata]
+"checksum log 0.4.14 (registry+https://github.com/rust-lang/crates.io-index)" = "51b9bbe6"
+"#);
+        assert_eq!(g.names(), ["app", "log", "util"]);
+        assert_eq!(g.edges("app", TravelMode::Children), ["log", "util"]);
+        assert_eq!(g.version("log"), Some("0.4.14"));
+        assert_eq!(g.source("log"), None);
+        assert_eq!(g.source("util"), Some("path"));
+    }
+
+    #[test]
+    fn inline_dependencies() {
+        let g = read(r#"
+version = 3
+
+[[package]]
+name = "a"
+version = "1.0.0"
+dependencies = ["b", "c"]
+
+[[package]]
+name = "b"
+version = "1.0.0"
+source = "git+https://github.com/x/b#0123abcd"
+dependencies = []
+
+[[package]]
+name = "c"
+version = "1.0.0"
+source = "sparse+https://example.com/index/"
+"#);
+        assert_eq!(g.edges("a", TravelMode::Children), ["b", "c"]);
+        assert!(g.edges("b", TravelMode::Children).is_empty());
+        assert_eq!(g.edges("c", TravelMode::Parents), ["a"]);
+        assert_eq!(g.source("b"), Some("git"));
+        assert_eq!(g.source("c"), Some("registry"));
+    }
+
+    #[test]
+    fn duplicated_names_carry_versions() {
+        let g = read(r#"
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "rand 0.7.3",
+ "rand 0.8.5",
+ "serde",
+]
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+
+[[package]]
+name = "serde"
+version = "1.0.0"
+"#);
+        assert_eq!(g.names(), ["app", "rand@0.7.3", "rand@0.8.5", "serde"]);
+        assert_eq!(g.edges("app", TravelMode::Children), ["rand@0.7.3", "rand@0.8.5", "serde"]);
+        assert_eq!(g.version("rand@0.8.5"), Some("0.8.5"));
+    }
+
+    #[test]
+    fn package_without_name_is_an_error() {
+        assert!(parse("[[package]]\nversion = \"1.0\"\n").is_err());
+        assert!(parse("[[package]]\nname\n").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::Path;
+use anyhow::{anyhow, bail};
+use crate::lib::dep::{Ecosystem, Graph};
+
+// Gemfile.lock
+// GEM、GIT、PATH 等 section 的 `specs:` 下，缩进 4 格为 gem 和解析出的版本，缩进 6 格为它的依赖
+//
+// GEM
+//   remote: https://rubygems.org/
+//   specs:
+//     activesupport (7.0.4)
+//       concurrent-ruby (~> 1.0, >= 1.0.2)
+
+pub(crate) fn read(path: &Path) -> anyhow::Result<Graph> {
+    let text = std::fs::read_to_string(path)?;
+    parse(&text, &path.display().to_string())
+}
+
+fn parse(text: &str, name: &str) -> anyhow::Result<Graph> {
+    let mut graph = Graph::new(Ecosystem::Bundler);
+    // 当前 section 的来源，不在 specs 里时为 `None`
+    let mut source = None;
+    let mut in_specs = false;
+    let mut current = None::<String>;
+    for (i, line) in text.lines().enumerate() {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let content = line.trim();
+        if content.is_empty() {
+            continue
+        }
+        match indent {
+            0 => {
+                source = match content {
+                    "GEM" => Some(None),
+                    "GIT" => Some(Some("git")),
+                    "PATH" => Some(Some("path")),
+                    "PLUGIN SOURCE" => Some(Some("plugin")),
+                    _ => None,
+                };
+                in_specs = false;
+                current = None;
+            },
+            2 => in_specs = source.is_some() && content == "specs:",
+            4 if in_specs => {
+                let (gem, vers) = split_name_and_paren(content)
+                    .ok_or_else(|| anyhow!("{}:{}: unexpected text: `{}`", name, i + 1, content))?;
+                // 平台相关的 gem 带有后缀，譬如 `1.13.10-x86_64-darwin`，按同一个 gem 处理
+                let vers = vers.map(|v| v.split('-').next().unwrap_or(v).to_string());
+                graph.add_node(gem, vers, source.flatten());
+                current = Some(gem.to_string());
+            },
+            6 if in_specs => {
+                let parent = match &current {
+                    Some(c) => c,
+                    None => bail!("{}:{}: dependency without gem: `{}`", name, i + 1, content),
+                };
+                let (dep, _) = split_name_and_paren(content)
+                    .ok_or_else(|| anyhow!("{}:{}: unexpected text: `{}`", name, i + 1, content))?;
+                graph.add_edge(parent, dep);
+            },
+            _ => {},
+        }
+    }
+    Ok(graph)
+}
+
+// `activesupport (7.0.4)` -> (`activesupport`, Some(`7.0.4`))
+fn split_name_and_paren(text: &str) -> Option<(&str, Option<&str>)> {
+    match text.split_once(" (") {
+        Some((name, rest)) => Some((name, Some(rest.strip_suffix(')')?))),
+        None if !text.contains(' ') => Some((text, None)),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::dep::{DepGraph, TravelMode};
+    use super::parse;
+
+    const LOCKFILE: &str = "\
+GIT
+  remote: https://github.com/rails/rails.git
+  revision: 0123abcd
+  specs:
+    rails (7.1.0.alpha)
+      activesupport (= 7.1.0.alpha)
+    activesupport (7.1.0.alpha)
+      concurrent-ruby (~> 1.0, >= 1.0.2)
+
+PATH
+  remote: engines/billing
+  specs:
+    billing (0.1.0)
+      rails
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    concurrent-ruby (1.2.2)
+    nokogiri (1.13.10-x86_64-darwin)
+      racc (~> 1.4)
+    nokogiri (1.13.10-arm64-darwin)
+      racc (~> 1.4)
+    racc (1.6.2)
+
+PLATFORMS
+  arm64-darwin
+  x86_64-darwin
+
+DEPENDENCIES
+  billing!
+  nokogiri
+
+BUNDLED WITH
+   2.4.10
+";
+
+    #[test]
+    fn sections_give_sources() {
+        let g = parse(LOCKFILE, "Gemfile.lock").unwrap();
+        assert_eq!(g.source("rails"), Some("git"));
+        assert_eq!(g.source("billing"), Some("path"));
+        assert_eq!(g.source("racc"), None);
+        assert_eq!(g.version("rails"), Some("7.1.0.alpha"));
+        assert_eq!(g.edges("billing", TravelMode::Children), ["rails"]);
+        assert_eq!(g.edges("concurrent-ruby", TravelMode::Parents), ["activesupport"]);
+    }
+
+    #[test]
+    fn platform_suffix_is_stripped() {
+        let g = parse(LOCKFILE, "Gemfile.lock").unwrap();
+        assert_eq!(g.version("nokogiri"), Some("1.13.10"));
+        assert_eq!(g.edges("nokogiri", TravelMode::Children), ["racc"]);
+        // PLATFORMS、DEPENDENCIES 下的内容不是 gem
+        assert!(!g.contains("arm64-darwin"));
+        assert_eq!(g.names().len(), 6);
+    }
+
+    #[test]
+    fn dependency_before_gem_is_an_error() {
+        let text = "GEM\n  specs:\n      racc (~> 1.4)\n";
+        let e = parse(text, "Gemfile.lock").err().unwrap();
+        assert!(e.to_string().starts_with("Gemfile.lock:3:"));
+    }
+}
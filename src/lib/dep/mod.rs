@@ -0,0 +1,439 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use anyhow::bail;
+
+pub(crate) mod pattern;
+pub(crate) mod gemfile;
+pub(crate) mod cargo;
+
+// 与包管理器无关的依赖图
+// Podfile.lock、Package.resolved、Gemfile.lock、Cargo.lock 读出来的依赖都按同样的方式遍历和展示
+
+// 节点来自哪种包管理器
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Ecosystem { CocoaPods, SwiftPM, Bundler, Cargo }
+
+impl Ecosystem {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Ecosystem::CocoaPods => "cocoapods",
+            Ecosystem::SwiftPM => "swiftpm",
+            Ecosystem::Bundler => "bundler",
+            Ecosystem::Cargo => "cargo",
+        }
+    }
+
+    // 输出数量时用的单位
+    pub(crate) fn unit(self) -> &'static str {
+        match self {
+            Ecosystem::CocoaPods => "pods",
+            Ecosystem::SwiftPM => "packages",
+            Ecosystem::Bundler => "gems",
+            Ecosystem::Cargo => "crates",
+        }
+    }
+}
+
+// 按文件名识别的 lockfile
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format { Podfile, PackageResolved, Gemfile, Cargo }
+
+impl Format {
+    // 未指定文件时按这个顺序在当前目录下查找
+    pub(crate) const FILE_NAMES: [&'static str; 4] = ["Podfile.lock", "Package.resolved", "Gemfile.lock", "Cargo.lock"];
+
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "Podfile.lock" => Some(Format::Podfile),
+            "Package.resolved" => Some(Format::PackageResolved),
+            "Gemfile.lock" => Some(Format::Gemfile),
+            "Cargo.lock" => Some(Format::Cargo),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum TravelMode { Parents, Children }
+
+// 遍历和展示只依赖这几个方法
+pub(crate) trait DepGraph {
+    // 所有节点的名字，已排序
+    fn names(&self) -> Vec<&str>;
+    fn contains(&self, name: &str) -> bool;
+    fn version(&self, name: &str) -> Option<&str>;
+    // `name` 的依赖或者反向依赖，只包含图里存在的节点，已排序
+    fn edges(&self, name: &str, mode: TravelMode) -> Vec<&str>;
+}
+
+// Gemfile.lock 和 Cargo.lock 的节点，名字为 `Graph::nodes` 的 key
+pub(crate) struct Node {
+    pub(crate) vers: Option<String>,
+    // 来源的类型，譬如 `path`、`git`，来自默认的源时为 `None`
+    pub(crate) source: Option<&'static str>,
+    pub(crate) parents: BTreeSet<String>,
+    pub(crate) children: BTreeSet<String>,
+}
+
+pub(crate) struct Graph {
+    pub(crate) ecosystem: Ecosystem,
+    pub(crate) nodes: HashMap<String, Node>,
+}
+
+impl Graph {
+    pub(crate) fn new(ecosystem: Ecosystem) -> Self {
+        Graph { ecosystem, nodes: HashMap::new() }
+    }
+
+    // 已经存在时只补上版本和来源
+    pub(crate) fn add_node(&mut self, name: &str, vers: Option<String>, source: Option<&'static str>) {
+        let node = self.node_mut(name);
+        if vers.is_some() {
+            node.vers = vers;
+        }
+        if source.is_some() {
+            node.source = source;
+        }
+    }
+
+    pub(crate) fn add_edge(&mut self, parent: &str, child: &str) {
+        if parent == child {
+            return
+        }
+        self.node_mut(parent).children.insert(child.to_string());
+        self.node_mut(child).parents.insert(parent.to_string());
+    }
+
+    pub(crate) fn source(&self, name: &str) -> Option<&'static str> {
+        self.nodes.get(name).and_then(|n| n.source)
+    }
+
+    fn node_mut(&mut self, name: &str) -> &mut Node {
+        self.nodes.entry(name.to_string()).or_insert_with(|| Node {
+            vers: None,
+            source: None,
+            parents: BTreeSet::new(),
+            children: BTreeSet::new(),
+        })
+    }
+}
+
+impl DepGraph for Graph {
+    fn names(&self) -> Vec<&str> {
+        let mut names = self.nodes.keys().map(|n| n.as_str()).collect::<Vec<&str>>();
+        names.sort();
+        names
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.nodes.contains_key(name)
+    }
+
+    fn version(&self, name: &str) -> Option<&str> {
+        self.nodes.get(name).and_then(|n| n.vers.as_deref())
+    }
+
+    fn edges(&self, name: &str, mode: TravelMode) -> Vec<&str> {
+        let node = match self.nodes.get(name) {
+            Some(n) => n,
+            None => return Vec::new(),
+        };
+        let edges = match mode {
+            TravelMode::Parents => &node.parents,
+            TravelMode::Children => &node.children,
+        };
+        edges.iter().map(|e| e.as_str()).filter(|e| self.nodes.contains_key(*e)).collect()
+    }
+}
+
+// 收集 target 的依赖闭包（`TravelMode::Parents` 时为反向依赖），包含 target 自身
+pub(crate) fn closure(
+    graph: &dyn DepGraph,
+    target: &str,
+    mode: TravelMode
+) -> anyhow::Result<HashSet<String>> {
+    if !graph.contains(target) {
+        bail!("Cannot find {}", target);
+    }
+    let mut visited = HashSet::from([target]);
+    let mut queue = VecDeque::from([target]);
+    while let Some(name) = queue.pop_front() {
+        for n in graph.edges(name, mode) {
+            if visited.insert(n) {
+                queue.push_back(n);
+            }
+        }
+    }
+    Ok(visited.into_iter().map(|n| n.to_string()).collect())
+}
+
+// 以树形（`combined` 时为合并的列表）展示 `targets` 的依赖或者反向依赖
+// `source_of` 给出节点旁边标注的来源，`unit` 为数量的单位，譬如 `pods`
+pub(crate) fn print_deps(
+    graph: &dyn DepGraph,
+    targets: &[String],
+    max_depth: usize,
+    combined: bool,
+    mode: TravelMode,
+    source_of: &dyn Fn(&str) -> Option<&'static str>,
+    unit: &str
+) {
+    if combined {
+        let rows = closure_rows(graph, targets, max_depth, mode, source_of);
+        printer::print_closure(targets, &rows, targets.len() > 1, unit);
+        return
+    }
+    for (i, target) in targets.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        printer::print_tree(&tree_nodes(graph, target, max_depth, mode, source_of));
+    }
+}
+
+// 所有目标的闭包合并成一个列表，不含目标自身
+fn closure_rows(
+    graph: &dyn DepGraph,
+    targets: &[String],
+    max_depth: usize,
+    mode: TravelMode,
+    source_of: &dyn Fn(&str) -> Option<&'static str>
+) -> Vec<ClosureRow> {
+    // name -> 经由哪些目标到达
+    let mut reached = BTreeMap::<&str, BTreeSet<&str>>::new();
+    for target in targets {
+        let mut depths = HashMap::from([(target.as_str(), 0)]);
+        let mut queue = VecDeque::from([target.as_str()]);
+        while let Some(name) = queue.pop_front() {
+            let depth = depths[name];
+            if depth >= max_depth {
+                continue
+            }
+            for n in graph.edges(name, mode) {
+                if !depths.contains_key(n) {
+                    depths.insert(n, depth + 1);
+                    queue.push_back(n);
+                    reached.entry(n).or_default().insert(target.as_str());
+                }
+            }
+        }
+    }
+    reached.into_iter()
+        .map(|(name, via)| ClosureRow {
+            name: name.to_string(),
+            vers: graph.version(name).map(|v| v.to_string()),
+            source: source_of(name),
+            via: via.into_iter().map(|v| v.to_string()).collect(),
+        })
+        .collect()
+}
+
+// 先序排列的树，已经展开过的子树只标记 `(*)`
+fn tree_nodes(
+    graph: &dyn DepGraph,
+    target: &str,
+    max_depth: usize,
+    mode: TravelMode,
+    source_of: &dyn Fn(&str) -> Option<&'static str>
+) -> Vec<TreeNode> {
+    let node = |name: &str, depth: usize, has_next_sibling: bool, repeated: bool| -> TreeNode {
+        TreeNode {
+            name: name.to_string(),
+            vers: graph.version(name).map(|v| v.to_string()),
+            source: source_of(name),
+            depth,
+            has_next_sibling,
+            repeated,
+        }
+    };
+    let mut nodes = vec![node(target, 0, false, false)];
+    let mut expanded = HashSet::from([target.to_string()]);
+    let mut stack = vec![(target.to_string(), 0)];
+    while let Some((name, depth)) = stack.pop() {
+        let next = graph.edges(&name, mode);
+        if depth > 0 {
+            let has_next_sibling = stack.last().map(|(_, d)| *d == depth).unwrap_or(false);
+            let repeated = !next.is_empty() && expanded.contains(&name);
+            nodes.push(node(&name, depth, has_next_sibling, repeated));
+            if repeated || depth >= max_depth {
+                continue
+            }
+            expanded.insert(name.clone());
+        }
+        // 逆序入栈，出栈时按名字排序
+        for n in next.into_iter().rev() {
+            stack.push((n.to_string(), depth + 1));
+        }
+    }
+    nodes
+}
+
+// 合并闭包里的一行
+struct ClosureRow {
+    name: String,
+    vers: Option<String>,
+    source: Option<&'static str>,
+    via: Vec<String>,
+}
+
+// 树上的一行
+struct TreeNode {
+    name: String,
+    vers: Option<String>,
+    // 来源的类型，譬如 `:path`
+    source: Option<&'static str>,
+    depth: usize,
+    has_next_sibling: bool,
+    // 子树已经在前面展开过
+    repeated: bool,
+}
+
+mod printer {
+    use ansi_term::{Colour, Style};
+    use crate::lib::tree::TreePrefix;
+    use super::{ClosureRow, TreeNode};
+
+    pub(super) fn print_tree(nodes: &[TreeNode]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let mut tree_prefix = TreePrefix::new();
+        for node in nodes {
+            let mut text =
+                if node.depth == 0 {
+                    Style::from(Colour::Green).bold().paint(&node.name).to_string()
+                } else {
+                    let prefix = tree_prefix.next(node.depth, node.has_next_sibling);
+                    format!("{}{}", Style::from(light_gray).paint(prefix), node.name)
+                };
+            if let Some(v) = &node.vers {
+                text.push_str(&format!(" {}", Style::from(Colour::Yellow).paint(v)));
+            }
+            if let Some(s) = node.source {
+                text.push_str(&Style::from(light_gray).paint(format!(" ({})", s)).to_string());
+            }
+            if node.repeated {
+                text.push_str(&Style::from(light_gray).paint(" (*)").to_string());
+            }
+            println!("{}", text);
+        }
+    }
+
+    pub(super) fn print_closure(targets: &[String], rows: &[ClosureRow], show_via: bool, unit: &str) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        println!(
+            "{} {}",
+            Style::from(Colour::Green).bold().paint(targets.join(", ")),
+            Style::from(light_gray).paint(format!("({} {})", rows.len(), unit))
+        );
+        for row in rows {
+            let mut text = row.name.clone();
+            if let Some(v) = &row.vers {
+                text.push_str(&format!(" {}", Style::from(Colour::Yellow).paint(v)));
+            }
+            if let Some(s) = row.source {
+                text.push_str(&Style::from(light_gray).paint(format!(" ({})", s)).to_string());
+            }
+            if show_via {
+                let via = format!(" via {}", row.via.join(", "));
+                text.push_str(&Style::from(light_gray).paint(via).to_string());
+            }
+            println!("    {}", text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closure, closure_rows, tree_nodes, Ecosystem, Graph, TravelMode};
+
+    // A、B 都依赖 C，B、C 依赖 D；D 和 E 互相依赖
+    fn graph() -> Graph {
+        let mut graph = Graph::new(Ecosystem::Cargo);
+        for (parent, child) in [("A", "C"), ("B", "C"), ("B", "D"), ("C", "D"), ("D", "E"), ("E", "D")] {
+            graph.add_edge(parent, child);
+        }
+        graph.add_node("C", Some("2.1.0".to_string()), Some("path"));
+        graph.add_node("F", None, None);
+        graph
+    }
+
+    fn tree(graph: &Graph, target: &str, max_depth: usize, mode: TravelMode) -> Vec<String> {
+        tree_nodes(graph, target, max_depth, mode, &|n| graph.source(n))
+            .iter()
+            .map(|n| {
+                let mut text = format!("{}{}", "  ".repeat(n.depth), n.name);
+                if n.repeated {
+                    text.push_str(" (*)");
+                }
+                text
+            })
+            .collect()
+    }
+
+    fn sorted(closure: std::collections::HashSet<String>) -> Vec<String> {
+        let mut ret = closure.into_iter().collect::<Vec<String>>();
+        ret.sort();
+        ret
+    }
+
+    #[test]
+    fn closures_follow_cycles() {
+        let graph = graph();
+        assert_eq!(sorted(closure(&graph, "C", TravelMode::Children).unwrap()), ["C", "D", "E"]);
+        assert_eq!(sorted(closure(&graph, "C", TravelMode::Parents).unwrap()), ["A", "B", "C"]);
+        assert_eq!(sorted(closure(&graph, "E", TravelMode::Parents).unwrap()), ["A", "B", "C", "D", "E"]);
+        assert_eq!(sorted(closure(&graph, "F", TravelMode::Children).unwrap()), ["F"]);
+        assert!(closure(&graph, "Z", TravelMode::Children).is_err());
+    }
+
+    #[test]
+    fn repeated_subtrees_are_marked() {
+        let mut graph = graph();
+        // D、E 的环到 D 为止
+        assert_eq!(tree(&graph, "A", usize::MAX, TravelMode::Children), ["A", "  C", "    D", "      E", "        D (*)"]);
+        // B 下面的 C 已经展开过
+        graph.add_edge("A", "B");
+        assert_eq!(tree(&graph, "A", usize::MAX, TravelMode::Children), [
+            "A",
+            "  B",
+            "    C",
+            "      D",
+            "        E",
+            "          D (*)",
+            "    D (*)",
+            "  C (*)",
+        ]);
+    }
+
+    #[test]
+    fn leaves_are_not_marked_and_depth_is_limited() {
+        let graph = graph();
+        let text = tree(&graph, "E", usize::MAX, TravelMode::Parents);
+        assert_eq!(text, ["E", "  D", "    B", "    C", "      A", "      B", "    E (*)"]);
+        assert_eq!(tree(&graph, "A", 2, TravelMode::Children), ["A", "  C", "    D"]);
+    }
+
+    #[test]
+    fn siblings_versions_and_sources() {
+        let graph = graph();
+        let nodes = tree_nodes(&graph, "B", usize::MAX, TravelMode::Children, &|n| graph.source(n));
+        let c = &nodes[1];
+        assert_eq!((c.name.as_str(), c.source, c.has_next_sibling), ("C", Some("path"), true));
+        assert_eq!(c.vers.as_deref(), Some("2.1.0"));
+        let last = nodes.iter().filter(|n| n.depth == 1).last().unwrap();
+        assert_eq!((last.name.as_str(), last.has_next_sibling), ("D", false));
+    }
+
+    #[test]
+    fn combined_closure_records_targets() {
+        let graph = graph();
+        let targets = ["A".to_string(), "B".to_string()];
+        let rows = |max_depth: usize| -> Vec<String> {
+            closure_rows(&graph, &targets, max_depth, TravelMode::Children, &|n| graph.source(n))
+                .iter()
+                .map(|r| format!("{} via {}", r.name, r.via.join(",")))
+                .collect()
+        };
+        assert_eq!(rows(usize::MAX), ["C via A,B", "D via A,B", "E via A,B"]);
+        assert_eq!(rows(1), ["C via A,B", "D via B"]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+use anyhow::bail;
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use crate::lib::util::similar::similar_names;
+
+// 在依赖图里挑选节点：精确的名字、glob（`Firebase*`）或者用 `/` 包起来的正则（`/^AF/`）
+
+pub(crate) enum Pattern {
+    Name(String),
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub(crate) fn parse(text: &str) -> anyhow::Result<Self> {
+        if text.len() > 1 && text.starts_with('/') && text.ends_with('/') {
+            // regex 不认识 `\/`，按习惯写了也接受
+            let inner = text[1..text.len() - 1].replace("\\/", "/");
+            return Ok(Pattern::Regex(Regex::new(&inner)?))
+        }
+        if text.contains(['*', '?', '[', '{']) {
+            return Ok(Pattern::Glob(Glob::new(text)?.compile_matcher()))
+        }
+        Ok(Pattern::Name(text.to_string()))
+    }
+
+    pub(crate) fn is_match(&self, name: &str) -> bool {
+        match self {
+            Pattern::Name(n) => n == name,
+            Pattern::Glob(g) => g.is_match(name),
+            Pattern::Regex(r) => r.is_match(name),
+        }
+    }
+}
+
+// 按顺序返回所有匹配的名字，每个 pattern 至少要匹配一个
+// `names` 已经排好序，`file` 为 lockfile 的文件名，用于错误信息
+pub(crate) fn match_names(
+    names: &[&str],
+    patterns: &[&str],
+    file: &str
+) -> anyhow::Result<Vec<String>> {
+    let mut ret = Vec::new();
+    let mut seen = BTreeSet::new();
+    for text in patterns {
+        let pattern = Pattern::parse(text)?;
+        let matched = names.iter()
+            .filter(|n| pattern.is_match(n))
+            .collect::<Vec<&&str>>();
+        if matched.is_empty() {
+            match pattern {
+                Pattern::Name(n) => {
+                    let similar = similar_names(&n, names.iter().cloned(), 5);
+                    if similar.is_empty() {
+                        bail!("Cannot find {} in {}", n, file);
+                    }
+                    bail!("Cannot find {} in {}, did you mean: {}?", n, file, similar.join(", "));
+                },
+                _ => bail!("Nothing in {} matches {}", file, text),
+            }
+        }
+        for n in matched {
+            if seen.insert(n.to_string()) {
+                ret.push(n.to_string());
+            }
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_names, Pattern};
+
+    #[test]
+    fn parse_kinds() {
+        assert!(matches!(Pattern::parse("Masonry").unwrap(), Pattern::Name(_)));
+        assert!(matches!(Pattern::parse("Firebase*").unwrap(), Pattern::Glob(_)));
+        assert!(matches!(Pattern::parse("/^AF/").unwrap(), Pattern::Regex(_)));
+        // 单独的 `/` 是名字
+        assert!(matches!(Pattern::parse("/").unwrap(), Pattern::Name(_)));
+        assert!(Pattern::parse("/(/").is_err());
+        assert!(Pattern::parse("/^AFNetworking\\/NSURL/").unwrap().is_match("AFNetworking/NSURLSession"));
+        assert!(!Pattern::parse("Masonry").unwrap().is_match("masonry"));
+    }
+
+    #[test]
+    fn errors_name_the_file() {
+        let names = ["rand", "rand_core", "serde"];
+        let err = match_names(&names, &["rand_cor"], "Cargo.lock").unwrap_err().to_string();
+        assert_eq!(err, "Cannot find rand_cor in Cargo.lock, did you mean: rand_core?");
+        let err = match_names(&names, &["tokio*"], "Cargo.lock").unwrap_err().to_string();
+        assert_eq!(err, "Nothing in Cargo.lock matches tokio*");
+        assert_eq!(match_names(&names, &["rand*", "/^s/"], "Cargo.lock").unwrap(), ["rand", "rand_core", "serde"]);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod tree;
 pub mod util;
-pub mod pod;
\ No newline at end of file
+pub mod pod;
+pub mod dep;
+pub mod xcode;
\ No newline at end of file
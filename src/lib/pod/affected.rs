@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufRead;
+use std::path::{Component, Path, PathBuf};
+use std::process;
+use anyhow::bail;
+use serde_json::{json, Value};
+use crate::lib::pod::dep::rdep_closure;
+use crate::lib::pod::lockfile::{root_name, Lockfile};
+
+// pod affected
+// 把改动的文件映射到 `:path` 引入的开发 pod，再展开到所有反向依赖，用于 CI 挑选需要跑的测试
+
+pub(crate) struct Affected {
+    // 开发 pod（root spec）-> 改动的文件
+    pub(crate) changed: BTreeMap<String, Vec<PathBuf>>,
+    // 受影响的 pod（root spec），包含 changed 本身
+    pub(crate) affected: BTreeSet<String>,
+    // 不属于任何开发 pod 的文件
+    pub(crate) unmatched: Vec<PathBuf>,
+}
+
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// `dir` 所在 git 仓库的根目录
+pub(crate) fn repo_root(dir: &Path) -> anyhow::Result<PathBuf> {
+    let top = git(dir, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(top.trim()))
+}
+
+// `git diff --name-only` 列出相对于 base 的改动（包含未提交的改动），返回绝对路径
+pub(crate) fn changed_files_from_git(dir: &Path, base: &str) -> anyhow::Result<Vec<PathBuf>> {
+    // 输出的路径相对于仓库根目录，`:path` 可能指向 Podfile 所在目录之外
+    let top = repo_root(dir)?;
+    let ret = git(dir, &["diff", "--name-only", base, "--"])?
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| normalize(&top.join(l.trim())))
+        .collect();
+    Ok(ret)
+}
+
+// 每行一个路径，相对路径按 `base_dir` 解析
+// 通常是仓库根目录，这样 `git diff --name-only | rubo pod affected` 在子目录里也能用
+pub(crate) fn changed_files_from_reader<R: BufRead>(reader: R, base_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut ret = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            ret.push(normalize(&base_dir.join(line)));
+        }
+    }
+    Ok(ret)
+}
+
+// `dir` 为 Podfile.lock 所在的目录，`:path` 相对于它；`files` 为绝对路径
+pub(crate) fn affected(lockfile: &Lockfile, dir: &Path, files: &[PathBuf]) -> anyhow::Result<Affected> {
+    let pods = lockfile.pods()?;
+    let dev_pods = lockfile.external_sources()?
+        .into_values()
+        .filter_map(|es| es.path.map(|p| (es.name, normalize(&dir.join(p)))))
+        .collect::<Vec<(String, PathBuf)>>();
+
+    let mut changed = BTreeMap::<String, Vec<PathBuf>>::new();
+    let mut unmatched = Vec::new();
+    for file in files {
+        // 开发 pod 可能嵌套，取最深的那个
+        let owner = dev_pods.iter()
+            .filter(|(_, p)| file.starts_with(p))
+            .max_by_key(|(_, p)| p.components().count());
+        let file = file.strip_prefix(dir).map(|f| f.to_path_buf()).unwrap_or_else(|_| file.clone());
+        match owner {
+            Some((name, _)) => changed.entry(name.clone()).or_default().push(file),
+            None => unmatched.push(file),
+        }
+    }
+
+    let mut affected = BTreeSet::new();
+    for name in changed.keys() {
+        // root spec 下的每个 subspec 都算改动了
+        for pod in pods.keys().filter(|p| root_name(p) == name) {
+            for p in rdep_closure(&pods, pod)? {
+                affected.insert(root_name(&p).to_string());
+            }
+        }
+        affected.insert(name.clone());
+    }
+    Ok(Affected { changed, affected, unmatched })
+}
+
+// 只做字面上的规整（去掉 `.` 和 `..`），被删除的文件也能参与比较
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut ret = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                ret.pop();
+            },
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+pub(crate) fn to_json(affected: &Affected) -> Value {
+    let changed = affected.changed.iter()
+        .map(|(name, files)| {
+            let files = files.iter().map(|f| f.to_string_lossy().to_string()).collect::<Vec<String>>();
+            (name.clone(), json!(files))
+        })
+        .collect::<serde_json::Map<String, Value>>();
+    json!({
+        "changed": changed,
+        "affected": affected.affected,
+        "unmatched": affected.unmatched.iter().map(|f| f.to_string_lossy().to_string()).collect::<Vec<String>>(),
+    })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::Affected;
+
+    // 标准输出只有 pod 名，方便传给测试脚本；汇总信息输出到标准错误
+    pub(crate) fn print_affected(affected: &Affected, separator: &str) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let names = affected.affected.iter().map(|n| n.as_str()).collect::<Vec<&str>>();
+        if !names.is_empty() {
+            println!("{}", names.join(separator));
+        }
+        let files = affected.changed.values().map(|f| f.len()).sum::<usize>();
+        let mut summary = format!(
+            "{} {} in {} development {}, {} {} affected",
+            files,
+            if files == 1 { "file" } else { "files" },
+            affected.changed.len(),
+            if affected.changed.len() == 1 { "pod" } else { "pods" },
+            names.len(),
+            if names.len() == 1 { "pod" } else { "pods" }
+        );
+        if !affected.unmatched.is_empty() {
+            summary.push_str(&format!(", {} outside development pods", affected.unmatched.len()));
+        }
+        eprintln!("{}", Style::from(light_gray).paint(summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+    use crate::lib::pod::lockfile::Lockfile;
+    use super::{affected, changed_files_from_reader};
+
+    // Core 嵌套在 Kit 的目录里；Shared 在 Podfile 所在目录之外
+    const LOCKFILE: &str = "
+PODS:
+  - App (1.0.0):
+    - Kit/UI
+  - Core (1.0.0)
+  - Kit/Base (1.0.0):
+    - Core
+  - Kit/UI (1.0.0):
+    - Kit/Base
+  - Other (1.0.0)
+  - Shared (1.0.0)
+
+DEPENDENCIES:
+  - App (from `./App`)
+  - Core (from `./Modules/Kit/Core`)
+  - Kit/UI (from `./Modules/Kit`)
+  - Other (= 1.0.0)
+  - Shared (from `../Shared`)
+
+EXTERNAL SOURCES:
+  App:
+    :path: \"./App\"
+  Core:
+    :path: \"./Modules/Kit/Core\"
+  Kit:
+    :path: \"./Modules/Kit\"
+  Shared:
+    :path: \"../Shared\"
+
+COCOAPODS: 1.11.0
+";
+
+    fn files(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn files_map_to_deepest_development_pod() {
+        let lockfile = Lockfile::from_yaml_str(LOCKFILE).unwrap();
+        let result = affected(&lockfile, Path::new("/repo/ios"), &files(&[
+            "/repo/ios/Modules/Kit/Core/Core.swift",
+            "/repo/ios/Modules/Kit/UI/View.swift",
+            "/repo/Shared/Shared.swift",
+            "/repo/ios/Podfile",
+            "/repo/README.md",
+        ])).unwrap();
+        let changed = result.changed.iter()
+            .map(|(k, v)| (k.as_str(), v.clone()))
+            .collect::<Vec<(&str, Vec<PathBuf>)>>();
+        assert_eq!(changed, [
+            ("Core", files(&["Modules/Kit/Core/Core.swift"])),
+            ("Kit", files(&["Modules/Kit/UI/View.swift"])),
+            ("Shared", files(&["/repo/Shared/Shared.swift"])),
+        ]);
+        assert_eq!(result.unmatched, files(&["Podfile", "/repo/README.md"]));
+    }
+
+    #[test]
+    fn affected_expands_to_dependents_of_every_subspec() {
+        let lockfile = Lockfile::from_yaml_str(LOCKFILE).unwrap();
+        let result = affected(&lockfile, Path::new("/repo/ios"), &files(&[
+            "/repo/ios/Modules/Kit/Core/Core.swift",
+        ])).unwrap();
+        assert_eq!(result.affected.into_iter().collect::<Vec<String>>(), ["App", "Core", "Kit"]);
+
+        let result = affected(&lockfile, Path::new("/repo/ios"), &files(&[
+            "/repo/Shared/Shared.swift",
+        ])).unwrap();
+        assert_eq!(result.affected.into_iter().collect::<Vec<String>>(), ["Shared"]);
+
+        let result = affected(&lockfile, Path::new("/repo/ios"), &files(&["/repo/ios/Podfile"])).unwrap();
+        assert!(result.affected.is_empty());
+    }
+
+    #[test]
+    fn reader_resolves_against_base_dir() {
+        let input = "ios/Modules/Kit/Core/Core.swift\n\n  ./ios/App/../App/main.swift  \n/abs/file\n";
+        let ret = changed_files_from_reader(Cursor::new(input), Path::new("/repo")).unwrap();
+        assert_eq!(ret, files(&[
+            "/repo/ios/Modules/Kit/Core/Core.swift",
+            "/repo/ios/App/main.swift",
+            "/abs/file",
+        ]));
+    }
+}
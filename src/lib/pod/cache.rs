@@ -0,0 +1,217 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash as _, Hasher};
+use std::path::{Path, PathBuf};
+use serde_json::{json, Value};
+use crate::lib::pod::located::Source;
+use crate::lib::pod::lockfile::{Interner, Lockfile, PodItem};
+use crate::lib::pod::version::Requirement;
+
+// PODS 解析结果的磁盘缓存，设置 `RUBO_POD_CACHE=<dir>` 后开启
+// - 以 PODFILE CHECKSUM 为文件名，`<dir>/<checksum>.json`
+// - 同一个 Podfile 在 `pod update` 后 Podfile.lock 也会变，所以再用文件内容的摘要校验
+// - 只缓存 PODS 解析出的依赖图，其余 section 很小，仍然从 YAML 里读
+
+pub(crate) const CACHE_DIR_ENV: &str = "RUBO_POD_CACHE";
+// 缓存格式变化时加一，旧的缓存自动失效
+const FORMAT: u64 = 2;
+
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    std::env::var(CACHE_DIR_ENV).ok().filter(|d| !d.is_empty()).map(PathBuf::from)
+}
+
+// 缓存没有命中时解析 PODS 并写入缓存，写入失败不影响结果
+// 两种情况下解析好的 PODS 都放进返回的 `Lockfile`，之后的 `pods()` 不会再解析
+pub(crate) fn load_or_parse(dir: &Path, source: Source, lenient: bool) -> anyhow::Result<Lockfile> {
+    let digest = digest(&source.text);
+    let lockfile = Lockfile::from_source(source, lenient)?;
+    let checksum = match lockfile.podfile_checksum() {
+        // 用作文件名，只接受十六进制摘要这样的字符
+        Some(c) if !c.is_empty() && c.chars().all(|c| c.is_ascii_alphanumeric()) => c,
+        _ => return Ok(lockfile),
+    };
+    let path = dir.join(format!("{}.json", checksum));
+    if let Some(pods) = load(&path, &digest) {
+        return Ok(lockfile.with_pods(pods))
+    }
+    // lenient 时可能跳过了出错的项，不缓存
+    if lenient {
+        return Ok(lockfile)
+    }
+    // 解析出错时不缓存，错误留给调用 `pods()` 的地方处理
+    let pods = match lockfile.pods() {
+        Ok(pods) => pods,
+        Err(_) => return Ok(lockfile),
+    };
+    let _ = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&path, save(&pods, &digest).to_string()));
+    Ok(lockfile.with_pods(pods))
+}
+
+// `DefaultHasher` 在不同的 Rust 版本间可能不同，最多只是缓存失效
+fn digest(content: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn load(path: &Path, digest: &str) -> Option<HashMap<String, PodItem>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let value = serde_json::from_str::<Value>(&text).ok()?;
+    if value["format"].as_u64() != Some(FORMAT) || value["digest"].as_str() != Some(digest) {
+        return None
+    }
+    let mut names = Interner::default();
+    let mut pods = HashMap::new();
+    for p in value["pods"].as_array()? {
+        let name = p["name"].as_str()?;
+        let mut pod = PodItem::new(name.to_string(), p["vers"].as_str().map(|v| v.to_string()));
+        for parent in p["parents"].as_array()? {
+            pod.parents.insert(names.intern(parent.as_str()?));
+        }
+        // `[name, requirement]`，没有版本要求时为 null
+        for c in p["children"].as_array()? {
+            let child = names.intern(c[0].as_str()?);
+            if let Some(r) = c[1].as_str() {
+                pod.requirements.insert(child.clone(), Requirement::parse(r)?);
+            }
+            pod.children.insert(child);
+        }
+        pods.insert(name.to_string(), pod);
+    }
+    Some(pods)
+}
+
+fn save(pods: &HashMap<String, PodItem>, digest: &str) -> Value {
+    let pods = pods.values()
+        .map(|p| {
+            let parents = p.parents.iter().map(|p| p.as_ref()).collect::<Vec<&str>>();
+            let children = p.children.iter()
+                .map(|c| json!([c.as_ref(), p.requirements.get(c).map(|r| r.to_string())]))
+                .collect::<Vec<Value>>();
+            json!({ "name": p.name, "vers": p.vers, "parents": parents, "children": children })
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "format": FORMAT,
+        "digest": digest,
+        "pods": pods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::PodItem;
+    use crate::lib::util::testing::TempDir;
+    use crate::lib::pod::located::Source;
+    use crate::lib::pod::lockfile::Lockfile;
+    use super::load_or_parse;
+
+    const LOCKFILE: &str = "
+PODS:
+  - AFNetworking (4.0.1):
+    - AFNetworking/Core (= 4.0.1)
+  - AFNetworking/Core (4.0.1)
+  - RBFoundation (0.1.0):
+    - AFNetworking (~> 4.0)
+    - Masonry
+
+DEPENDENCIES:
+  - RBFoundation (from `./Modules/RBFoundation`)
+
+PODFILE CHECKSUM: 0f1e2d3c
+
+COCOAPODS: 1.11.3
+";
+
+    fn parse(tmp: &TempDir, text: &str) -> Lockfile {
+        let source = Source { name: "Podfile.lock".to_string(), text: text.to_string() };
+        load_or_parse(tmp.path(), source, false).unwrap()
+    }
+
+    // 排好序的 `name vers children parents`，方便比较
+    fn describe(pods: &HashMap<String, PodItem>) -> Vec<String> {
+        let mut ret = pods.values()
+            .map(|p| {
+                let sorted = |names: Vec<String>| {
+                    let mut names = names;
+                    names.sort();
+                    names.join(",")
+                };
+                let children = p.children.iter()
+                    .map(|c| match p.requirements.get(c) {
+                        Some(r) => format!("{} ({})", c, r),
+                        None => c.to_string(),
+                    })
+                    .collect();
+                let parents = p.parents.iter().map(|p| p.to_string()).collect();
+                format!("{} {:?} [{}] [{}]", p.name, p.vers, sorted(children), sorted(parents))
+            })
+            .collect::<Vec<String>>();
+        ret.sort();
+        ret
+    }
+
+    #[test]
+    fn round_trip() {
+        let tmp = TempDir::new();
+        let parsed = parse(&tmp, LOCKFILE);
+        assert!(tmp.path().join("0f1e2d3c.json").is_file());
+        let loaded = parse(&tmp, LOCKFILE);
+        let expected = describe(&parsed.pods().unwrap());
+        assert_eq!(expected, [
+            "AFNetworking Some(\"4.0.1\") [AFNetworking/Core (= 4.0.1)] [RBFoundation]",
+            "AFNetworking/Core Some(\"4.0.1\") [] [AFNetworking]",
+            "Masonry None [] [RBFoundation]",
+            "RBFoundation Some(\"0.1.0\") [AFNetworking (~> 4.0),Masonry] []",
+        ]);
+        assert_eq!(describe(&loaded.pods().unwrap()), expected);
+        // 其余 section 仍然可以读
+        assert_eq!(loaded.dependencies().unwrap()[0].name, "RBFoundation");
+    }
+
+    #[test]
+    fn hit_reads_cache_instead_of_pods() {
+        let tmp = TempDir::new();
+        parse(&tmp, LOCKFILE);
+        // 改掉缓存里的版本，命中时读到的是缓存的内容
+        let path = tmp.path().join("0f1e2d3c.json");
+        let text = std::fs::read_to_string(&path).unwrap().replace("0.1.0", "9.9.9");
+        std::fs::write(&path, text).unwrap();
+        let pods = parse(&tmp, LOCKFILE).pods().unwrap();
+        assert_eq!(pods["RBFoundation"].vers.as_deref(), Some("9.9.9"));
+    }
+
+    #[test]
+    fn invalidated_by_content_and_format() {
+        let tmp = TempDir::new();
+        parse(&tmp, LOCKFILE);
+        // `pod update` 之后 PODFILE CHECKSUM 不变，但内容变了
+        let updated = LOCKFILE.replace("RBFoundation (0.1.0)", "RBFoundation (0.2.0)");
+        let pods = parse(&tmp, &updated).pods().unwrap();
+        assert_eq!(pods["RBFoundation"].vers.as_deref(), Some("0.2.0"));
+        // 缓存被新的内容覆盖
+        let pods = parse(&tmp, &updated).pods().unwrap();
+        assert_eq!(pods["RBFoundation"].vers.as_deref(), Some("0.2.0"));
+
+        // 格式不对或者损坏的缓存都重新解析
+        let path = tmp.path().join("0f1e2d3c.json");
+        let text = std::fs::read_to_string(&path).unwrap().replace("\"format\":2", "\"format\":1");
+        std::fs::write(&path, text.replace("0.2.0", "9.9.9")).unwrap();
+        let pods = parse(&tmp, &updated).pods().unwrap();
+        assert_eq!(pods["RBFoundation"].vers.as_deref(), Some("0.2.0"));
+        std::fs::write(&path, "{").unwrap();
+        let pods = parse(&tmp, &updated).pods().unwrap();
+        assert_eq!(pods["RBFoundation"].vers.as_deref(), Some("0.2.0"));
+    }
+
+    #[test]
+    fn no_cache_without_podfile_checksum() {
+        let tmp = TempDir::new();
+        let text = LOCKFILE.replace("PODFILE CHECKSUM: 0f1e2d3c\n", "");
+        let pods = parse(&tmp, &text).pods().unwrap();
+        assert_eq!(pods.len(), 4);
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+use crate::lib::pod::lockfile::{root_name, Lockfile};
+use crate::lib::pod::version::{Requirement, Version};
+
+// pod check
+// 检查 PODS 和 DEPENDENCIES 里的版本要求是否被解析出的版本满足，用于发现手工合并出错的 Podfile.lock
+
+// DEPENDENCIES 里的要求，来源记作 Podfile
+pub(crate) const PODFILE: &str = "Podfile";
+
+pub(crate) struct Violation {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) requirement: Option<Requirement>,
+    pub(crate) resolved: Option<String>,
+    pub(crate) reason: String,
+}
+
+pub(crate) fn check(lockfile: &Lockfile) -> anyhow::Result<(usize, Vec<Violation>)> {
+    let pods = lockfile.pods()?;
+    let external = lockfile.external_sources()?;
+
+    // 所有指向某个 pod 的版本要求：(from, requirement)
+    let mut incoming = HashMap::<&str, Vec<(&str, &Requirement)>>::new();
+    for pod in pods.values() {
+        for (child, r) in &pod.requirements {
+            incoming.entry(child.as_ref()).or_default().push((pod.name.as_str(), r));
+        }
+    }
+    let deps = lockfile.dependencies()?;
+    for dep in &deps {
+        if let Some(r) = &dep.requirement {
+            incoming.entry(dep.name.as_str()).or_default().push((PODFILE, r));
+        }
+    }
+
+    let mut checked = 0;
+    let mut ret = Vec::new();
+    let mut names = incoming.keys().cloned().collect::<Vec<&str>>();
+    names.sort();
+    for name in names {
+        let reqs = incoming.get(name).unwrap();
+        let resolved = pods.get(name).and_then(|p| p.vers.clone());
+        let root = root_name(name);
+        // 和 CocoaPods 的 resolver 一致：有任一要求是 prerelease，或者来自 external source，才接受 prerelease
+        let allow_prerelease = external.contains_key(root) || reqs.iter().any(|(_, r)| r.is_prerelease());
+        for (from, r) in reqs {
+            checked += 1;
+            let violation = |reason: &str| Violation {
+                from: from.to_string(),
+                to: name.to_string(),
+                requirement: Some((*r).clone()),
+                resolved: resolved.clone(),
+                reason: reason.to_string(),
+            };
+            let version = match resolved.as_deref() {
+                Some(v) => Version::parse(v),
+                None => {
+                    ret.push(violation("not found in PODS"));
+                    continue
+                },
+            };
+            match version {
+                None => ret.push(violation("invalid resolved version")),
+                Some(v) if !r.is_satisfied_by(&v) => ret.push(violation("not satisfied")),
+                Some(v) if v.is_prerelease() && !allow_prerelease => ret.push(violation("prerelease not requested")),
+                _ => {},
+            }
+        }
+    }
+
+    // subspec 和 root spec 的版本必须一致
+    let mut roots = HashSet::new();
+    for pod in pods.values() {
+        if pod.is_subspec() {
+            roots.insert(pod.root_name());
+        }
+    }
+    let mut roots = roots.into_iter().collect::<Vec<&str>>();
+    roots.sort();
+    for root in roots {
+        let root_vers = pods.get(root).and_then(|p| p.vers.clone());
+        let mut subspecs = pods.values()
+            .filter(|p| p.name.starts_with(&format!("{}/", root)))
+            .collect::<Vec<_>>();
+        subspecs.sort_by(|a, b| a.name.cmp(&b.name));
+        for sub in subspecs {
+            checked += 1;
+            if root_vers.is_some() && sub.vers.is_some() && sub.vers != root_vers {
+                ret.push(Violation {
+                    from: root.to_string(),
+                    to: sub.name.clone(),
+                    requirement: None,
+                    resolved: sub.vers.clone(),
+                    reason: format!("subspec version differs from {} {}", root, root_vers.clone().unwrap()),
+                });
+            }
+        }
+    }
+    Ok((checked, ret))
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::Violation;
+
+    pub(crate) fn print_violations(checked: usize, violations: &[Violation]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if violations.is_empty() {
+            println!(
+                "{}",
+                Style::from(Colour::Green).paint(format!("All {} requirements are satisfied", checked))
+            );
+            return
+        }
+        for v in violations {
+            let requirement = v.requirement.as_ref()
+                .map(|r| format!("requires {}, ", r))
+                .unwrap_or_default();
+            let resolved = v.resolved.clone().unwrap_or_else(|| "nothing".to_string());
+            println!(
+                " {} {} -> {}  {}",
+                Style::from(Colour::Red).bold().paint("✗"),
+                Style::from(Colour::Yellow).paint(&v.from),
+                Style::from(Colour::Yellow).paint(&v.to),
+                Style::from(light_gray).paint(format!("{}resolved {} ({})", requirement, resolved, v.reason))
+            );
+        }
+        eprintln!(
+            "{}",
+            Style::from(light_gray).paint(format!("{} of {} requirements are not satisfied", violations.len(), checked))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::pod::lockfile::Lockfile;
+    use super::{check, Violation, PODFILE};
+
+    fn violations(text: &str) -> (usize, Vec<Violation>) {
+        check(&Lockfile::from_yaml_str(text).unwrap()).unwrap()
+    }
+
+    fn summary(violations: &[Violation]) -> Vec<String> {
+        violations.iter().map(|v| format!("{} -> {}: {}", v.from, v.to, v.reason)).collect()
+    }
+
+    #[test]
+    fn satisfied_lockfile() {
+        let (checked, result) = violations("
+PODS:
+  - A (1.0.0):
+    - B (~> 2.0)
+  - B (2.3.0)
+
+DEPENDENCIES:
+  - A (>= 1.0, < 2.0)
+
+COCOAPODS: 1.11.0
+");
+        assert_eq!(checked, 2);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn unsatisfied_edge() {
+        let (checked, result) = violations("
+PODS:
+  - A (1.0.0):
+    - B (~> 2.0)
+  - B (3.0.0)
+
+DEPENDENCIES:
+  - A
+  - B (= 3.0.0)
+
+COCOAPODS: 1.11.0
+");
+        assert_eq!(checked, 2);
+        assert_eq!(summary(&result), ["A -> B: not satisfied"]);
+        assert_eq!(result[0].requirement.as_ref().map(|r| r.to_string()).as_deref(), Some("~> 2.0"));
+        assert_eq!(result[0].resolved.as_deref(), Some("3.0.0"));
+    }
+
+    #[test]
+    fn podfile_requirement_and_missing_pod() {
+        let (_, result) = violations("
+PODS:
+  - A (1.0.0):
+    - C (= 1.0)
+
+DEPENDENCIES:
+  - A (~> 2.0)
+
+COCOAPODS: 1.11.0
+");
+        assert_eq!(summary(&result), [
+            format!("{} -> A: not satisfied", PODFILE),
+            "A -> C: not found in PODS".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn prerelease_needs_to_be_requested() {
+        let text = "
+PODS:
+  - A (1.0.0):
+    - B (~> 2.0)
+  - B (2.1.0-beta.1)
+
+DEPENDENCIES:
+  - A
+
+COCOAPODS: 1.11.0
+";
+        let (_, result) = violations(text);
+        assert_eq!(summary(&result), ["A -> B: prerelease not requested"]);
+        let (_, result) = violations(&text.replace("B (~> 2.0)", "B (~> 2.1.0-beta)"));
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn subspec_version_differs() {
+        let (_, result) = violations("
+PODS:
+  - A (1.0.0):
+    - A/Core (= 1.0.0)
+  - A/Core (1.1.0)
+
+DEPENDENCIES:
+  - A
+
+COCOAPODS: 1.11.0
+");
+        assert_eq!(summary(&result), [
+            "A -> A/Core: not satisfied",
+            "A -> A/Core: subspec version differs from A 1.0.0",
+        ]);
+    }
+}
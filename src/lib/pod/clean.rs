@@ -0,0 +1,412 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use crate::lib::pod::lockfile::Lockfile;
+use crate::lib::pod::version::Version;
+use crate::lib::util::walker::{Walker, WakerEntry};
+
+// pod clean
+// 清理 CocoaPods 的下载缓存，缓存结构参考 cocoapods-downloader 的 Cache：
+// - <root>/Release/<Name>/<version>-<checksum[0,5]>/
+// - <root>/Specs/Release/<Name>/<version>-<checksum[0,5]>.podspec.json
+// - <root>/External/<Name>/<hash>/
+// - <root>/Specs/External/<Name>/<hash>.podspec.json
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum CacheKind { Release, External }
+
+pub(crate) struct CacheEntry {
+    pub(crate) kind: CacheKind,
+    pub(crate) name: String,
+    // External 的缓存没有版本号
+    pub(crate) version: Option<String>,
+    // 源码目录和 podspec 文件
+    pub(crate) paths: Vec<PathBuf>,
+    pub(crate) size: u64,
+    pub(crate) last_access: SystemTime,
+}
+
+pub(crate) struct Candidate {
+    pub(crate) entry: CacheEntry,
+    pub(crate) reasons: Vec<String>,
+}
+
+pub(crate) struct Cleaner {
+    root: PathBuf,
+    // 只处理这些 pod，`None` 表示不限制
+    names: Option<HashSet<String>>,
+    // 策略一：超过 N 天未访问
+    unused_days: Option<u64>,
+    // 策略二：版本号低于阈值，External 的缓存没有版本号，不会被选中
+    below: Option<Version>,
+    // 策略三：这些目录下的 Podfile.lock 都没有使用
+    projects: Option<Vec<PathBuf>>,
+}
+
+impl Cleaner {
+    pub(crate) fn new<P: AsRef<Path>>(root: P) -> Self {
+        Cleaner {
+            root: root.as_ref().to_path_buf(),
+            names: None,
+            unused_days: None,
+            below: None,
+            projects: None,
+        }
+    }
+
+    // 默认的缓存目录，和 CocoaPods 一致，优先读取 $CP_CACHE_DIR
+    pub(crate) fn default_root() -> PathBuf {
+        let base = std::env::var("CP_CACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                PathBuf::from(home).join("Library/Caches/CocoaPods")
+            });
+        base.join("Pods")
+    }
+
+    pub(crate) fn names(&mut self, names: Option<HashSet<String>>) -> &mut Self {
+        self.names = names;
+        self
+    }
+
+    pub(crate) fn unused_days(&mut self, days: Option<u64>) -> &mut Self {
+        self.unused_days = days;
+        self
+    }
+
+    pub(crate) fn below(&mut self, version: Option<Version>) -> &mut Self {
+        self.below = version;
+        self
+    }
+
+    pub(crate) fn projects(&mut self, roots: Option<Vec<PathBuf>>) -> &mut Self {
+        self.projects = roots;
+        self
+    }
+
+    // 根据策略筛选出可删除的缓存，多个策略同时指定时需全部满足
+    pub(crate) fn plan(&self) -> anyhow::Result<Vec<Candidate>> {
+        let in_use = match &self.projects {
+            Some(roots) => Some(used_pods(roots)?),
+            None => None,
+        };
+        let now = SystemTime::now();
+        let mut ret = Vec::new();
+        for entry in self.scan()? {
+            let mut reasons = Vec::new();
+            if let Some(days) = self.unused_days {
+                let idle = now.duration_since(entry.last_access).unwrap_or(Duration::ZERO);
+                if idle < Duration::from_secs(days.saturating_mul(24 * 3600)) {
+                    continue
+                }
+                reasons.push(format!("not accessed in {} days", idle.as_secs() / (24 * 3600)));
+            }
+            if let Some(below) = &self.below {
+                let older = entry.version.as_deref()
+                    .and_then(Version::parse)
+                    .map(|v| &v < below)
+                    .unwrap_or(false);
+                if !older {
+                    continue
+                }
+                reasons.push(format!("older than {}", below));
+            }
+            if let Some(in_use) = &in_use {
+                let used = in_use.iter().any(|(name, vers)| {
+                    name == &entry.name && match (&entry.kind, &entry.version) {
+                        (CacheKind::Release, Some(v)) => vers.as_deref() == Some(v.as_str()),
+                        _ => true,
+                    }
+                });
+                if used {
+                    continue
+                }
+                reasons.push("not used by any project".to_string());
+            }
+            ret.push(Candidate { entry, reasons });
+        }
+        Ok(ret)
+    }
+
+    // 扫描缓存目录，把源码目录和 podspec 按 (name, slug) 归到一起
+    fn scan(&self) -> anyhow::Result<Vec<CacheEntry>> {
+        let mut slugs = BTreeMap::<(CacheKind, String, String), Vec<PathBuf>>::new();
+        for (kind, dir) in [(CacheKind::Release, "Release"), (CacheKind::External, "External")] {
+            for (base, suffix) in [(self.root.join(dir), ""), (self.root.join("Specs").join(dir), ".podspec.json")] {
+                if !base.is_dir() {
+                    continue
+                }
+                for pod_dir in std::fs::read_dir(&base)? {
+                    let pod_dir = pod_dir?.path();
+                    let name = file_name(&pod_dir);
+                    if !pod_dir.is_dir() || name.starts_with('.') {
+                        continue
+                    }
+                    if let Some(names) = &self.names {
+                        if !names.contains(&name) {
+                            continue
+                        }
+                    }
+                    for item in std::fs::read_dir(&pod_dir)? {
+                        let item = item?.path();
+                        let item_name = file_name(&item);
+                        if item_name.starts_with('.') {
+                            continue
+                        }
+                        let slug = item_name.strip_suffix(suffix).unwrap_or(&item_name).to_string();
+                        slugs.entry((kind, name.clone(), slug)).or_default().push(item);
+                    }
+                }
+            }
+        }
+
+        let mut ret = Vec::new();
+        for ((kind, name, slug), paths) in slugs {
+            let size = Cell::new(0u64);
+            let last_access = Cell::new(SystemTime::UNIX_EPOCH);
+            let touch = |t: SystemTime| {
+                if t > last_access.get() {
+                    last_access.set(t);
+                }
+            };
+            // 只看文件的 atime，目录的 atime 在扫描（read_dir）时就会被刷新
+            for path in &paths {
+                let meta = std::fs::symlink_metadata(path)?;
+                if !meta.is_dir() {
+                    size.set(size.get() + meta.len());
+                    if let Ok(t) = meta.accessed() {
+                        touch(t);
+                    }
+                    continue
+                }
+                if let Ok(t) = meta.modified() {
+                    touch(t);
+                }
+                let handle = |entry: WakerEntry| {
+                    if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
+                        size.set(size.get() + entry.size().unwrap_or(0));
+                        if let Ok(t) = entry.accessed() {
+                            touch(t);
+                        }
+                    }
+                };
+                let mut walker = Walker::new(path);
+                walker.hide_ignore(false);
+                walker.start(&handle)?;
+            }
+            let version = match kind {
+                CacheKind::Release => Some(version_from_slug(&slug)),
+                CacheKind::External => None,
+            };
+            ret.push(CacheEntry { kind, name, version, paths, size: size.get(), last_access: last_access.get() });
+        }
+        Ok(ret)
+    }
+}
+
+// 删除缓存，返回释放的字节数
+pub(crate) fn remove(candidates: &[Candidate]) -> anyhow::Result<u64> {
+    let mut freed = 0;
+    for c in candidates {
+        for path in &c.entry.paths {
+            if path.is_dir() {
+                std::fs::remove_dir_all(path)?;
+            } else {
+                std::fs::remove_file(path)?;
+            }
+            // 顺便删掉空的 <Name>/ 目录
+            if let Some(parent) = path.parent() {
+                let empty = std::fs::read_dir(parent).map(|mut d| d.next().is_none()).unwrap_or(false);
+                if empty {
+                    std::fs::remove_dir(parent)?;
+                }
+            }
+        }
+        freed += c.entry.size;
+    }
+    Ok(freed)
+}
+
+// 收集 roots 下所有 Podfile.lock 用到的 (name, version)，subspec 归到 root spec
+fn used_pods(roots: &[PathBuf]) -> anyhow::Result<HashSet<(String, Option<String>)>> {
+    let lockfiles = RefCell::new(Vec::<PathBuf>::new());
+    for root in roots {
+        if root.is_file() {
+            lockfiles.borrow_mut().push(root.clone());
+            continue
+        }
+        let handle = |entry: WakerEntry| {
+            let is_file = entry.file_type().map(|ft| ft.is_file()).unwrap_or(false);
+            if is_file && entry.file_name() == "Podfile.lock" {
+                lockfiles.borrow_mut().push(entry.path());
+            }
+        };
+        Walker::new(root).start(&handle)?;
+    }
+    let mut ret = HashSet::new();
+    for path in lockfiles.into_inner() {
+        // 据此决定哪些缓存还在用，不能跳过出错的项
+        let lockfile = Lockfile::from_file(&path, false)?;
+        for item in lockfile.pods()?.into_values() {
+            ret.insert((item.root_name().to_string(), item.vers));
+        }
+    }
+    Ok(ret)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+// `4.0.1-8fc3d` -> `4.0.1`
+fn version_from_slug(slug: &str) -> String {
+    match slug.rsplit_once('-') {
+        Some((vers, checksum)) if checksum.len() == 5 && checksum.chars().all(|c| c.is_ascii_hexdigit()) => {
+            vers.to_string()
+        },
+        _ => slug.to_string(),
+    }
+}
+
+pub(crate) mod printer {
+    use std::time::{Duration, SystemTime};
+    use ansi_term::{Colour, Style};
+    use bytesize::ByteSize;
+    use super::{Candidate, CacheKind};
+
+    pub(crate) fn print_report(candidates: &[Candidate], freed: Option<u64>) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let now = SystemTime::now();
+        let name_width = candidates.iter()
+            .map(|c| c.entry.name.len() + c.entry.version.as_ref().map(|v| v.len() + 1).unwrap_or(9))
+            .max()
+            .unwrap_or(0);
+        for c in candidates {
+            let e = &c.entry;
+            let label = match (&e.kind, &e.version) {
+                (CacheKind::Release, Some(v)) => format!("{} {}", e.name, v),
+                _ => format!("{} external", e.name),
+            };
+            let days = now.duration_since(e.last_access).unwrap_or(Duration::ZERO).as_secs() / (24 * 3600);
+            let size = ByteSize(e.size).to_string();
+            println!(
+                "{}  {:>10}  {}  {}",
+                Style::from(Colour::Yellow).paint(format!("{:width$}", label, width = name_width)),
+                size,
+                Style::from(light_gray).paint(format!("last access {:>4} days ago", days)),
+                Style::from(light_gray).paint(c.reasons.join(", "))
+            );
+        }
+        let total: u64 = candidates.iter().map(|c| c.entry.size).sum();
+        let noun = if candidates.len() == 1 { "entry" } else { "entries" };
+        let summary =
+            if let Some(freed) = freed {
+                format!("\nRemoved {} {}, freed {}", candidates.len(), noun, ByteSize(freed))
+            } else {
+                format!(
+                    "\n{} {}, {} reclaimable (dry run, pass --delete to remove them)",
+                    candidates.len(),
+                    noun,
+                    ByteSize(total)
+                )
+            };
+        println!("{}", Style::from(Colour::Green).bold().paint(summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, FileTimes};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+    use crate::lib::pod::version::Version;
+    use crate::lib::util::testing::TempDir;
+    use super::{version_from_slug, CacheKind, Cleaner};
+
+    fn set_time(path: &Path, days_ago: u64) {
+        let t = SystemTime::now() - Duration::from_secs(days_ago * 24 * 3600);
+        File::open(path).unwrap().set_times(FileTimes::new().set_accessed(t).set_modified(t)).unwrap();
+    }
+
+    // AFNetworking 3.2.0 和 external 的缓存 100 天没有访问，其余的刚刚访问过
+    fn cache() -> TempDir {
+        let dir = TempDir::new();
+        for (path, days) in [
+            ("Release/AFNetworking/4.0.1-8fc3d/AFNetworking.h", 0),
+            ("Specs/Release/AFNetworking/4.0.1-8fc3d.podspec.json", 0),
+            ("Release/AFNetworking/3.2.0-1a2b3/AFNetworking.h", 100),
+            ("Specs/Release/AFNetworking/3.2.0-1a2b3.podspec.json", 100),
+            ("Release/Masonry/1.1.0-0c0c0/Masonry.h", 0),
+            ("External/AFNetworking/5f2e0a9c/AFNetworking.h", 100),
+        ] {
+            let file = dir.write(path, "x");
+            set_time(&file, days);
+            set_time(file.parent().unwrap(), days);
+        }
+        dir
+    }
+
+    fn selected(cleaner: &Cleaner) -> Vec<String> {
+        cleaner.plan().unwrap()
+            .iter()
+            .map(|c| match c.entry.kind {
+                CacheKind::Release => format!("{} {}", c.entry.name, c.entry.version.as_deref().unwrap()),
+                CacheKind::External => format!("{} external", c.entry.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slug_without_checksum_is_kept() {
+        assert_eq!(version_from_slug("4.0.1-8fc3d"), "4.0.1");
+        assert_eq!(version_from_slug("1.0.0-beta-1"), "1.0.0-beta-1");
+    }
+
+    #[test]
+    fn source_and_podspec_are_one_entry() {
+        let dir = cache();
+        let mut cleaner = Cleaner::new(dir.path());
+        cleaner.below(Version::parse("4.0")).names(Some(["AFNetworking".to_string()].into()));
+        let candidates = cleaner.plan().unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].entry.paths.len(), 2);
+        assert_eq!(candidates[0].entry.size, 2);
+    }
+
+    #[test]
+    fn below_never_selects_external() {
+        let dir = cache();
+        let mut cleaner = Cleaner::new(dir.path());
+        cleaner.below(Version::parse("100"));
+        assert_eq!(selected(&cleaner), ["AFNetworking 3.2.0", "AFNetworking 4.0.1", "Masonry 1.1.0"]);
+    }
+
+    #[test]
+    fn strategies_must_all_match() {
+        let dir = cache();
+        let mut cleaner = Cleaner::new(dir.path());
+        cleaner.unused_days(Some(30));
+        assert_eq!(selected(&cleaner), ["AFNetworking 3.2.0", "AFNetworking external"]);
+        cleaner.below(Version::parse("5.0"));
+        assert_eq!(selected(&cleaner), ["AFNetworking 3.2.0"]);
+        let candidates = cleaner.plan().unwrap();
+        assert_eq!(candidates[0].reasons, ["not accessed in 100 days", "older than 5.0"]);
+    }
+
+    #[test]
+    fn projects_keep_used_versions() {
+        let dir = cache();
+        let lockfile = dir.write(
+            "App/Podfile.lock",
+            "PODS:\n  - AFNetworking/NSURLSession (3.2.0)\n\nCOCOAPODS: 1.11.0\n"
+        );
+        let mut cleaner = Cleaner::new(dir.path());
+        cleaner.projects(Some(vec![lockfile.parent().unwrap().to_path_buf()]));
+        // external 的缓存只要 pod 还在用就保留
+        assert_eq!(selected(&cleaner), ["AFNetworking 4.0.1", "Masonry 1.1.0"]);
+        cleaner.below(Version::parse("2.0")).names(Some(["Masonry".to_string()].into()));
+        assert_eq!(selected(&cleaner), ["Masonry 1.1.0"]);
+    }
+}
@@ -1,123 +1,323 @@
-use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::cell::RefCell;
-use std::rc::Rc;
 use anyhow::bail;
 use super::lockfile::Lockfile;
-use crate::lib::pod::lockfile::PodItem;
+use crate::lib::dep::{DepGraph, Ecosystem, TravelMode};
+use crate::lib::pod::lockfile::{collapse_subspecs, root_name, Dependency, ExternalSource, Name, PodItem};
+use crate::lib::pod::pattern::match_pods;
+use crate::lib::pod::project::Project;
 
-pub(crate) fn print_deps<P: AsRef<Path>>(
-    path: P,
-    target: &str,
-    max_depth: usize
+pub(crate) fn print_deps(
+    project: &Project,
+    patterns: &[String],
+    max_depth: usize,
+    collapse: bool,
+    combined: bool
 ) -> anyhow::Result<()> {
-    travel_and_print(path, target, max_depth, TravelMode::Children)
+    travel_and_print(project, patterns, max_depth, collapse, combined, TravelMode::Children)
 }
 
-pub(crate) fn print_reserve_deps<P: AsRef<Path>>(
-    path: P,
-    target: &str,
-    max_depth: usize
+pub(crate) fn print_reserve_deps(
+    project: &Project,
+    patterns: &[String],
+    max_depth: usize,
+    collapse: bool,
+    combined: bool
 ) -> anyhow::Result<()> {
-    travel_and_print(path, target, max_depth, TravelMode::Parents)
+    travel_and_print(project, patterns, max_depth, collapse, combined, TravelMode::Parents)
 }
 
-fn travel_and_print<P: AsRef<Path>>(
-    path: P,
-    target: &str,
+// `patterns` 可以是名字、glob 或者正则，见 `Pattern`
+// `combined` 为 true 时把所有目标的闭包合并成一个列表，否则每个目标一棵树
+fn travel_and_print(
+    project: &Project,
+    patterns: &[String],
     max_depth: usize,
+    collapse: bool,
+    combined: bool,
     mode: TravelMode
 ) -> anyhow::Result<()> {
-    let lockfile = Lockfile::from_file(path)?;
+    let collapsed;
+    let pods = if collapse {
+        collapsed = collapse_subspecs(&project.pods);
+        &collapsed
+    } else {
+        &project.pods
+    };
+    let targets = match_pods(pods, patterns, collapse)?;
+    let source_of = |name: &str| source_of(pods, &project.external_sources, name);
+    crate::lib::dep::print_deps(pods, &targets, max_depth, combined, mode, &source_of, Ecosystem::CocoaPods.unit());
+    Ok(())
+}
+
+// SwiftPM 的包标出 ecosystem，pod 标出 external source 的类型
+pub(crate) fn source_of(
+    pods: &HashMap<String, PodItem>,
+    external: &HashMap<String, ExternalSource>,
+    name: &str
+) -> Option<&'static str> {
+    if pods[name].ecosystem != Ecosystem::CocoaPods {
+        return Some(pods[name].ecosystem.name())
+    }
+    external.get(root_name(name)).and_then(|es| {
+        if es.path.is_some() {
+            Some(":path")
+        } else if es.git.is_some() {
+            Some(":git")
+        } else if es.podspec.is_some() {
+            Some(":podspec")
+        } else {
+            None
+        }
+    })
+}
+
+impl DepGraph for HashMap<String, PodItem> {
+    fn names(&self) -> Vec<&str> {
+        let mut names = self.keys().map(|n| n.as_str()).collect::<Vec<&str>>();
+        names.sort();
+        names
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.contains_key(name)
+    }
+
+    fn version(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|p| p.vers.as_deref())
+    }
+
+    fn edges(&self, name: &str, mode: TravelMode) -> Vec<&str> {
+        let pod = match self.get(name) {
+            Some(p) => p,
+            None => return Vec::new(),
+        };
+        let edges = match mode {
+            TravelMode::Parents => &pod.parents,
+            TravelMode::Children => &pod.children,
+        };
+        let mut ret = edges.iter()
+            .map(|e| e.as_ref())
+            .filter(|e| self.contains_key(*e))
+            .collect::<Vec<&str>>();
+        ret.sort();
+        ret
+    }
+}
+
+// 列出 root 下各个 subspec 之间的依赖（rdep 时为反向依赖）
+pub(crate) fn print_subspec_deps<P: AsRef<Path>>(
+    path: P,
+    lenient: bool,
+    root: &str,
+    reverse: bool
+) -> anyhow::Result<()> {
+    let lockfile = Lockfile::from_file(path, lenient)?;
     let pods = lockfile.pods()?;
+    let root = root_name(root);
+    let mut members = pods.values()
+        .filter(|p| p.root_name() == root)
+        .collect::<Vec<&PodItem>>();
+    if members.is_empty() {
+        bail!("Cannot find {} in Podfile.lock", root);
+    }
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut rows = Vec::new();
+    for pod in members {
+        let edges = if reverse { &pod.parents } else { &pod.children };
+        let mut internal = edges.iter()
+            .filter(|e| root_name(e) == root)
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>();
+        internal.sort();
+        let external = edges.len() - internal.len();
+        rows.push((pod.name.as_str(), internal, external));
+    }
+    printer::print_subspec_rows(&rows);
+    Ok(())
+}
+
+// 收集 target 的依赖闭包（包含 target 自身）
+pub(crate) fn dep_closure(
+    pods: &HashMap<String, PodItem>,
+    target: &str
+) -> anyhow::Result<HashSet<String>> {
+    closure(pods, target, TravelMode::Children)
+}
+
+// 收集 target 的反向依赖闭包（包含 target 自身）
+pub(crate) fn rdep_closure(
+    pods: &HashMap<String, PodItem>,
+    target: &str
+) -> anyhow::Result<HashSet<String>> {
+    closure(pods, target, TravelMode::Parents)
+}
+
+fn closure(
+    pods: &HashMap<String, PodItem>,
+    target: &str,
+    mode: TravelMode
+) -> anyhow::Result<HashSet<String>> {
     if !pods.contains_key(target) {
         bail!("Cannot find {} in Podfile.lock", target);
     }
-
-    let mut result = TravelResult::new(RefCell::new(HashMap::new()));
-    let travel = Travel::new(mode,&pods);
-    travel.collect(
-        target.to_string(),
-        Chain { value: "".to_string(), depth: 0 },
-        &result
-    );
-    let r = Rc::borrow(&mut result);
-    let mut r = RefCell::borrow_mut(r);
-    r.remove(&target.to_string());
-    let mut chains = r.values()
-        .map(|s| s.value.as_str())
-        .collect::<Vec<&str>>();
-    chains.sort();
-    printer::print_pretty_chains(chains.into_iter(), max_depth);
-    Ok(())
+    crate::lib::dep::closure(pods, target, mode)
 }
 
+// 路径上的一跳：pod 名、解析出的版本，以及上一跳（或 DEPENDENCIES）对它的要求
 #[derive(Clone)]
-struct Chain {
-    value: String,
-    depth: usize,
+pub(crate) struct Hop {
+    pub(crate) name: String,
+    pub(crate) vers: Option<String>,
+    pub(crate) requirement: Option<String>,
 }
 
-struct Travel<'a> {
-    mode: TravelMode,
-    source: &'a HashMap<String, PodItem>,
+// 列出从 DEPENDENCIES 出发到 target 的所有路径，`limit` 限制路径条数
+pub(crate) fn print_why<P: AsRef<Path>>(
+    path: P,
+    lenient: bool,
+    target: &str,
+    limit: Option<usize>
+) -> anyhow::Result<()> {
+    let lockfile = Lockfile::from_file(path, lenient)?;
+    let pods = lockfile.pods()?;
+    let roots = lockfile.dependencies()?;
+    // 多找一条，用来判断是否有被省略的路径
+    let mut paths = why_paths(&pods, &roots, target, limit.map(|l| l + 1))?;
+    let truncated = limit.map(|l| paths.len() > l).unwrap_or(false);
+    if let Some(l) = limit {
+        paths.truncate(l);
+    }
+    printer::print_why_paths(target, &paths, truncated);
+    Ok(())
 }
 
-enum TravelMode { Parents, Children }
-type TravelResult = Rc<RefCell<HashMap<String, Chain>>>;
+pub(crate) fn why_paths(
+    pods: &HashMap<String, PodItem>,
+    roots: &[Dependency],
+    target: &str,
+    limit: Option<usize>
+) -> anyhow::Result<Vec<Vec<Hop>>> {
+    // 只沿着能到达 target 的 pod 往下走
+    let reachable = rdep_closure(pods, target)?;
+    let mut ret = Vec::new();
+    let mut stack = Vec::new();
+    let mut roots = roots.iter()
+        .filter(|d| reachable.contains(&d.name))
+        .collect::<Vec<&Dependency>>();
+    roots.sort_by(|a, b| a.name.cmp(&b.name));
+    for dep in roots {
+        let requirement = dep.requirement.as_ref()
+            .map(|r| r.to_string())
+            .or_else(|| dep.source.as_ref().map(|s| format!("from {}", s)));
+        stack.push(hop(pods, &dep.name, requirement));
+        collect_why(pods, &reachable, target, limit, &mut stack, &mut ret);
+        stack.pop();
+    }
+    Ok(ret)
+}
 
-impl<'a> Travel<'a> {
-    fn new(mode: TravelMode, source: &'a HashMap<String, PodItem>) -> Self {
-        Self { mode, source }
+fn hop(pods: &HashMap<String, PodItem>, name: &str, requirement: Option<String>) -> Hop {
+    Hop {
+        name: name.to_string(),
+        vers: pods.get(name).and_then(|p| p.vers.clone()),
+        requirement,
     }
+}
+
+fn collect_why(
+    pods: &HashMap<String, PodItem>,
+    reachable: &HashSet<String>,
+    target: &str,
+    limit: Option<usize>,
+    stack: &mut Vec<Hop>,
+    result: &mut Vec<Vec<Hop>>
+) {
+    if limit.map(|l| result.len() >= l).unwrap_or(false) {
+        return
+    }
+    let current = &stack.last().unwrap().name;
+    if current == target {
+        result.push(stack.clone());
+        return
+    }
+    let pod = match pods.get(current) {
+        Some(p) => p,
+        None => return,
+    };
+    let mut children = pod.children.iter()
+        .filter(|c| reachable.contains(c.as_ref()))
+        // 跳过环
+        .filter(|c| !stack.iter().any(|h| h.name.as_str() == c.as_ref()))
+        .collect::<Vec<&Name>>();
+    children.sort();
+    for c in children {
+        stack.push(hop(pods, c, pod.requirements.get(c).map(|r| r.to_string())));
+        collect_why(pods, reachable, target, limit, stack, result);
+        stack.pop();
+    }
+}
+
+mod printer {
+    use std::collections::BTreeSet;
+    use ansi_term::{Colour, Style};
+    use super::Hop;
 
-    fn collect(&self, target: String, chain: Chain, result: &TravelResult) {
-        {
-            let r = Rc::borrow(result);
-            let r = RefCell::borrow(r);
-            if let Some(exist) = r.get(&target) {
-                // 保留路径更短的那一个
-                if exist.depth <= chain.depth {
-                    return;
+    pub(super) fn print_why_paths(target: &str, paths: &[Vec<Hop>], truncated: bool) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if paths.is_empty() {
+            println!("{}", Style::from(light_gray).paint(format!("{} is not reachable from DEPENDENCIES", target)));
+            return
+        }
+        for (i, path) in paths.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("{}", Style::from(Colour::White).bold().paint(format!("Path {}", i + 1)));
+            for (depth, hop) in path.iter().enumerate() {
+                let mut text = hop.name.clone();
+                if let Some(v) = &hop.vers {
+                    text.push_str(&format!(" {}", v));
+                }
+                if let Some(r) = &hop.requirement {
+                    let r = format!(" ({})", r);
+                    text.push_str(&Style::from(light_gray).paint(r).to_string());
                 }
+                print_depth_text(&text, depth + 1);
             }
         }
-        {
-            let mut rt = result.borrow_mut();
-            rt.insert(target.clone(), chain.clone());
-        }
-        let pod = self.source.get(&target).unwrap();
-        let iter =
-            match self.mode {
-                TravelMode::Parents => { &pod.parents },
-                TravelMode::Children => { &pod.children }
-            };
-        for c in iter {
-            let depth: usize = chain.depth + 1;
-            let value = if depth > 1 {
-                format!("{}:{}", chain.value, c)
+        let roots = paths.iter()
+            .map(|p| p[0].name.as_str())
+            .collect::<BTreeSet<&str>>();
+        let summary = format!(
+            "\n{}{} {} to {}, via top-level dependencies: {}",
+            if truncated { "first " } else { "" },
+            paths.len(),
+            if paths.len() == 1 { "path" } else { "paths" },
+            target,
+            roots.into_iter().collect::<Vec<&str>>().join(", ")
+        );
+        let summary =
+            if truncated {
+                format!("{}\nmore paths are omitted, pass --limit 0 to list all", summary)
             } else {
-                c.clone()
+                summary
             };
-            self.collect(c.clone(), Chain { value, depth }, result);
-        }
+        println!("{}", Style::from(light_gray).paint(summary));
     }
-}
-
-mod printer {
-    use ansi_term::{Colour, Style};
 
-    pub(super) fn print_pretty_chains<'a, T>(chains: T, max_depth: usize)
-        where T: IntoIterator<Item = &'a str> {
-        for chain in chains {
-            let comps = chain.split(":").collect::<Vec<&str>>();
-            let depth = comps.len();
-            if depth > max_depth {
-                continue
+    pub(super) fn print_subspec_rows(rows: &[(&str, Vec<&str>, usize)]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        for (name, internal, external) in rows {
+            let mut text = name.to_string();
+            if *external > 0 {
+                let other = format!(" (+{} outside)", external);
+                text.push_str(&Style::from(light_gray).paint(other).to_string());
+            }
+            print_depth_text(&text, 1);
+            for sub in internal {
+                print_depth_text(sub, 2);
             }
-            let text= comps.last().unwrap();
-            print_depth_text(text, depth);
         }
     }
 
@@ -137,4 +337,99 @@ mod printer {
             Style::from(color).paint(text).to_string()
         );
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::{Dependency, Lockfile, PodItem};
+    use crate::lib::dep::Ecosystem;
+    use super::{dep_closure, rdep_closure, source_of, why_paths};
+
+    // App 依赖 A、B；A、B 都依赖 C，C 依赖 D；D 和 E 互相依赖
+    const LOCKFILE: &str = "
+PODS:
+  - A (1.0.0):
+    - C (~> 2.0)
+  - B (1.0.0):
+    - C
+    - D
+  - C (2.1.0):
+    - D
+  - D (3.0.0):
+    - E
+  - E (1.0.0):
+    - D
+  - F (1.0.0)
+
+DEPENDENCIES:
+  - A (~> 1.0)
+  - B
+  - F
+
+COCOAPODS: 1.11.0
+";
+
+    fn load() -> (HashMap<String, PodItem>, Vec<Dependency>) {
+        let lockfile = Lockfile::from_yaml_str(LOCKFILE).unwrap();
+        (lockfile.pods().unwrap(), lockfile.dependencies().unwrap())
+    }
+
+    fn names(paths: &[Vec<super::Hop>]) -> Vec<String> {
+        paths.iter()
+            .map(|p| p.iter().map(|h| h.name.as_str()).collect::<Vec<&str>>().join(" -> "))
+            .collect()
+    }
+
+    #[test]
+    fn closures() {
+        let (pods, _) = load();
+        let mut deps = dep_closure(&pods, "C").unwrap().into_iter().collect::<Vec<String>>();
+        deps.sort();
+        assert_eq!(deps, ["C", "D", "E"]);
+        let mut rdeps = rdep_closure(&pods, "C").unwrap().into_iter().collect::<Vec<String>>();
+        rdeps.sort();
+        assert_eq!(rdeps, ["A", "B", "C"]);
+        assert!(dep_closure(&pods, "Z").is_err());
+    }
+
+    #[test]
+    fn every_simple_path() {
+        let (pods, roots) = load();
+        let paths = why_paths(&pods, &roots, "E", None).unwrap();
+        assert_eq!(names(&paths), ["A -> C -> D -> E", "B -> C -> D -> E", "B -> D -> E"]);
+        // 顶层依赖的要求来自 DEPENDENCIES
+        assert_eq!(paths[0][0].requirement.as_deref(), Some("~> 1.0"));
+        assert_eq!(paths[0][2].vers.as_deref(), Some("3.0.0"));
+    }
+
+    #[test]
+    fn limit_stops_early() {
+        let (pods, roots) = load();
+        let paths = why_paths(&pods, &roots, "E", Some(2)).unwrap();
+        assert_eq!(names(&paths), ["A -> C -> D -> E", "B -> C -> D -> E"]);
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let (pods, roots) = load();
+        assert!(why_paths(&pods, &roots, "A", None).unwrap().len() == 1);
+        let lockfile = Lockfile::from_yaml_str(&LOCKFILE.replace("  - F\n", "")).unwrap();
+        let paths = why_paths(&pods, &lockfile.dependencies().unwrap(), "F", None).unwrap();
+        assert!(paths.is_empty());
+    }
+
+    #[test]
+    fn sources() {
+        let lockfile = Lockfile::from_yaml_str(&format!(
+            "{}\nEXTERNAL SOURCES:\n  C:\n    :path: \"./C\"\n  D:\n    :git: https://x.com/D.git\n",
+            LOCKFILE.replace("COCOAPODS: 1.11.0\n", "")
+        )).unwrap();
+        let mut pods = lockfile.pods().unwrap();
+        pods.get_mut("E").unwrap().ecosystem = Ecosystem::SwiftPM;
+        let external = lockfile.external_sources().unwrap();
+        let sources = ["A", "C", "D", "E"].iter()
+            .map(|n| source_of(&pods, &external, n))
+            .collect::<Vec<Option<&str>>>();
+        assert_eq!(sources, [None, Some(":path"), Some(":git"), Some("swiftpm")]);
+    }
+}
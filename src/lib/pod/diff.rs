@@ -0,0 +1,292 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::process;
+use anyhow::{anyhow, bail};
+use serde_json::{json, Value};
+use crate::lib::pod::located::Source;
+use crate::lib::pod::lockfile::{Lockfile, PodItem};
+use crate::lib::pod::version::Version;
+
+// pod diff
+// 比较两个 Podfile.lock：pod 的增删、升降级，以及依赖边的增删
+
+pub(crate) enum PodChange {
+    Added(Option<String>),
+    Removed(Option<String>),
+    Upgraded(String, String),
+    Downgraded(String, String),
+    // 版本号无法比较大小，譬如 external source 的版本
+    Changed(Option<String>, Option<String>),
+}
+
+pub(crate) struct LockfileDiff {
+    pub(crate) pods: BTreeMap<String, PodChange>,
+    pub(crate) added_edges: BTreeSet<(String, String)>,
+    pub(crate) removed_edges: BTreeSet<(String, String)>,
+}
+
+impl LockfileDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pods.is_empty() && self.added_edges.is_empty() && self.removed_edges.is_empty()
+    }
+}
+
+// 读取 Podfile.lock，`spec` 可以是文件路径，也可以是 `rev:path` 形式的 git revision
+pub(crate) fn load_lockfile(spec: &str, lenient: bool) -> anyhow::Result<Lockfile> {
+    if Path::new(spec).exists() {
+        return Lockfile::from_file(spec, lenient)
+    }
+    let (rev, path) = spec.split_once(':')
+        .ok_or_else(|| anyhow!("{:?} is not exists", spec))?;
+    let path = if path.is_empty() { "Podfile.lock" } else { path };
+    // `rev:./path` 相对于当前目录，`rev:path` 相对于仓库根目录，和 git show 一致
+    let output = process::Command::new("git")
+        .arg("show")
+        .arg(format!("{}:{}", rev, path))
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "git show {}:{} failed: {}",
+            rev,
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let text = String::from_utf8_lossy(&output.stdout).into_owned();
+    Lockfile::from_source(Source { name: spec.to_string(), text }, lenient)
+}
+
+pub(crate) fn diff(old: &Lockfile, new: &Lockfile) -> anyhow::Result<LockfileDiff> {
+    let (old, new) = (old.pods()?, new.pods()?);
+    let mut pods = BTreeMap::new();
+    for (name, item) in &new {
+        match old.get(name) {
+            None => {
+                pods.insert(name.clone(), PodChange::Added(item.vers.clone()));
+            },
+            Some(o) if o.vers != item.vers => {
+                pods.insert(name.clone(), version_change(o.vers.clone(), item.vers.clone()));
+            },
+            _ => {},
+        }
+    }
+    for (name, item) in &old {
+        if !new.contains_key(name) {
+            pods.insert(name.clone(), PodChange::Removed(item.vers.clone()));
+        }
+    }
+    let (old_edges, new_edges) = (edges(&old), edges(&new));
+    let added_edges = new_edges.difference(&old_edges).cloned().collect();
+    let removed_edges = old_edges.difference(&new_edges).cloned().collect();
+    Ok(LockfileDiff { pods, added_edges, removed_edges })
+}
+
+fn version_change(old: Option<String>, new: Option<String>) -> PodChange {
+    let parsed = (
+        old.as_deref().and_then(Version::parse),
+        new.as_deref().and_then(Version::parse),
+    );
+    match (parsed, old, new) {
+        ((Some(o), Some(n)), Some(old), Some(new)) => {
+            match o.cmp(&n) {
+                Ordering::Less => PodChange::Upgraded(old, new),
+                Ordering::Greater => PodChange::Downgraded(old, new),
+                Ordering::Equal => PodChange::Changed(Some(old), Some(new)),
+            }
+        },
+        (_, old, new) => PodChange::Changed(old, new),
+    }
+}
+
+fn edges(pods: &HashMap<String, PodItem>) -> BTreeSet<(String, String)> {
+    pods.values()
+        .flat_map(|p| p.children.iter().map(move |c| (p.name.clone(), c.to_string())))
+        .collect()
+}
+
+pub(crate) fn to_json(diff: &LockfileDiff) -> Value {
+    let mut added = vec![];
+    let mut removed = vec![];
+    let mut upgraded = vec![];
+    let mut downgraded = vec![];
+    let mut changed = vec![];
+    for (name, change) in &diff.pods {
+        match change {
+            PodChange::Added(v) => added.push(json!({ "name": name, "version": v })),
+            PodChange::Removed(v) => removed.push(json!({ "name": name, "version": v })),
+            PodChange::Upgraded(o, n) => upgraded.push(json!({ "name": name, "from": o, "to": n })),
+            PodChange::Downgraded(o, n) => downgraded.push(json!({ "name": name, "from": o, "to": n })),
+            PodChange::Changed(o, n) => changed.push(json!({ "name": name, "from": o, "to": n })),
+        }
+    }
+    let edge_json = |edges: &BTreeSet<(String, String)>| -> Vec<Value> {
+        edges.iter().map(|(from, to)| json!({ "from": from, "to": to })).collect()
+    };
+    json!({
+        "pods": {
+            "added": added,
+            "removed": removed,
+            "upgraded": upgraded,
+            "downgraded": downgraded,
+            "changed": changed,
+        },
+        "edges": {
+            "added": edge_json(&diff.added_edges),
+            "removed": edge_json(&diff.removed_edges),
+        },
+    })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::{LockfileDiff, PodChange};
+
+    pub(crate) fn print_diff(diff: &LockfileDiff) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if diff.is_empty() {
+            println!("{}", Style::from(light_gray).paint("No changes"));
+            return
+        }
+        let count = |f: fn(&PodChange) -> bool| diff.pods.values().filter(|c| f(c)).count();
+        println!(
+            "{} {}",
+            Style::from(Colour::White).bold().paint("Pods"),
+            Style::from(light_gray).paint(format!(
+                "({} added, {} removed, {} upgraded, {} downgraded, {} changed)",
+                count(|c| matches!(c, PodChange::Added(_))),
+                count(|c| matches!(c, PodChange::Removed(_))),
+                count(|c| matches!(c, PodChange::Upgraded(..))),
+                count(|c| matches!(c, PodChange::Downgraded(..))),
+                count(|c| matches!(c, PodChange::Changed(..))),
+            ))
+        );
+        let vers = |v: &Option<String>| v.clone().unwrap_or_else(|| "?".to_string());
+        for (name, change) in &diff.pods {
+            let (bullet, color, text) = match change {
+                PodChange::Added(v) => ("+", Colour::Green, format!("{} {}", name, vers(v))),
+                PodChange::Removed(v) => ("-", Colour::Red, format!("{} {}", name, vers(v))),
+                PodChange::Upgraded(o, n) => ("↑", Colour::Yellow, format!("{} {} -> {}", name, o, n)),
+                PodChange::Downgraded(o, n) => ("↓", Colour::Purple, format!("{} {} -> {}", name, o, n)),
+                PodChange::Changed(o, n) => ("~", Colour::Cyan, format!("{} {} -> {}", name, vers(o), vers(n))),
+            };
+            print_line(bullet, color, &text);
+        }
+        if diff.added_edges.is_empty() && diff.removed_edges.is_empty() {
+            return
+        }
+        println!();
+        println!(
+            "{} {}",
+            Style::from(Colour::White).bold().paint("Dependencies"),
+            Style::from(light_gray).paint(format!(
+                "({} added, {} removed)",
+                diff.added_edges.len(),
+                diff.removed_edges.len()
+            ))
+        );
+        for (from, to) in &diff.added_edges {
+            print_line("+", Colour::Green, &format!("{} -> {}", from, to));
+        }
+        for (from, to) in &diff.removed_edges {
+            print_line("-", Colour::Red, &format!("{} -> {}", from, to));
+        }
+    }
+
+    fn print_line(bullet: &str, color: Colour, text: &str) {
+        println!(
+            "    {} {}",
+            Style::from(color).bold().paint(bullet),
+            Style::from(color).paint(text)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::pod::lockfile::Lockfile;
+    use super::{diff, to_json, PodChange};
+
+    const OLD: &str = "
+PODS:
+  - AFNetworking (3.2.0):
+    - AFNetworking/NSURLSession (= 3.2.0)
+  - AFNetworking/NSURLSession (3.2.0)
+  - Masonry (1.1.0)
+  - SDWebImage (5.10.0)
+  - YYModel (1.0.4)
+
+COCOAPODS: 1.11.0
+";
+
+    const NEW: &str = "
+PODS:
+  - AFNetworking (4.0.1):
+    - AFNetworking/NSURLSession (= 4.0.1)
+    - AFNetworking/Reachability (= 4.0.1)
+  - AFNetworking/NSURLSession (4.0.1)
+  - AFNetworking/Reachability (4.0.1)
+  - Masonry (1.1.0)
+  - SDWebImage (5.9.5)
+  - SnapKit (5.6.0)
+  - YYModel (1.0.4.0)
+
+COCOAPODS: 1.11.0
+";
+
+    fn lockfile(text: &str) -> Lockfile {
+        Lockfile::from_yaml_str(text).unwrap()
+    }
+
+    #[test]
+    fn pod_changes() {
+        let result = diff(&lockfile(OLD), &lockfile(NEW)).unwrap();
+        let changes = result.pods.iter()
+            .map(|(name, change)| {
+                let text = match change {
+                    PodChange::Added(v) => format!("+ {:?}", v),
+                    PodChange::Removed(v) => format!("- {:?}", v),
+                    PodChange::Upgraded(o, n) => format!("{} -> {}", o, n),
+                    PodChange::Downgraded(o, n) => format!("{} <- {}", n, o),
+                    PodChange::Changed(o, n) => format!("{:?} ~ {:?}", o, n),
+                };
+                format!("{}: {}", name, text)
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(changes, [
+            "AFNetworking: 3.2.0 -> 4.0.1",
+            "AFNetworking/NSURLSession: 3.2.0 -> 4.0.1",
+            "AFNetworking/Reachability: + Some(\"4.0.1\")",
+            "SDWebImage: 5.9.5 <- 5.10.0",
+            "SnapKit: + Some(\"5.6.0\")",
+            // 版本号写法不同但大小相等
+            "YYModel: Some(\"1.0.4\") ~ Some(\"1.0.4.0\")",
+        ]);
+    }
+
+    #[test]
+    fn removed_pods_and_edges() {
+        let result = diff(&lockfile(NEW), &lockfile(OLD)).unwrap();
+        assert!(matches!(result.pods["SnapKit"], PodChange::Removed(Some(ref v)) if v == "5.6.0"));
+        assert!(matches!(result.pods["SDWebImage"], PodChange::Upgraded(..)));
+        assert!(result.added_edges.is_empty());
+        let removed = result.removed_edges.iter().cloned().collect::<Vec<(String, String)>>();
+        assert_eq!(removed, [("AFNetworking".to_string(), "AFNetworking/Reachability".to_string())]);
+    }
+
+    #[test]
+    fn same_lockfile_is_empty() {
+        let result = diff(&lockfile(OLD), &lockfile(OLD)).unwrap();
+        assert!(result.is_empty());
+        let json = to_json(&result);
+        assert_eq!(json["pods"]["added"].as_array().map(|a| a.len()), Some(0));
+    }
+
+    #[test]
+    fn json_groups_changes() {
+        let json = to_json(&diff(&lockfile(OLD), &lockfile(NEW)).unwrap());
+        assert_eq!(json["pods"]["downgraded"][0]["name"], "SDWebImage");
+        assert_eq!(json["pods"]["downgraded"][0]["from"], "5.10.0");
+        assert_eq!(json["edges"]["added"][0]["to"], "AFNetworking/Reachability");
+    }
+}
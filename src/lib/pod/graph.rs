@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde_json::{json, Value};
+use crate::lib::dep::Ecosystem;
+use crate::lib::pod::lockfile::{Dependency, PodItem};
+
+// pod graph
+// 把 Podfile.lock 的依赖图导出为 Graphviz DOT、Mermaid 或 JSON
+// SwiftPM 的包在 DOT 里是圆角框，在 Mermaid 里是胶囊形，JSON 里给出 `ecosystem`
+
+#[derive(Clone, Copy)]
+pub(crate) enum Format { Dot, Mermaid, Json }
+
+impl Format {
+    pub(crate) const NAMES: [&'static str; 3] = ["dot", "mermaid", "json"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dot" => Some(Format::Dot),
+            "mermaid" => Some(Format::Mermaid),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct Node {
+    pub(crate) vers: Option<String>,
+    pub(crate) ecosystem: Ecosystem,
+}
+
+pub(crate) struct Graph {
+    pub(crate) nodes: BTreeMap<String, Node>,
+    // (parent, child)
+    pub(crate) edges: BTreeSet<(String, String)>,
+}
+
+pub(crate) struct GraphBuilder<'a> {
+    pods: &'a HashMap<String, PodItem>,
+    // 以这些 pod 为中心，`reverse` 为 true 时沿反向依赖展开
+    centers: Option<(Vec<String>, bool)>,
+    max_depth: Option<usize>,
+    includes: Option<GlobSet>,
+    excludes: Option<GlobSet>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    pub(crate) fn new(pods: &'a HashMap<String, PodItem>) -> Self {
+        GraphBuilder { pods, centers: None, max_depth: None, includes: None, excludes: None }
+    }
+
+    // `names` 为空时导出整个依赖图
+    pub(crate) fn centers(&mut self, names: Vec<String>, reverse: bool) -> &mut Self {
+        self.centers = if names.is_empty() { None } else { Some((names, reverse)) };
+        self
+    }
+
+    pub(crate) fn max_depth(&mut self, depth: Option<usize>) -> &mut Self {
+        self.max_depth = depth;
+        self
+    }
+
+    pub(crate) fn includes(&mut self, patterns: &[String]) -> anyhow::Result<&mut Self> {
+        self.includes = glob_set(patterns)?;
+        Ok(self)
+    }
+
+    pub(crate) fn excludes(&mut self, patterns: &[String]) -> anyhow::Result<&mut Self> {
+        self.excludes = glob_set(patterns)?;
+        Ok(self)
+    }
+
+    // `roots` 为 DEPENDENCIES，未指定中心 pod 时用于计算深度
+    pub(crate) fn build(&self, roots: &[Dependency]) -> anyhow::Result<Graph> {
+        let (starts, reverse) = match &self.centers {
+            Some((names, reverse)) => {
+                if let Some(name) = names.iter().find(|n| !self.pods.contains_key(*n)) {
+                    anyhow::bail!("Cannot find {} in Podfile.lock", name);
+                }
+                (names.clone(), *reverse)
+            },
+            None => {
+                let mut starts = roots.iter()
+                    .map(|d| d.name.clone())
+                    .filter(|n| self.pods.contains_key(n))
+                    .collect::<Vec<String>>();
+                // 没有 DEPENDENCIES 或者不限深度时，直接取全部 pod
+                if starts.is_empty() || self.max_depth.is_none() {
+                    starts = self.pods.keys().cloned().collect();
+                }
+                (starts, false)
+            },
+        };
+
+        // BFS，记录每个 pod 的最短深度
+        let mut depths = HashMap::<&str, usize>::new();
+        let mut queue = VecDeque::new();
+        for s in &starts {
+            depths.insert(s.as_str(), 0);
+            queue.push_back(s.as_str());
+        }
+        while let Some(name) = queue.pop_front() {
+            let depth = depths[name];
+            if self.max_depth.map(|m| depth >= m).unwrap_or(false) {
+                continue
+            }
+            let pod = &self.pods[name];
+            let next = if reverse { &pod.parents } else { &pod.children };
+            for n in next {
+                let n = n.as_ref();
+                if !depths.contains_key(n) && self.pods.contains_key(n) {
+                    depths.insert(n, depth + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        let keep = |name: &str| -> bool {
+            let included = self.includes.as_ref().map(|g| g.is_match(name)).unwrap_or(true);
+            let excluded = self.excludes.as_ref().map(|g| g.is_match(name)).unwrap_or(false);
+            let is_center = self.centers.as_ref().map(|(c, _)| c.iter().any(|c| c == name)).unwrap_or(false);
+            is_center || (included && !excluded)
+        };
+        let nodes = depths.keys()
+            .filter(|n| keep(n))
+            .map(|n| {
+                let pod = &self.pods[*n];
+                (n.to_string(), Node { vers: pod.vers.clone(), ecosystem: pod.ecosystem })
+            })
+            .collect::<BTreeMap<String, Node>>();
+        let mut edges = BTreeSet::new();
+        for name in nodes.keys() {
+            for c in &self.pods[name].children {
+                if nodes.contains_key(c.as_ref()) {
+                    edges.insert((name.clone(), c.to_string()));
+                }
+            }
+        }
+        Ok(Graph { nodes, edges })
+    }
+}
+
+fn glob_set(patterns: &[String]) -> anyhow::Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None)
+    }
+    let mut builder = GlobSetBuilder::new();
+    for p in patterns {
+        builder.add(Glob::new(p)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+pub(crate) fn render(graph: &Graph, format: Format, versions: bool) -> String {
+    let label = |name: &str, vers: &Option<String>, sep: &str, escape: &dyn Fn(&str) -> String| -> String {
+        match (versions, vers) {
+            (true, Some(v)) => format!("{}{}{}", escape(name), sep, escape(v)),
+            _ => escape(name),
+        }
+    };
+    match format {
+        Format::Dot => {
+            let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+            let mut out = String::from("digraph pods {\n    rankdir=LR;\n    node [shape=box];\n");
+            for (name, node) in &graph.nodes {
+                let style = if node.ecosystem == Ecosystem::SwiftPM { ", style=rounded" } else { "" };
+                out.push_str(&format!(
+                    "    \"{}\" [label=\"{}\"{}];\n",
+                    escape(name),
+                    label(name, &node.vers, "\\n", &escape),
+                    style
+                ));
+            }
+            for (from, to) in &graph.edges {
+                out.push_str(&format!("    \"{}\" -> \"{}\";\n", escape(from), escape(to)));
+            }
+            out.push_str("}\n");
+            out
+        },
+        Format::Mermaid => {
+            // mermaid 的 id 不能包含 `/` 等字符，统一用序号
+            let ids = graph.nodes.keys()
+                .enumerate()
+                .map(|(i, n)| (n.as_str(), format!("n{}", i)))
+                .collect::<HashMap<&str, String>>();
+            let mut out = String::from("graph LR\n");
+            for (name, node) in &graph.nodes {
+                let text = label(name, &node.vers, " ", &|s: &str| s.replace('"', "#quot;"));
+                let (open, close) = if node.ecosystem == Ecosystem::SwiftPM { ("([", "])") } else { ("[", "]") };
+                out.push_str(&format!("    {}{}\"{}\"{}\n", ids[name.as_str()], open, text, close));
+            }
+            for (from, to) in &graph.edges {
+                out.push_str(&format!("    {} --> {}\n", ids[from.as_str()], ids[to.as_str()]));
+            }
+            out
+        },
+        Format::Json => {
+            let nodes = graph.nodes.iter()
+                .map(|(name, node)| json!({ "id": name, "version": node.vers, "ecosystem": node.ecosystem.name() }))
+                .collect::<Vec<Value>>();
+            let edges = graph.edges.iter()
+                .map(|(from, to)| json!({ "from": from, "to": to }))
+                .collect::<Vec<Value>>();
+            let value = json!({ "nodes": nodes, "edges": edges });
+            serde_json::to_string_pretty(&value).unwrap_or_default() + "\n"
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::{Lockfile, PodItem};
+    use super::{render, Format, GraphBuilder};
+
+    const LOCKFILE: &str = "
+PODS:
+  - A (1.0.0):
+    - C
+  - B (1.0.0):
+    - C
+  - C (2.0.0):
+    - D
+  - D (3.0.0)
+
+DEPENDENCIES:
+  - A
+  - B
+
+COCOAPODS: 1.11.0
+";
+
+    fn pods() -> HashMap<String, PodItem> {
+        Lockfile::from_yaml_str(LOCKFILE).unwrap().pods().unwrap()
+    }
+
+    fn edges(builder: &GraphBuilder) -> Vec<String> {
+        builder.build(&[]).unwrap().edges.iter().map(|(a, b)| format!("{}->{}", a, b)).collect()
+    }
+
+    #[test]
+    fn several_centers() {
+        let pods = pods();
+        let mut builder = GraphBuilder::new(&pods);
+        builder.centers(vec!["A".to_string(), "B".to_string()], false).max_depth(Some(1));
+        assert_eq!(edges(&builder), ["A->C", "B->C"]);
+        builder.centers(vec!["D".to_string()], true).max_depth(None);
+        assert_eq!(edges(&builder), ["A->C", "B->C", "C->D"]);
+        builder.centers(vec!["Z".to_string()], false);
+        assert!(builder.build(&[]).is_err());
+    }
+
+    #[test]
+    fn filters_keep_centers() {
+        let pods = pods();
+        let mut builder = GraphBuilder::new(&pods);
+        builder.centers(vec!["C".to_string()], false);
+        builder.excludes(&["C".to_string(), "A".to_string()]).unwrap();
+        let graph = builder.build(&[]).unwrap();
+        assert_eq!(graph.nodes.keys().cloned().collect::<Vec<String>>(), ["C", "D"]);
+    }
+
+    #[test]
+    fn render_formats() {
+        let pods = pods();
+        let mut builder = GraphBuilder::new(&pods);
+        builder.includes(&["[CD]".to_string()]).unwrap();
+        let graph = builder.build(&[]).unwrap();
+        let dot = render(&graph, Format::Dot, true);
+        assert!(dot.contains("\"C\" -> \"D\";"));
+        assert!(dot.contains("\"D\" [label=\"D\\n3.0.0\"];"));
+        let mermaid = render(&graph, Format::Mermaid, false);
+        assert!(mermaid.contains("n0 --> n1"));
+        let json = render(&graph, Format::Json, false);
+        assert!(json.contains("\"from\": \"C\""));
+    }
+}
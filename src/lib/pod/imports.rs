@@ -0,0 +1,316 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use anyhow::bail;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::lib::pod::affected::normalize;
+use crate::lib::pod::lockfile::{root_name, Lockfile, PodItem};
+use crate::lib::util::walker::{Walker, WakerEntry};
+
+// pod imports
+// PODS 里的边只是 podspec 声明的依赖，源码里 import 了却没有声明的 pod 只是碰巧能编译
+// 扫描 `:path` 引入的开发 pod 的源码，和声明的依赖对比：
+// - import 了但是没有声明
+// - 声明了但是从来没有 import
+
+const SOURCE_EXTENSIONS: [&str; 5] = ["h", "m", "mm", "swift", "pch"];
+
+// `#import <AFNetworking/AFNetworking.h>`、`#include "Foo/Bar.h"`，只取带目录的
+static HEADER_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*#\s*(?:import|include)\s*[<"]([^/>"]+)/[^>"]*[>"]"#).unwrap()
+});
+// `@import AFNetworking;`、`@import Firebase.Core;`
+static MODULE_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*@import\s+([A-Za-z_]\w*)").unwrap()
+});
+// `import Alamofire`、`@testable import Foo`、`import struct Foo.Bar`
+static SWIFT_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:@\w+(?:\([^)]*\))?\s+)*import\s+(?:(?:typealias|struct|class|enum|protocol|let|var|func)\s+)?([A-Za-z_]\w*)").unwrap()
+});
+
+// 源码里的一处 import
+pub(crate) struct Location {
+    // 相对于 `scan` 的 `base`
+    pub(crate) file: PathBuf,
+    pub(crate) line: usize,
+}
+
+pub(crate) struct PodImports {
+    // 开发 pod 的 root spec
+    pub(crate) name: String,
+    pub(crate) dir: PathBuf,
+    // 扫描的源文件数量
+    pub(crate) files: usize,
+    // import 了但没有声明的 pod，以及第一次 import 的位置
+    pub(crate) undeclared: Vec<(String, Location)>,
+    // 声明了但没有 import 的 pod
+    pub(crate) unused: Vec<String>,
+}
+
+// `dir` 为 Podfile.lock 所在的目录，`:path` 相对于它
+// `modules` 为模块名到 pod 的映射，补充模块名和 pod 名不一致的情况，譬如 `Lottie=lottie-ios`
+pub(crate) fn imports(
+    lockfile: &Lockfile,
+    dir: &Path,
+    modules: &HashMap<String, String>
+) -> anyhow::Result<Vec<PodImports>> {
+    let pods = lockfile.pods()?;
+    let module_map = module_map(&pods, modules);
+    let mut dev_pods = lockfile.external_sources()?
+        .into_values()
+        .filter_map(|es| es.path.map(|p| (es.name, normalize(&dir.join(p)))))
+        .collect::<Vec<(String, PathBuf)>>();
+    dev_pods.sort();
+
+    let mut ret = Vec::new();
+    for (name, pod_dir) in &dev_pods {
+        if !pod_dir.is_dir() {
+            bail!("{:?} is not exists, it is the path of {}", pod_dir, name);
+        }
+        // 嵌套在里面的其他开发 pod 不算
+        let nested = dev_pods.iter()
+            .filter(|(n, p)| n != name && p.starts_with(pod_dir))
+            .map(|(_, p)| p.clone())
+            .collect::<Vec<PathBuf>>();
+        let files = source_files(pod_dir, &nested)?;
+        let mut imported = scan(&files, &module_map, dir);
+        imported.remove(name);
+
+        // 所有 subspec 声明的依赖，按 root spec 合并
+        let declared = pods.values()
+            .filter(|p| p.root_name() == name)
+            .flat_map(|p| p.children.iter())
+            .map(|c| root_name(c).to_string())
+            .filter(|c| c != name)
+            .collect::<BTreeSet<String>>();
+        let unused = declared.iter()
+            .filter(|d| !imported.contains_key(*d))
+            .cloned()
+            .collect();
+        let undeclared = imported.into_iter()
+            .filter(|(p, _)| !declared.contains(p))
+            .collect();
+        ret.push(PodImports {
+            name: name.clone(),
+            dir: pod_dir.strip_prefix(dir).unwrap_or(pod_dir).to_path_buf(),
+            files: files.len(),
+            undeclared,
+            unused,
+        });
+    }
+    Ok(ret)
+}
+
+// 模块名 -> pod（root spec）：同名，或者把非字母数字换成 `_`（CocoaPods 生成模块名的规则）
+// `modules` 里指定的映射优先
+pub(crate) fn module_map(
+    pods: &HashMap<String, PodItem>,
+    modules: &HashMap<String, String>
+) -> HashMap<String, String> {
+    let mut ret = HashMap::new();
+    for name in pods.keys() {
+        let root = root_name(name);
+        ret.insert(root.to_string(), root.to_string());
+        ret.insert(c99ext_identifier(root), root.to_string());
+    }
+    for (module, pod) in modules {
+        ret.insert(module.clone(), root_name(pod).to_string());
+    }
+    ret
+}
+
+// 源文件里 import 的 pod 和第一次 import 的位置，位置相对于 `base`
+// 不是 pod 的模块（系统库等）不关心
+pub(crate) fn scan(
+    files: &[PathBuf],
+    module_map: &HashMap<String, String>,
+    base: &Path
+) -> BTreeMap<String, Location> {
+    let mut ret = BTreeMap::<String, Location>::new();
+    for file in files {
+        let text = match std::fs::read(file) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+            Err(_) => continue,
+        };
+        for (i, line) in text.lines().enumerate() {
+            let pod = match import_of(line).and_then(|m| module_map.get(m)) {
+                Some(p) => p,
+                None => continue,
+            };
+            ret.entry(pod.clone()).or_insert_with(|| Location {
+                file: file.strip_prefix(base).unwrap_or(file).to_path_buf(),
+                line: i + 1,
+            });
+        }
+    }
+    ret
+}
+
+// 目录下的源文件，按 .gitignore 过滤，跳过 Pods/（譬如 Example 工程带的）和 `excluded` 里的目录
+pub(crate) fn source_files(dir: &Path, excluded: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let found = RefCell::new(Vec::<PathBuf>::new());
+    let handle = |entry: WakerEntry| {
+        let path = entry.path();
+        let is_file = entry.file_type().map(|ft| ft.is_file()).unwrap_or(false);
+        let matched = path.extension()
+            .map(|e| SOURCE_EXTENSIONS.iter().any(|x| *x == e.to_string_lossy()))
+            .unwrap_or(false);
+        if !is_file || !matched || excluded.iter().any(|n| path.starts_with(n)) {
+            return
+        }
+        let in_pods = path.strip_prefix(dir)
+            .map(|p| p.iter().any(|c| c == "Pods"))
+            .unwrap_or(false);
+        if !in_pods {
+            found.borrow_mut().push(path);
+        }
+    };
+    Walker::new(dir).start(&handle)?;
+    let mut files = found.into_inner();
+    files.sort();
+    Ok(files)
+}
+
+// 一行里 import 的模块名
+fn import_of(line: &str) -> Option<&str> {
+    [&HEADER_IMPORT_RE, &MODULE_IMPORT_RE, &SWIFT_IMPORT_RE]
+        .iter()
+        .find_map(|re| re.captures(line))
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str())
+}
+
+// `GoogleUtilities-Logger` -> `GoogleUtilities_Logger`
+fn c99ext_identifier(name: &str) -> String {
+    let mut ret = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if ret.starts_with(|c: char| c.is_ascii_digit()) {
+        ret.insert(0, '_');
+    }
+    ret
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::PodImports;
+
+    pub(crate) fn print_imports(result: &[PodImports]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let mut undeclared = 0;
+        let mut unused = 0;
+        for pod in result {
+            println!(
+                "{} {}",
+                Style::from(Colour::Green).bold().paint(&pod.name),
+                Style::from(light_gray).paint(format!("({}, {} files)", pod.dir.display(), pod.files))
+            );
+            if pod.undeclared.is_empty() && pod.unused.is_empty() {
+                println!("    {}", Style::from(light_gray).paint("imports match declared dependencies"));
+            }
+            for (name, loc) in &pod.undeclared {
+                println!(
+                    "    {} {}  {}",
+                    Style::from(Colour::Red).bold().paint("+"),
+                    Style::from(Colour::Yellow).paint(name),
+                    Style::from(light_gray).paint(format!("not declared, imported at {}:{}", loc.file.display(), loc.line))
+                );
+            }
+            for name in &pod.unused {
+                println!(
+                    "    {} {}  {}",
+                    Style::from(Colour::Red).bold().paint("-"),
+                    Style::from(Colour::Yellow).paint(name),
+                    Style::from(light_gray).paint("declared but never imported")
+                );
+            }
+            undeclared += pod.undeclared.len();
+            unused += pod.unused.len();
+        }
+        eprintln!(
+            "{}",
+            Style::from(light_gray).paint(format!(
+                "{} development pods, {} undeclared imports, {} unused dependencies",
+                result.len(),
+                undeclared,
+                unused
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use crate::lib::pod::lockfile::Lockfile;
+    use crate::lib::util::testing::TempDir;
+    use super::{c99ext_identifier, import_of, imports};
+
+    #[test]
+    fn import_lines() {
+        let cases = [
+            ("#import <AFNetworking/AFNetworking.h>", Some("AFNetworking")),
+            ("  # include \"Masonry/View+MASAdditions.h\"", Some("Masonry")),
+            ("@import Firebase.Core;", Some("Firebase")),
+            ("import Alamofire", Some("Alamofire")),
+            ("@testable import RBFoundation", Some("RBFoundation")),
+            ("@_implementationOnly import Kingfisher", Some("Kingfisher")),
+            ("import struct SnapKit.ConstraintMaker", Some("SnapKit")),
+            // 不带目录的头文件是 pod 自己的
+            ("#import \"RBView.h\"", None),
+            ("// import Alamofire", None),
+            ("let important = 1", None),
+        ];
+        for (line, module) in cases {
+            assert_eq!(import_of(line), module, "{}", line);
+        }
+        assert_eq!(c99ext_identifier("GoogleUtilities-Logger"), "GoogleUtilities_Logger");
+        assert_eq!(c99ext_identifier("1PasswordExtension"), "_1PasswordExtension");
+    }
+
+    #[test]
+    fn undeclared_and_unused() {
+        let tmp = TempDir::new();
+        tmp.write("Modules/RBHome/Classes/Home.swift", "import UIKit\nimport Alamofire\nimport Lottie\n");
+        tmp.write("Modules/RBHome/Classes/Legacy.m", "#import <Masonry/Masonry.h>\n#import <RBHome/RBHome.h>\n");
+        // Example 工程带的 Pods/ 和嵌套的开发 pod 都不扫描
+        tmp.write("Modules/RBHome/Example/Pods/Kingfisher/K.swift", "import Kingfisher\n");
+        tmp.write("Modules/RBHome/RBShare/Share.swift", "import Kingfisher\n");
+        let lockfile = Lockfile::from_yaml_str("
+PODS:
+  - Alamofire (5.4.4)
+  - Kingfisher (7.0.0)
+  - lottie-ios (3.4.0)
+  - Masonry (1.1.0)
+  - RBHome (0.1.0):
+    - Alamofire
+    - Kingfisher
+  - RBShare (0.1.0)
+
+EXTERNAL SOURCES:
+  RBHome:
+    :path: \"./Modules/RBHome\"
+  RBShare:
+    :path: \"./Modules/RBHome/RBShare\"
+
+COCOAPODS: 1.11.0
+").unwrap();
+        let modules = HashMap::from([("Lottie".to_string(), "lottie-ios".to_string())]);
+        let result = imports(&lockfile, tmp.path(), &modules).unwrap();
+        assert_eq!(result.iter().map(|r| r.name.as_str()).collect::<Vec<&str>>(), ["RBHome", "RBShare"]);
+        let home = &result[0];
+        assert_eq!(home.dir, Path::new("Modules/RBHome"));
+        assert_eq!(home.files, 2);
+        let undeclared = home.undeclared.iter()
+            .map(|(p, l)| format!("{} {}:{}", p, l.file.display(), l.line))
+            .collect::<Vec<String>>();
+        assert_eq!(undeclared, [
+            "Masonry Modules/RBHome/Classes/Legacy.m:1",
+            "lottie-ios Modules/RBHome/Classes/Home.swift:3",
+        ]);
+        assert_eq!(home.unused, ["Kingfisher"]);
+        assert_eq!(result[1].undeclared.iter().map(|(p, _)| p.as_str()).collect::<Vec<&str>>(), ["Kingfisher"]);
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::bail;
+use crate::lib::pod::lockfile::{root_name, Lockfile};
+
+// pod info
+// 汇总 Podfile.lock 里和某个 pod 有关的所有信息
+
+pub(crate) struct PodInfo {
+    pub(crate) name: String,
+    pub(crate) vers: Option<String>,
+    // 是 subspec 时为 root spec
+    pub(crate) root: Option<String>,
+    pub(crate) subspecs: Vec<String>,
+    // 在 DEPENDENCIES 里声明时的要求，没有声明时为空
+    pub(crate) declared: Vec<String>,
+    pub(crate) spec_repo: Option<String>,
+    // EXTERNAL SOURCES、CHECKOUT OPTIONS 里的字段，譬如 `:git https://x.com/Foo.git`
+    pub(crate) external: Option<Vec<String>>,
+    pub(crate) checkout: Option<Vec<String>>,
+    pub(crate) checksum: Option<String>,
+    pub(crate) dependencies: Vec<String>,
+    pub(crate) dependents: Vec<String>,
+    pub(crate) cocoapods_version: Option<String>,
+    pub(crate) podfile_checksum: Option<String>,
+}
+
+pub(crate) fn info(lockfile: &Lockfile, name: &str) -> anyhow::Result<PodInfo> {
+    let pods = lockfile.pods()?;
+    let pod = match pods.get(name) {
+        Some(p) => p,
+        None => bail!("Cannot find {} in Podfile.lock", name),
+    };
+    // SPEC REPOS、EXTERNAL SOURCES、SPEC CHECKSUMS 等都以 root spec 为 key
+    let root = root_name(name);
+
+    let mut subspecs = pods.keys()
+        .filter(|k| k.starts_with(&format!("{}/", name)))
+        .cloned()
+        .collect::<Vec<String>>();
+    subspecs.sort();
+
+    let declared = lockfile.dependencies()?
+        .into_iter()
+        .filter(|d| d.name == name)
+        .map(|d| {
+            d.requirement
+                .map(|r| r.to_string())
+                .or_else(|| d.source.map(|s| format!("from {}", s)))
+                .unwrap_or_else(|| "any version".to_string())
+        })
+        .collect::<Vec<String>>();
+
+    let spec_repo = lockfile.spec_repos()?
+        .into_iter()
+        .find(|r| r.pods.iter().any(|p| p == root))
+        .map(|r| r.name);
+    let fields = |fields: Vec<(&str, &str)>| -> Vec<String> {
+        fields.into_iter().map(|(k, v)| format!("{} {}", k, v)).collect()
+    };
+    let external = lockfile.external_sources()?.get(root).map(|es| fields(es.fields()));
+    let checkout = lockfile.checkout_options()?.get(root).map(|co| fields(co.fields()));
+
+    let mut dependencies = pod.children.iter()
+        .map(|c| match pod.requirements.get(c) {
+            Some(r) => format!("{} ({})", c, r),
+            None => c.to_string(),
+        })
+        .collect::<Vec<String>>();
+    dependencies.sort();
+    let mut dependents = pod.parents.iter()
+        .map(|p| match pods.get(p.as_ref()).and_then(|p| p.requirements.get(name)) {
+            Some(r) => format!("{} ({})", p, r),
+            None => p.to_string(),
+        })
+        .collect::<Vec<String>>();
+    dependents.sort();
+
+    Ok(PodInfo {
+        name: pod.name.clone(),
+        vers: pod.vers.clone(),
+        root: if root != name { Some(root.to_string()) } else { None },
+        subspecs,
+        declared,
+        spec_repo,
+        external,
+        checkout,
+        checksum: lockfile.spec_checksums()?.get(root).cloned(),
+        dependencies,
+        dependents,
+        cocoapods_version: lockfile.cocoapods_version(),
+        podfile_checksum: lockfile.podfile_checksum(),
+    })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::PodInfo;
+
+    pub(crate) fn print_info(info: &PodInfo) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let row = |label: &str, value: String| {
+            println!("  {} {}", Style::from(light_gray).paint(format!("{:<13}", label)), value);
+        };
+        let list = |items: &[String]| -> String {
+            if items.is_empty() { "-".to_string() } else { items.join(", ") }
+        };
+
+        println!(
+            "{} {}",
+            Style::from(Colour::Green).bold().paint(&info.name),
+            Style::from(Colour::Yellow).paint(info.vers.clone().unwrap_or_else(|| "?".to_string()))
+        );
+        if let Some(root) = &info.root {
+            row("root spec", root.clone());
+        }
+        if !info.subspecs.is_empty() {
+            row("subspecs", list(&info.subspecs));
+        }
+        row(
+            "declared",
+            if info.declared.is_empty() {
+                "no (transitive)".to_string()
+            } else {
+                format!("yes ({})", info.declared.join(", "))
+            }
+        );
+        if let Some(repo) = &info.spec_repo {
+            row("spec repo", repo.clone());
+        }
+        if let Some(external) = &info.external {
+            row("external", list(external));
+        }
+        if let Some(checkout) = &info.checkout {
+            row("checkout", list(checkout));
+        }
+        if let Some(checksum) = &info.checksum {
+            row("checksum", checksum.clone());
+        }
+        row("dependencies", list(&info.dependencies));
+        row("dependents", list(&info.dependents));
+
+        let footer = format!(
+            "\nPodfile.lock: COCOAPODS {}, PODFILE CHECKSUM {}",
+            info.cocoapods_version.as_deref().unwrap_or("?"),
+            info.podfile_checksum.as_deref().unwrap_or("?")
+        );
+        println!("{}", Style::from(light_gray).paint(footer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::pod::lockfile::Lockfile;
+    use super::info;
+
+    const LOCKFILE: &str = "
+PODS:
+  - AFNetworking (4.0.1):
+    - AFNetworking/NSURLSession (= 4.0.1)
+    - AFNetworking/Reachability (= 4.0.1)
+  - AFNetworking/NSURLSession (4.0.1):
+    - AFNetworking/Reachability
+  - AFNetworking/Reachability (4.0.1)
+  - Kingfisher (7.0.0)
+  - RBFoundation (0.1.0):
+    - AFNetworking/NSURLSession
+
+DEPENDENCIES:
+  - AFNetworking (~> 4.0)
+  - Kingfisher (from `https://github.com/onevcat/Kingfisher.git`, tag `7.0.0`)
+  - RBFoundation (from `./Modules/RBFoundation`)
+
+SPEC REPOS:
+  trunk:
+    - AFNetworking
+
+EXTERNAL SOURCES:
+  Kingfisher:
+    :git: https://github.com/onevcat/Kingfisher.git
+    :tag: 7.0.0
+  RBFoundation:
+    :path: \"./Modules/RBFoundation\"
+
+CHECKOUT OPTIONS:
+  Kingfisher:
+    :git: https://github.com/onevcat/Kingfisher.git
+    :tag: 7.0.0
+
+SPEC CHECKSUMS:
+  AFNetworking: 7864c38297c79aaca1500c33288e429c3451fdce
+  Kingfisher: 0d7e4b6d2c1f0a9b2e5d4a0b4f6c8d9e1a2b3c4d
+  RBFoundation: 1a2b3c
+
+PODFILE CHECKSUM: 0f1e2d3c
+
+COCOAPODS: 1.11.3
+";
+
+    fn lockfile() -> Lockfile {
+        Lockfile::from_yaml_str(LOCKFILE).unwrap()
+    }
+
+    #[test]
+    fn root_spec_from_trunk() {
+        let info = info(&lockfile(), "AFNetworking").unwrap();
+        assert_eq!(info.vers.as_deref(), Some("4.0.1"));
+        assert_eq!(info.root, None);
+        assert_eq!(info.subspecs, ["AFNetworking/NSURLSession", "AFNetworking/Reachability"]);
+        assert_eq!(info.declared, ["~> 4.0"]);
+        assert_eq!(info.spec_repo.as_deref(), Some("trunk"));
+        assert!(info.external.is_none());
+        assert_eq!(info.checksum.as_deref(), Some("7864c38297c79aaca1500c33288e429c3451fdce"));
+        assert_eq!(info.cocoapods_version.as_deref(), Some("1.11.3"));
+        assert_eq!(info.podfile_checksum.as_deref(), Some("0f1e2d3c"));
+    }
+
+    #[test]
+    fn subspec_takes_sections_of_root() {
+        let info = info(&lockfile(), "AFNetworking/NSURLSession").unwrap();
+        assert_eq!(info.root.as_deref(), Some("AFNetworking"));
+        assert!(info.declared.is_empty());
+        assert_eq!(info.spec_repo.as_deref(), Some("trunk"));
+        assert_eq!(info.dependencies, ["AFNetworking/Reachability"]);
+        assert_eq!(info.dependents, ["AFNetworking (= 4.0.1)", "RBFoundation"]);
+    }
+
+    #[test]
+    fn external_sources_and_checkout() {
+        let info = info(&lockfile(), "Kingfisher").unwrap();
+        assert_eq!(info.declared, ["from `https://github.com/onevcat/Kingfisher.git`, tag `7.0.0`"]);
+        assert_eq!(info.spec_repo, None);
+        assert_eq!(info.external.unwrap(), [":git https://github.com/onevcat/Kingfisher.git", ":tag 7.0.0"]);
+        assert_eq!(info.checkout.unwrap(), [":git https://github.com/onevcat/Kingfisher.git", ":tag 7.0.0"]);
+
+        let info = super::info(&lockfile(), "RBFoundation").unwrap();
+        assert_eq!(info.declared, ["from `./Modules/RBFoundation`"]);
+        assert_eq!(info.external.unwrap(), [":path ./Modules/RBFoundation"]);
+        assert!(info.checkout.is_none());
+    }
+
+    #[test]
+    fn unknown_pod() {
+        assert!(info(&lockfile(), "Masonry").is_err());
+    }
+}
@@ -0,0 +1,338 @@
+use std::path::{Path, PathBuf};
+use serde_json::{json, Value};
+use crate::lib::pod::lockfile::{collapse_subspecs, Lockfile};
+
+// pod licenses
+// 收集每个 pod 的 license，生成致谢文档并标出没有 license 或者是 copyleft 的 pod
+// license 文本的来源依次为：
+// - podspec JSON（Pods/Local Podspecs/<name>.podspec.json）里 `license` 的 `text` 或 `file`
+// - pod 目录下的 LICENSE*、LICENCE*、COPYING* 文件
+// 类型优先按文本识别，识别不出时用 podspec 里声明的 `type`
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format { Markdown, Json, Plist }
+
+impl Format {
+    pub(crate) const NAMES: [&'static str; 3] = ["markdown", "json", "plist"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "markdown" => Some(Format::Markdown),
+            "json" => Some(Format::Json),
+            "plist" => Some(Format::Plist),
+            _ => None,
+        }
+    }
+}
+
+const LOCAL_PODSPECS: &str = "Local Podspecs";
+const FILE_PREFIXES: [&str; 3] = ["LICENSE", "LICENCE", "COPYING"];
+// 要求衍生作品开源的 license，LGPL、MPL 这类弱 copyleft 也算在内
+const COPYLEFT: [&str; 8] = ["GPL-2.0", "GPL-3.0", "LGPL-2.1", "LGPL-3.0", "AGPL-3.0", "MPL-2.0", "EPL-1.0", "EPL-2.0"];
+
+pub(crate) struct License {
+    pub(crate) name: String,
+    pub(crate) vers: Option<String>,
+    // 识别出的 SPDX identifier
+    pub(crate) spdx: Option<&'static str>,
+    // podspec 里声明的 `type`，原样保留
+    pub(crate) declared: Option<String>,
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) text: Option<String>,
+}
+
+impl License {
+    pub(crate) fn is_missing(&self) -> bool {
+        self.spdx.is_none() && self.declared.is_none() && self.text.is_none()
+    }
+
+    pub(crate) fn is_copyleft(&self) -> bool {
+        self.spdx.map(|id| COPYLEFT.contains(&id)).unwrap_or(false)
+    }
+
+    // 展示用的类型：SPDX identifier，其次是 podspec 里声明的类型
+    pub(crate) fn kind(&self) -> Option<&str> {
+        self.spdx.or(self.declared.as_deref())
+    }
+}
+
+// `dir` 为 Podfile.lock 所在的目录，`:path` 引入的开发 pod 相对于它查找
+pub(crate) fn licenses(
+    lockfile: &Lockfile,
+    dir: &Path,
+    pods_dir: &Path,
+    development: bool
+) -> anyhow::Result<Vec<License>> {
+    let pods = collapse_subspecs(&lockfile.pods()?);
+    let external = lockfile.external_sources()?;
+    let mut names = pods.keys().collect::<Vec<&String>>();
+    names.sort_by_key(|n| n.to_lowercase());
+
+    let mut ret = Vec::new();
+    for name in names {
+        let dev_path = external.get(name).and_then(|es| es.path.as_ref());
+        let pod_dir = match dev_path {
+            Some(_) if !development => continue,
+            Some(p) => dir.join(p),
+            None => pods_dir.join(name),
+        };
+        let podspec = [
+            pods_dir.join(LOCAL_PODSPECS).join(format!("{}.podspec.json", name)),
+            pod_dir.join(format!("{}.podspec.json", name)),
+        ]
+            .into_iter()
+            .find(|p| p.is_file())
+            .map(|p| -> anyhow::Result<Value> { Ok(serde_json::from_str(&std::fs::read_to_string(p)?)?) })
+            .transpose()?;
+        // `license` 可以是字符串，也可以是 `{ type, file, text }`
+        let field = |key: &str| -> Option<String> {
+            let license = podspec.as_ref().map(|s| &s["license"])?;
+            match (key, license) {
+                ("type", Value::String(s)) => Some(s.clone()),
+                _ => license[key].as_str().map(|s| s.to_string()),
+            }
+        };
+        let declared = field("type");
+        let (file, text) = match (field("text"), field("file")) {
+            (Some(text), _) => (None, Some(text)),
+            (None, Some(file)) if pod_dir.join(&file).is_file() => {
+                let path = pod_dir.join(file);
+                let text = std::fs::read_to_string(&path)?;
+                (Some(path), Some(text))
+            },
+            _ => match find_license_file(&pod_dir)? {
+                Some(path) => {
+                    let text = String::from_utf8_lossy(&std::fs::read(&path)?).to_string();
+                    (Some(path), Some(text))
+                },
+                None => (None, None),
+            },
+        };
+        let spdx = text.as_deref()
+            .and_then(classify_text)
+            .or_else(|| declared.as_deref().and_then(classify_type));
+        let pod = &pods[name];
+        ret.push(License { name: name.clone(), vers: pod.vers.clone(), spdx, declared, file, text });
+    }
+    Ok(ret)
+}
+
+// 只看 pod 目录的第一层，`LICENSE` 优先于 `LICENSE.md` 这类
+fn find_license_file(dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(None)
+    }
+    let mut found = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_uppercase();
+        if entry.file_type()?.is_file() && FILE_PREFIXES.iter().any(|p| name.starts_with(p)) {
+            found.push((name.len(), name, entry.path()));
+        }
+    }
+    found.sort();
+    Ok(found.into_iter().next().map(|(_, _, p)| p))
+}
+
+// 开头几行里找 license 的标题，正文里常会提到别的 license（MPL-2.0 提到了 GNU AGPL）
+const TITLE_LINES: usize = 5;
+
+// 按 license 的标题或者正文里的特征语句识别
+fn classify_text(text: &str) -> Option<&'static str> {
+    let normalize = |s: &str| s.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase();
+    let head = normalize(&text.lines().filter(|l| !l.trim().is_empty()).take(TITLE_LINES).collect::<Vec<&str>>().join(" "));
+    let text = normalize(text);
+    let title = |s: &str| head.contains(s);
+    let has = |s: &str| text.contains(s);
+    // 标题后面的第一个版本号，正文可能会提到其他版本
+    let version = |v: &str| head.split("version ").nth(1).map(|rest| rest.starts_with(v)).unwrap_or(false);
+    let id = if title("mozilla public license") {
+        "MPL-2.0"
+    } else if title("gnu affero general public license") {
+        "AGPL-3.0"
+    } else if title("gnu lesser general public license") || title("gnu library general public license") {
+        if version("3") { "LGPL-3.0" } else { "LGPL-2.1" }
+    } else if title("gnu general public license") {
+        if version("3") { "GPL-3.0" } else { "GPL-2.0" }
+    } else if title("eclipse public license") {
+        if version("2.0") { "EPL-2.0" } else { "EPL-1.0" }
+    } else if title("apache license") {
+        "Apache-2.0"
+    } else if has("permission is hereby granted, free of charge") {
+        "MIT"
+    } else if has("redistribution and use in source and binary forms") {
+        if has("neither the name") || has("may be used to endorse or promote") { "BSD-3-Clause" } else { "BSD-2-Clause" }
+    } else if has("permission to use, copy, modify, and/or distribute this software for any purpose") {
+        "ISC"
+    } else if title("boost software license") {
+        "BSL-1.0"
+    } else if has("this is free and unencumbered software released into the public domain") {
+        "Unlicense"
+    } else if has("altered source versions must be plainly marked") {
+        "Zlib"
+    } else if title("cc0 1.0 universal") {
+        "CC0-1.0"
+    } else {
+        return None
+    };
+    Some(id)
+}
+
+// podspec 里的 `type`，譬如 `MIT`、`Apache License, Version 2.0`、`BSD`、`GPLv3`
+// 按单词匹配，避免 `Limited` 这样的词被当成 MIT
+fn classify_type(declared: &str) -> Option<&'static str> {
+    let t = declared.to_lowercase();
+    let words = t.split(|c: char| !c.is_ascii_alphanumeric()).collect::<Vec<&str>>();
+    let word = |p: &str| words.iter().any(|w| w.starts_with(p));
+    let three = t.contains('3');
+    let id = if word("agpl") || word("affero") {
+        "AGPL-3.0"
+    } else if word("lgpl") || word("lesser") {
+        if three { "LGPL-3.0" } else { "LGPL-2.1" }
+    } else if word("gpl") || t.contains("general public") {
+        if three { "GPL-3.0" } else { "GPL-2.0" }
+    } else if word("mpl") || word("mozilla") {
+        "MPL-2.0"
+    } else if word("apache") {
+        "Apache-2.0"
+    } else if words.contains(&"mit") {
+        "MIT"
+    } else if word("bsd") {
+        if t.contains('2') { "BSD-2-Clause" } else { "BSD-3-Clause" }
+    } else if words.contains(&"isc") {
+        "ISC"
+    } else if word("zlib") {
+        "Zlib"
+    } else {
+        return None
+    };
+    Some(id)
+}
+
+pub(crate) fn render(licenses: &[License], format: Format) -> String {
+    match format {
+        Format::Markdown => {
+            let mut out = String::from("# Acknowledgements\n\nThis application makes use of the following third party libraries:\n");
+            for l in licenses {
+                out.push_str(&format!("\n## {}\n\n", l.name));
+                match (&l.text, l.kind()) {
+                    (Some(text), _) => out.push_str(&format!("{}\n", text.trim_end())),
+                    (None, Some(kind)) => out.push_str(&format!("{}\n", kind)),
+                    (None, None) => out.push_str("No license found\n"),
+                }
+            }
+            out
+        },
+        Format::Json => {
+            let items = licenses.iter()
+                .map(|l| json!({
+                    "name": l.name,
+                    "version": l.vers,
+                    "license": l.spdx,
+                    "declared": l.declared,
+                    "file": l.file.as_ref().map(|f| f.to_string_lossy().to_string()),
+                    "missing": l.is_missing(),
+                    "copyleft": l.is_copyleft(),
+                    "text": l.text,
+                }))
+                .collect::<Vec<Value>>();
+            format!("{}\n", serde_json::to_string_pretty(&Value::Array(items)).unwrap_or_default())
+        },
+        // 与 CocoaPods 生成的 Pods-acknowledgements.plist 结构相同，可以直接放进 Settings.bundle
+        Format::Plist => {
+            let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+            let group = |title: &str, footer: &str, license: Option<&str>| -> String {
+                let mut out = String::from("\t\t<dict>\n");
+                out.push_str(&format!("\t\t\t<key>FooterText</key>\n\t\t\t<string>{}</string>\n", escape(footer)));
+                if let Some(license) = license {
+                    out.push_str(&format!("\t\t\t<key>License</key>\n\t\t\t<string>{}</string>\n", escape(license)));
+                }
+                out.push_str(&format!("\t\t\t<key>Title</key>\n\t\t\t<string>{}</string>\n", escape(title)));
+                out.push_str("\t\t\t<key>Type</key>\n\t\t\t<string>PSGroupSpecifier</string>\n\t\t</dict>\n");
+                out
+            };
+            let mut out = String::from(concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+                "<plist version=\"1.0\">\n<dict>\n\t<key>PreferenceSpecifiers</key>\n\t<array>\n",
+            ));
+            out.push_str(&group("Acknowledgements", "This application makes use of the following third party libraries:", None));
+            for l in licenses {
+                let footer = l.text.as_deref().map(|t| t.trim_end()).or_else(|| l.kind()).unwrap_or("No license found");
+                out.push_str(&group(&l.name, footer, l.kind()));
+            }
+            out.push_str(concat!(
+                "\t</array>\n\t<key>StringsTable</key>\n\t<string>Acknowledgements</string>\n",
+                "\t<key>Title</key>\n\t<string>Acknowledgements</string>\n</dict>\n</plist>\n",
+            ));
+            out
+        },
+    }
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::License;
+
+    // 致谢文档可能输出到标准输出，所以输出到标准错误
+    pub(crate) fn print_flags(licenses: &[License]) -> usize {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let mut count = 0;
+        for l in licenses {
+            let reason = if l.is_missing() {
+                "no license found".to_string()
+            } else if l.is_copyleft() {
+                format!("copyleft license {}", l.spdx.unwrap_or_default())
+            } else if l.kind().is_none() {
+                "unrecognized license text, check it manually".to_string()
+            } else {
+                continue
+            };
+            count += 1;
+            eprintln!(
+                "{} {} {}",
+                Style::from(Colour::Red).bold().paint("!"),
+                Style::from(Colour::Yellow).paint(&l.name),
+                Style::from(light_gray).paint(reason)
+            );
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::classify_text;
+
+    #[test]
+    fn mpl_mentioning_gnu_licenses_is_mpl() {
+        let text = "Mozilla Public License Version 2.0\n\
+            ==================================\n\n\
+            1. Definitions\n--------------\n\n\
+            1.12. \"Secondary License\"\n    means either the GNU General Public License, Version 2.0, the GNU\n\
+            \x20   Lesser General Public License, Version 2.1, the GNU Affero General\n\
+            \x20   Public License, Version 3.0, or any later versions of those\n\
+            \x20   licenses.\n";
+        assert_eq!(classify_text(text), Some("MPL-2.0"));
+    }
+
+    #[test]
+    fn gnu_licenses_by_title() {
+        let gpl3 = "                    GNU GENERAL PUBLIC LICENSE\n                       Version 3, 29 June 2007\n";
+        assert_eq!(classify_text(gpl3), Some("GPL-3.0"));
+        let lgpl21 = "GNU LESSER GENERAL PUBLIC LICENSE\nVersion 2.1, February 1999\n\n\
+            This license ... the GNU General Public License, version 3 ...\n";
+        assert_eq!(classify_text(lgpl21), Some("LGPL-2.1"));
+        let agpl = "GNU AFFERO GENERAL PUBLIC LICENSE\nVersion 3, 19 November 2007\n";
+        assert_eq!(classify_text(agpl), Some("AGPL-3.0"));
+    }
+
+    #[test]
+    fn permissive_licenses_by_body() {
+        let mit = "The MIT License (MIT)\n\nCopyright (c) 2020 Someone\n\n\
+            Permission is hereby granted, free of charge, to any person obtaining a copy\n";
+        assert_eq!(classify_text(mit), Some("MIT"));
+        let apache = "Copyright 2015 Google Inc.\n\nLicensed under the Apache License, Version 2.0 (the \"License\");\n";
+        assert_eq!(classify_text(apache), Some("Apache-2.0"));
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use anyhow::{anyhow, bail};
+use yaml_rust::{Yaml, YamlLoader};
+use crate::lib::dep::pattern::Pattern;
+use crate::lib::pod::lockfile::{root_name, PodItem};
+
+// pod lint
+// 按分层规则检查 pod 之间的依赖，规则文件（默认为 Podfile.lock 旁边的 rubo-layers.yaml）：
+//
+// layers:              # 从低到高
+//   - name: Foundation
+//     pods: [RBFoundation, "RBUtil*"]
+//   - name: Business
+//     pods: ["/^Biz/"]
+// allow:               # 例外，允许这些依赖
+//   - "RBLegacy -> Business"
+// forbid:              # 即使层次允许也禁止，优先于 allow
+//   - "Business -> Business"
+//
+// 默认只能依赖同层或者更低层的 pod；规则两边为层名，或者 pod 的名字、glob、正则
+// 没有分层的 pod（譬如第三方库）不参与分层检查，但会沿着它们继续找，间接依赖也算
+
+pub(crate) const DEFAULT_RULES: &str = "rubo-layers.yaml";
+
+pub(crate) struct Rules {
+    layers: Vec<Layer>,
+    allow: Vec<Rule>,
+    forbid: Vec<Rule>,
+}
+
+struct Layer {
+    name: String,
+    patterns: Vec<Pattern>,
+}
+
+struct Rule {
+    text: String,
+    from: Side,
+    to: Side,
+}
+
+enum Side {
+    // `Rules::layers` 的下标
+    Layer(usize),
+    Pods(Pattern),
+}
+
+impl Rules {
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Rules::parse(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let root = YamlLoader::load_from_str(content)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("empty rules"))?;
+        let strings = |v: &Yaml, what: &str| -> anyhow::Result<Vec<String>> {
+            match v {
+                Yaml::BadValue | Yaml::Null => Ok(Vec::new()),
+                Yaml::String(s) => Ok(vec![s.clone()]),
+                Yaml::Array(a) => a.iter()
+                    .map(|i| i.as_str().map(|s| s.to_string()).ok_or_else(|| anyhow!("{} should be strings", what)))
+                    .collect(),
+                _ => bail!("{} should be a list of strings", what),
+            }
+        };
+
+        let mut layers = Vec::<Layer>::new();
+        for item in root["layers"].as_vec().ok_or_else(|| anyhow!("`layers` should be a list"))? {
+            let name = item["name"].as_str().ok_or_else(|| anyhow!("every layer should have a `name`"))?;
+            if layers.iter().any(|l| l.name == name) {
+                bail!("duplicated layer {}", name);
+            }
+            let patterns = strings(&item["pods"], &format!("pods of layer {}", name))?
+                .iter()
+                .map(|p| Pattern::parse(p))
+                .collect::<anyhow::Result<Vec<Pattern>>>()?;
+            layers.push(Layer { name: name.to_string(), patterns });
+        }
+
+        let rules = |key: &str| -> anyhow::Result<Vec<Rule>> {
+            strings(&root[key], &format!("`{}`", key))?
+                .into_iter()
+                .map(|text| {
+                    let (from, to) = text.split_once("->")
+                        .ok_or_else(|| anyhow!("rule `{}` should be like `A -> B`", text))?;
+                    let side = |s: &str| -> anyhow::Result<Side> {
+                        let s = s.trim();
+                        if s.is_empty() {
+                            bail!("rule `{}` should be like `A -> B`", text);
+                        }
+                        match layers.iter().position(|l| l.name == s) {
+                            Some(i) => Ok(Side::Layer(i)),
+                            None => Ok(Side::Pods(Pattern::parse(s)?)),
+                        }
+                    };
+                    Ok(Rule { from: side(from)?, to: side(to)?, text: text.clone() })
+                })
+                .collect()
+        };
+        let allow = rules("allow")?;
+        let forbid = rules("forbid")?;
+        Ok(Rules { layers, allow, forbid })
+    }
+
+    // 每个 pod 所在的层，subspec 没有匹配时按 root spec 匹配
+    fn assign(&self, pods: &HashMap<String, PodItem>) -> anyhow::Result<HashMap<String, usize>> {
+        let find = |name: &str| -> anyhow::Result<Option<usize>> {
+            let matched = self.layers.iter()
+                .enumerate()
+                .filter(|(_, l)| l.patterns.iter().any(|p| p.is_match(name)))
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>();
+            match matched.as_slice() {
+                [] => Ok(None),
+                [i] => Ok(Some(*i)),
+                _ => {
+                    let names = matched.iter().map(|i| self.layers[*i].name.as_str()).collect::<Vec<&str>>();
+                    bail!("{} belongs to more than one layer: {}", name, names.join(", "))
+                },
+            }
+        };
+        let mut ret = HashMap::new();
+        for name in pods.keys() {
+            let layer = match find(name)? {
+                Some(i) => Some(i),
+                None if name.contains('/') => find(root_name(name))?,
+                None => None,
+            };
+            if let Some(i) = layer {
+                ret.insert(name.clone(), i);
+            }
+        }
+        Ok(ret)
+    }
+}
+
+impl Rule {
+    fn is_match(&self, from: &str, to: &str, layers: &HashMap<String, usize>) -> bool {
+        let side_match = |side: &Side, name: &str| match side {
+            Side::Layer(i) => layers.get(name) == Some(i),
+            Side::Pods(p) => p.is_match(name) || p.is_match(root_name(name)),
+        };
+        side_match(&self.from, from) && side_match(&self.to, to)
+    }
+}
+
+pub(crate) struct Violation {
+    // 从 `from` 到 `to` 的依赖链，包含两端
+    pub(crate) chain: Vec<String>,
+    pub(crate) from_layer: Option<String>,
+    pub(crate) to_layer: Option<String>,
+    pub(crate) reason: String,
+}
+
+pub(crate) struct LintResult {
+    // 分层的 pod 数量
+    pub(crate) layered: usize,
+    // 检查过的依赖数量
+    pub(crate) checked: usize,
+    pub(crate) violations: Vec<Violation>,
+}
+
+pub(crate) fn lint(rules: &Rules, pods: &HashMap<String, PodItem>) -> anyhow::Result<LintResult> {
+    let layers = rules.assign(pods)?;
+    let layer_name = |name: &str| layers.get(name).map(|i| rules.layers[*i].name.clone());
+
+    // 从分层的 pod 和规则左边匹配的 pod 出发
+    let mut starts = pods.keys()
+        .filter(|n| {
+            layers.contains_key(*n) || rules.allow.iter().chain(rules.forbid.iter()).any(|r| match &r.from {
+                Side::Layer(_) => false,
+                Side::Pods(p) => p.is_match(n) || p.is_match(root_name(n)),
+            })
+        })
+        .map(|n| n.as_str())
+        .collect::<Vec<&str>>();
+    starts.sort();
+
+    let mut checked = 0;
+    let mut violations = Vec::new();
+    for from in starts {
+        // 广度优先，穿过没有分层的 pod，到分层的 pod 为止；每个终点保留最短的链
+        let mut prev = HashMap::<&str, &str>::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(name) = queue.pop_front() {
+            let mut children = pods[name].children.iter()
+                .map(|c| c.as_ref())
+                .filter(|c| pods.contains_key(*c))
+                .collect::<Vec<&str>>();
+            children.sort();
+            for to in children {
+                if to == from || prev.contains_key(to) {
+                    continue
+                }
+                prev.insert(to, name);
+                if !layers.contains_key(to) {
+                    queue.push_back(to);
+                }
+                // subspec 之间的依赖不检查
+                if root_name(to) == root_name(from) {
+                    continue
+                }
+                checked += 1;
+                let reason = if let Some(r) = rules.forbid.iter().find(|r| r.is_match(from, to, &layers)) {
+                    format!("forbidden by `{}`", r.text)
+                } else if rules.allow.iter().any(|r| r.is_match(from, to, &layers)) {
+                    continue
+                } else {
+                    match (layers.get(from), layers.get(to)) {
+                        (Some(f), Some(t)) if t > f => format!(
+                            "{} must not depend on higher layer {}",
+                            rules.layers[*f].name,
+                            rules.layers[*t].name
+                        ),
+                        _ => continue,
+                    }
+                };
+                let mut chain = vec![to.to_string()];
+                let mut current = to;
+                while current != from {
+                    current = prev[current];
+                    chain.push(current.to_string());
+                }
+                chain.reverse();
+                violations.push(Violation {
+                    chain,
+                    from_layer: layer_name(from),
+                    to_layer: layer_name(to),
+                    reason,
+                });
+            }
+        }
+    }
+    Ok(LintResult { layered: layers.len(), checked, violations })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::LintResult;
+
+    pub(crate) fn print_lint(result: &LintResult) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if result.violations.is_empty() {
+            println!(
+                "{}",
+                Style::from(Colour::Green).paint(format!(
+                    "No violations in {} dependencies of {} layered pods",
+                    result.checked,
+                    result.layered
+                ))
+            );
+            return
+        }
+        let with_layer = |name: &str, layer: &Option<String>| match layer {
+            Some(l) => format!("{} {}", Style::from(Colour::Yellow).paint(name), Style::from(light_gray).paint(format!("({})", l))),
+            None => Style::from(Colour::Yellow).paint(name).to_string(),
+        };
+        for v in &result.violations {
+            println!(
+                " {} {} -> {}  {}",
+                Style::from(Colour::Red).bold().paint("✗"),
+                with_layer(v.chain.first().unwrap(), &v.from_layer),
+                with_layer(v.chain.last().unwrap(), &v.to_layer),
+                Style::from(light_gray).paint(&v.reason)
+            );
+            if v.chain.len() > 2 {
+                println!("     {}", Style::from(light_gray).paint(format!("via {}", v.chain.join(" -> "))));
+            }
+        }
+        eprintln!(
+            "{}",
+            Style::from(light_gray).paint(format!(
+                "{} of {} dependencies violate the layering rules",
+                result.violations.len(),
+                result.checked
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::{Lockfile, PodItem};
+    use super::{lint, Rules};
+
+    // RBFoundation 经由第三方库 Alamofire、Moya 间接依赖 BizHome
+    const LOCKFILE: &str = "
+PODS:
+  - Alamofire (5.4.4):
+    - Moya
+  - BizHome (1.0.0):
+    - BizShare/Core
+    - RBFoundation
+  - BizShare/Core (1.0.0):
+    - BizShare/Util
+  - BizShare/Util (1.0.0)
+  - Moya (15.0.0):
+    - BizHome
+  - RBFoundation (1.0.0):
+    - Alamofire
+  - RBLegacy (1.0.0):
+    - BizHome
+
+COCOAPODS: 1.11.0
+";
+
+    const RULES: &str = "
+layers:
+  - name: Foundation
+    pods: [RBFoundation, \"RBLegacy\"]
+  - name: Business
+    pods: [\"/^Biz/\"]
+";
+
+    fn pods() -> HashMap<String, PodItem> {
+        Lockfile::from_yaml_str(LOCKFILE).unwrap().pods().unwrap()
+    }
+
+    fn describe(rules: &str) -> Vec<String> {
+        let result = lint(&Rules::parse(rules).unwrap(), &pods()).unwrap();
+        result.violations.iter()
+            .map(|v| format!("{}: {}", v.chain.join(" -> "), v.reason))
+            .collect()
+    }
+
+    #[test]
+    fn chains_go_through_unlayered_pods() {
+        let result = lint(&Rules::parse(RULES).unwrap(), &pods()).unwrap();
+        // subspec 按 root spec 分层
+        assert_eq!(result.layered, 5);
+        assert_eq!(describe(RULES), [
+            "RBFoundation -> Alamofire -> Moya -> BizHome: Foundation must not depend on higher layer Business",
+            "RBLegacy -> BizHome: Foundation must not depend on higher layer Business",
+        ]);
+        let v = &result.violations[0];
+        assert_eq!((v.from_layer.as_deref(), v.to_layer.as_deref()), (Some("Foundation"), Some("Business")));
+    }
+
+    #[test]
+    fn allow_and_forbid() {
+        let rules = format!("{}allow:\n  - \"RBLegacy -> Business\"\nforbid:\n  - \"BizHome -> BizShare*\"\n", RULES);
+        assert_eq!(describe(&rules), [
+            "BizHome -> BizShare/Core: forbidden by `BizHome -> BizShare*`",
+            "RBFoundation -> Alamofire -> Moya -> BizHome: Foundation must not depend on higher layer Business",
+        ]);
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert!(Rules::parse("layers: Foundation").is_err());
+        assert!(Rules::parse("layers:\n  - pods: [A]").is_err());
+        assert!(Rules::parse("layers:\n  - name: A\n  - name: A").is_err());
+        assert!(Rules::parse("layers: []\nallow:\n  - \"A => B\"").is_err());
+        assert!(Rules::parse("layers: []\nforbid:\n  - \"A ->\"").is_err());
+        let overlapping = "layers:\n  - name: A\n    pods: [\"Biz*\"]\n  - name: B\n    pods: [BizHome]\n";
+        let err = lint(&Rules::parse(overlapping).unwrap(), &pods()).err().unwrap().to_string();
+        assert_eq!(err, "BizHome belongs to more than one layer: A, B");
+    }
+}
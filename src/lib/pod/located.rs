@@ -0,0 +1,261 @@
+use std::fmt;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+use yaml_rust::ScanError;
+
+// 带位置的解析错误
+// yaml-rust 的 `Yaml` 不保留位置，出错时再用事件解析一遍原文，按路径找到出错节点的行列
+
+// 从 section 到出错节点的路径
+#[derive(Clone, Copy)]
+pub(crate) enum Step {
+    // hash 里的 key 对应的值
+    Key(&'static str),
+    // 数组的第几项
+    Index(usize),
+    // hash 的第几个 key
+    KeyAt(usize),
+    // hash 的第几个值
+    ValueAt(usize),
+}
+
+// 解析用的原文，`name` 一般为文件路径
+pub(crate) struct Source {
+    pub(crate) name: String,
+    pub(crate) text: String,
+}
+
+pub(crate) struct ParseError {
+    pub(crate) kind: &'static str,
+    pub(crate) section: &'static str,
+    // section 里的第几项，从 1 开始
+    pub(crate) entry: Option<usize>,
+    pub(crate) text: Option<String>,
+    pub(crate) path: Vec<Step>,
+    // 指出出错位置的代码片段
+    pub(crate) snippet: Option<String>,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: &'static str, section: &'static str, path: &[Step]) -> Self {
+        // 路径的第二步即为 section 里的第几项
+        let entry = match path.get(1) {
+            Some(Step::Index(i)) | Some(Step::KeyAt(i)) | Some(Step::ValueAt(i)) => Some(i + 1),
+            _ => None,
+        };
+        ParseError { kind, section, entry, text: None, path: path.to_vec(), snippet: None }
+    }
+
+    pub(crate) fn text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub(crate) fn locate(mut self, source: Option<&Source>) -> Self {
+        self.snippet = source.and_then(|s| {
+            let marker = find(&s.text, &self.path)?;
+            Some(snippet(s, marker.line(), marker.col(), self.text.as_deref()))
+        });
+        self
+    }
+
+    // 不带代码片段的一行描述
+    pub(crate) fn summary(&self) -> String {
+        let mut ret = format!("{} in {}", self.kind, self.section);
+        if let Some(entry) = self.entry {
+            ret.push_str(&format!(" (entry {})", entry));
+        }
+        if let Some(text) = &self.text {
+            ret.push_str(&format!(": `{}`", text));
+        }
+        ret
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.summary())?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{}", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// YAML 本身的语法错误也给出代码片段
+pub(crate) fn scan_error(source: &Source, e: &ScanError) -> anyhow::Error {
+    let marker = e.marker();
+    anyhow::anyhow!("{}\n{}", e, snippet(source, marker.line(), marker.col(), None))
+}
+
+// `line` 从 1 开始，`col` 从 0 开始；`text` 为出错的文本，用于确定 `^` 的长度
+fn snippet(source: &Source, line: usize, col: usize, text: Option<&str>) -> String {
+    let content = source.text.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    let rest = content.chars().count().saturating_sub(col);
+    let width = text.map(|t| t.chars().count()).unwrap_or(1).clamp(1, rest.max(1));
+    let number = line.to_string();
+    let pad = " ".repeat(number.len());
+    format!(
+        "{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}",
+        pad, source.name, line, col + 1,
+        pad,
+        number, content,
+        pad, " ".repeat(col), "^".repeat(width)
+    )
+}
+
+// 与 `Yaml` 结构相同，只记录位置
+enum Node {
+    Scalar(Marker),
+    Seq(Marker, Vec<Node>),
+    // key 只记录字符串，用于 `Step::Key`
+    Map(Marker, Vec<(Option<String>, Node, Node)>),
+}
+
+impl Node {
+    fn marker(&self) -> Marker {
+        match self {
+            Node::Scalar(m) | Node::Seq(m, _) | Node::Map(m, _) => *m,
+        }
+    }
+}
+
+fn find(text: &str, path: &[Step]) -> Option<Marker> {
+    let mut builder = Builder { stack: Vec::new(), root: None };
+    Parser::new(text.chars()).load(&mut builder, false).ok()?;
+    let mut node = builder.root.as_ref()?;
+    for step in path {
+        node = match (step, node) {
+            (Step::Key(k), Node::Map(_, entries)) => {
+                entries.iter().find(|(key, _, _)| key.as_deref() == Some(*k)).map(|(_, _, v)| v)?
+            },
+            (Step::Index(i), Node::Seq(_, items)) => items.get(*i)?,
+            (Step::KeyAt(i), Node::Map(_, entries)) => entries.get(*i).map(|(_, k, _)| k)?,
+            (Step::ValueAt(i), Node::Map(_, entries)) => entries.get(*i).map(|(_, _, v)| v)?,
+            _ => return Some(node.marker()),
+        };
+    }
+    Some(node.marker())
+}
+
+enum Frame {
+    Seq(Marker, Vec<Node>),
+    // 还没有配对的 key
+    Map(Marker, Vec<(Option<String>, Node, Node)>, Option<(Option<String>, Node)>),
+}
+
+struct Builder {
+    stack: Vec<Frame>,
+    root: Option<Node>,
+}
+
+impl Builder {
+    fn push(&mut self, node: Node, scalar: Option<String>) {
+        match self.stack.last_mut() {
+            Some(Frame::Seq(_, items)) => items.push(node),
+            Some(Frame::Map(_, entries, pending)) => match pending.take() {
+                Some((key, k)) => entries.push((key, k, node)),
+                None => *pending = Some((scalar, node)),
+            },
+            None => {
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(s, ..) => self.push(Node::Scalar(mark), Some(s)),
+            Event::Alias(_) => self.push(Node::Scalar(mark), None),
+            Event::SequenceStart(_) => self.stack.push(Frame::Seq(mark, Vec::new())),
+            Event::MappingStart(_) => self.stack.push(Frame::Map(mark, Vec::new(), None)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let node = match self.stack.pop() {
+                    Some(Frame::Seq(m, items)) => Node::Seq(m, items),
+                    Some(Frame::Map(m, entries, _)) => Node::Map(m, entries),
+                    None => return,
+                };
+                self.push(node, None);
+            },
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan_error, ParseError, Source, Step};
+    use yaml_rust::YamlLoader;
+
+    const TEXT: &str = "PODS:
+  - A (1.0)
+  - B (1.0):
+    - C (>= x)
+
+SPEC CHECKSUMS:
+  A: aaa
+  B: [bbb]
+
+COCOAPODS: 1.11.0
+";
+
+    fn source(text: &str) -> Source {
+        Source { name: "Podfile.lock".to_string(), text: text.to_string() }
+    }
+
+    #[test]
+    fn caret_points_at_entry_text() {
+        let path = [Step::Key("PODS"), Step::Index(1), Step::ValueAt(0), Step::Index(0)];
+        let e = ParseError::new("unexpected text", "PODS", &path)
+            .text("C (>= x)")
+            .locate(Some(&source(TEXT)));
+        assert_eq!(e.summary(), "unexpected text in PODS (entry 2): `C (>= x)`");
+        assert_eq!(e.snippet.as_deref(), Some(" --> Podfile.lock:4:7
+  |
+4 |     - C (>= x)
+  |       ^^^^^^^^"));
+    }
+
+    #[test]
+    fn locates_keys_and_values_of_hashes() {
+        let path = [Step::Key("SPEC CHECKSUMS"), Step::ValueAt(1)];
+        let e = ParseError::new("unexpected type", "SPEC CHECKSUMS", &path).locate(Some(&source(TEXT)));
+        assert_eq!(e.entry, Some(2));
+        assert!(e.snippet.unwrap().starts_with(" --> Podfile.lock:8:6\n"));
+
+        let path = [Step::Key("SPEC CHECKSUMS"), Step::KeyAt(1)];
+        let e = ParseError::new("unexpected type", "SPEC CHECKSUMS", &path).locate(Some(&source(TEXT)));
+        assert!(e.snippet.unwrap().ends_with("8 |   B: [bbb]\n  |   ^"));
+
+        // 只有 section 时指向 section 的值
+        let e = ParseError::new("unexpected type", "PODS", &[Step::Key("PODS")]).locate(Some(&source(TEXT)));
+        assert_eq!(e.entry, None);
+        assert_eq!(e.summary(), "unexpected type in PODS");
+        assert!(e.snippet.unwrap().starts_with(" --> Podfile.lock:2:3\n"));
+
+        // 没有原文时没有代码片段
+        let e = ParseError::new("unexpected type", "PODS", &[Step::Key("PODS")]).locate(None);
+        assert!(e.snippet.is_none());
+    }
+
+    #[test]
+    fn yaml_syntax_error_has_snippet() {
+        let text = "PODS:\n  - A (1.0)\n  - [B\n\nCOCOAPODS: 1.11.0\n";
+        let e = YamlLoader::load_from_str(text).unwrap_err();
+        let message = scan_error(&source(text), &e).to_string();
+        assert!(message.contains("--> Podfile.lock:"), "{}", message);
+        assert!(message.contains("^"), "{}", message);
+    }
+}
@@ -1,27 +1,158 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use regex;
+use std::rc::Rc;
 use yaml_rust::{YamlLoader, Yaml};
+use ansi_term::{Colour, Style};
 use anyhow::{anyhow};
+use once_cell::sync::Lazy;
 use regex::Regex;
+use crate::lib::dep::Ecosystem;
+use crate::lib::pod::version::Requirement;
+use crate::lib::pod::cache;
+use crate::lib::pod::located::{self, ParseError, Source, Step};
+use crate::lib::pod::writer;
 
+// 边上的 pod 名，解析时同名的共用一份，不为每条边复制 `String`
+pub(crate) type Name = Rc<str>;
+
+#[derive(Clone)]
 pub(crate) struct PodItem {
-    #[allow(dead_code)]
     pub(crate) name: String,
     pub(crate) vers: Option<String>,
-    pub(crate) parents: HashSet<String>,
-    pub(crate) children: HashSet<String>,
+    pub(crate) ecosystem: Ecosystem,
+    pub(crate) parents: HashSet<Name>,
+    pub(crate) children: HashSet<Name>,
+    // 依赖的版本要求，譬如 `~> 4.0`，key 为 children 里的名字
+    pub(crate) requirements: HashMap<Name, Requirement>,
 }
 
 impl PodItem {
-    fn new(name: String, vers: Option<String>) -> Self {
-        PodItem { name, vers, parents: HashSet::new(), children: HashSet::new() }
+    pub(crate) fn new(name: String, vers: Option<String>) -> Self {
+        PodItem {
+            name,
+            vers,
+            ecosystem: Ecosystem::CocoaPods,
+            parents: HashSet::new(),
+            children: HashSet::new(),
+            requirements: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn root_name(&self) -> &str {
+        root_name(&self.name)
+    }
+
+    // `AFNetworking/UIKit` -> Some(`UIKit`)
+    pub(crate) fn subspec_name(&self) -> Option<&str> {
+        self.name.split_once('/').map(|(_, sub)| sub)
+    }
+
+    pub(crate) fn is_subspec(&self) -> bool {
+        self.subspec_name().is_some()
+    }
+}
+
+// 取出 `name` 对应的 pod，没有时插入一个，只在插入时分配 key
+fn pod_mut<'a>(pods: &'a mut HashMap<String, PodItem>, name: &str) -> &'a mut PodItem {
+    if !pods.contains_key(name) {
+        pods.insert(name.to_string(), PodItem::new(name.to_string(), None));
     }
+    pods.get_mut(name).unwrap()
+}
+
+// subspec 的 root spec，`AFNetworking/UIKit` -> `AFNetworking`
+pub(crate) fn root_name(name: &str) -> &str {
+    name.split('/').next().unwrap_or(name)
 }
 
+// 把 subspec 合并到 root spec 上，边也一并合并，合并后不再有 subspec 之间的边
+pub(crate) fn collapse_subspecs(pods: &HashMap<String, PodItem>) -> HashMap<String, PodItem> {
+    let mut ret = HashMap::<String, PodItem>::new();
+    let mut names = Interner::default();
+    for pod in pods.values() {
+        let root = pod.root_name();
+        let item = pod_mut(&mut ret, root);
+        item.ecosystem = pod.ecosystem;
+        if !pod.is_subspec() || item.vers.is_none() {
+            item.vers = pod.vers.clone().or_else(|| item.vers.clone());
+        }
+        for c in &pod.children {
+            let c_root = root_name(c);
+            if c_root == root {
+                continue
+            }
+            let c_root = names.intern(c_root);
+            if let Some(r) = pod.requirements.get(c) {
+                item.requirements.entry(c_root.clone()).or_insert_with(|| r.clone());
+            }
+            item.children.insert(c_root);
+        }
+        for p in &pod.parents {
+            let p_root = root_name(p);
+            if p_root != root {
+                item.parents.insert(names.intern(p_root));
+            }
+        }
+    }
+    ret
+}
+
+// DEPENDENCIES 里的一项，即 Podfile 里直接声明的依赖
+pub(crate) struct Dependency {
+    pub(crate) name: String,
+    // 版本要求，譬如 `~> 4.0`
+    pub(crate) requirement: Option<Requirement>,
+    // 来源描述，譬如 `./Modules/Foo`、`https://x.com/Foo.git`, tag `1.0`
+    pub(crate) source: Option<String>,
+}
+
+// SPEC REPOS 里的一项
+pub(crate) struct SpecRepo {
+    // trunk 或者 repo 的 url
+    pub(crate) name: String,
+    pub(crate) pods: Vec<String>,
+}
+
+// EXTERNAL SOURCES 里的一项
 pub(crate) struct ExternalSource {
     pub(crate) name: String,
     pub(crate) path: Option<String>,
+    pub(crate) git: Option<String>,
+    pub(crate) branch: Option<String>,
+    pub(crate) tag: Option<String>,
+    pub(crate) commit: Option<String>,
+    pub(crate) podspec: Option<String>,
+}
+
+impl ExternalSource {
+    // 有值的字段，譬如 [(`:git`, `https://x.com/Foo.git`), (`:tag`, `1.0`)]
+    pub(crate) fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            (":path", &self.path), (":git", &self.git), (":branch", &self.branch),
+            (":tag", &self.tag), (":commit", &self.commit), (":podspec", &self.podspec),
+        ]
+            .into_iter()
+            .filter_map(|(k, v)| v.as_deref().map(|v| (k, v)))
+            .collect()
+    }
+}
+
+// CHECKOUT OPTIONS 里的一项，即 external source 实际 checkout 的版本
+pub(crate) struct CheckoutOption {
+    pub(crate) name: String,
+    pub(crate) git: Option<String>,
+    pub(crate) branch: Option<String>,
+    pub(crate) tag: Option<String>,
+    pub(crate) commit: Option<String>,
+}
+
+impl CheckoutOption {
+    pub(crate) fn fields(&self) -> Vec<(&'static str, &str)> {
+        [(":git", &self.git), (":branch", &self.branch), (":tag", &self.tag), (":commit", &self.commit)]
+            .into_iter()
+            .filter_map(|(k, v)| v.as_deref().map(|v| (k, v)))
+            .collect()
+    }
 }
 
 mod parse_failed {
@@ -35,119 +166,512 @@ mod parse_failed {
 
 pub(crate) struct Lockfile {
     root: Yaml,
+    // 已经解析好的 PODS，从缓存读到或者写入缓存时解析的，见 `cache`
+    pods: Option<HashMap<String, PodItem>>,
+    // 原文，出错时用来定位
+    source: Option<Source>,
+    // `--lenient`：跳过解析失败的项并输出警告，而不是直接报错
+    lenient: bool,
 }
 
 impl Lockfile {
-    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P, lenient: bool) -> anyhow::Result<Self> {
+        let name = path.as_ref().display().to_string();
         let content = std::fs::read_to_string(path)?;
-        let docs = YamlLoader::load_from_str(content.as_str())?;
-        if docs.len() < 1 {
-            Err(anyhow!("docs should not be empty"))
-        } else {
-            Ok(Lockfile { root: docs[0].clone() })
+        match cache::cache_dir() {
+            Some(dir) => cache::load_or_parse(&dir, Source { name, text: content }, lenient),
+            None => Self::from_source(Source { name, text: content }, lenient),
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn from_yaml_str(content: &str) -> anyhow::Result<Self> {
+        Self::from_source(Source { name: "Podfile.lock".to_string(), text: content.to_string() }, false)
+    }
+
+    pub(crate) fn from_source(source: Source, lenient: bool) -> anyhow::Result<Self> {
+        let root = YamlLoader::load_from_str(&source.text)
+            .map_err(|e| located::scan_error(&source, &e))?
+            .into_iter()
+            .next()
+            .ok_or(anyhow!("docs should not be empty"))?;
+        Ok(Lockfile { root, pods: None, source: Some(source), lenient })
+    }
+
+    pub(crate) fn from_yaml(root: Yaml) -> Self {
+        Lockfile { root, pods: None, source: None, lenient: false }
+    }
+
+    pub(crate) fn with_pods(mut self, pods: HashMap<String, PodItem>) -> Self {
+        self.pods = Some(pods);
+        self
+    }
+
+    #[cfg(test)]
+    pub(crate) fn root(&self) -> &Yaml {
+        &self.root
+    }
+
+    // 按 CocoaPods 的格式输出
+    pub(crate) fn to_yaml_string(&self) -> String {
+        writer::to_yaml_string(&self.root)
+    }
+
     // 解析 PODS 里的内容
     pub(crate) fn pods(&self) -> anyhow::Result<HashMap<String, PodItem>> {
-        let name_and_vers_from_str = |s: &str| -> anyhow::Result<(String, Option<String>)> {
-            let (name, vers) = ("name", "vers");
-            let raw_str = format!("(?P<{}>(?:\\s?[^\\s(])+)(?: \\((?P<{}>.+)\\))?", name, vers);
-            let re = Regex::new(raw_str.as_str()).unwrap();
-            let caps = re.captures(s)
-                .ok_or(anyhow!(parse_failed::UNEXPECTED_TEXT))?;
-            let name = caps.name(name)
-                .ok_or(anyhow!(parse_failed::UNEXPECTED_TEXT))?
-                .as_str()
-                .to_owned();
-            let vers = caps.name(vers)
-                .map(|v| v.as_str().to_owned());
-            Ok((name, vers))
-        };
-
-        let dep_name_from_str = |s: &str| -> anyhow::Result<String> {
-            let name = "name";
-            let raw_str = format!("(?P<{}>(?:\\s?[^\\s(])+)(?: \\((.+)\\))?", name);
-            let re = Regex::new(raw_str.as_str()).unwrap();
-            let caps = re.captures(s)
-                .ok_or(anyhow!(parse_failed::UNEXPECTED_TEXT))?;
-            caps.name(name)
-                .map(|m| m.as_str().to_owned())
-                .ok_or(anyhow!(parse_failed::UNEXPECTED_TEXT))
+        if let Some(pods) = &self.pods {
+            return Ok(pods.clone())
+        }
+        let mut result = HashMap::<String, PodItem>::new();
+        let mut names = Interner::default();
+        let vec = match self.root["PODS"].as_vec() {
+            Some(v) => v,
+            None => {
+                self.skip(ParseError::new(parse_failed::UNEXPECTED_TYPE, "PODS", &[Step::Key("PODS")]))?;
+                return Ok(result)
+            },
         };
-
-        let pods = &self.root["PODS"];
-        let mut result = HashMap::new();
-        let vec = pods.as_vec().ok_or(anyhow!(parse_failed::UNEXPECTED_TYPE))?;
-        for v in vec {
-            let (pod_line, dep_lines) =
-                match v {
-                    Yaml::String(s) => {
-                        (s.as_str(), vec![])
-                    },
-                    Yaml::Hash(h) => {
-                        if h.len() != 1 {
-                            return Err(anyhow!(parse_failed::UNEXPECTED_LENGTH))
-                        }
-                        let k = h.keys().last().unwrap();
-                        let name = k.as_str().ok_or(anyhow!(parse_failed::UNEXPECTED_TYPE))?;
-                        let deps = h.get(k).unwrap().as_vec().ok_or(anyhow!(parse_failed::UNEXPECTED_TYPE))?;
-                        let mut dep_lines = Vec::<&str>::new();
-                        for dep in deps {
-                            let line = dep.as_str().ok_or(anyhow!(parse_failed::UNEXPECTED_TYPE))?;
-                            dep_lines.push(line);
-                        }
-                        (name, dep_lines)
+        for (i, v) in vec.iter().enumerate() {
+            let (pod_line, deps) = match pod_entry(i, v) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.skip(e)?;
+                    continue
+                },
+            };
+            let (pod_name, pod_vers) = pod_line;
+            let pod_name = names.intern(pod_name);
+            pod_mut(&mut result, &pod_name).vers = pod_vers.map(|v| v.to_owned());
+            for (j, dep) in deps.into_iter().flatten().enumerate() {
+                let (dep_name, requirement) = match dep_line(i, j, dep) {
+                    Ok(d) => d,
+                    Err(e) => {
+                        self.skip(e)?;
+                        continue
                     },
-                    _ => {
-                        return Err(anyhow!(parse_failed::UNEXPECTED_TYPE))
-                    }
                 };
-            let (pod_name, pod_vers) = name_and_vers_from_str(pod_line)?;
-            if !result.contains_key(&pod_name) {
-                let item = PodItem::new(pod_name.clone(), pod_vers);
-                result.insert(pod_name.clone(), item);
-            } else {
-                let mut item = result.get_mut(&pod_name).unwrap();
-                item.vers = pod_vers;
-            };
-            for line in dep_lines {
-                let dep_name = dep_name_from_str(line)?;
-                if dep_name != pod_name {
-                    let pod = result.get_mut(&pod_name).unwrap();
-                    pod.children.insert(dep_name.clone());
+                let dep_name = names.intern(dep_name);
+                let dep = pod_mut(&mut result, &dep_name);
+                if dep_name == pod_name {
+                    continue
                 }
-                let dep =
-                    if !result.contains_key(&dep_name) {
-                        let item = PodItem::new(dep_name.clone(), None);
-                        result.insert(dep_name.clone(), item);
-                        result.get_mut(&dep_name).unwrap()
-                    } else {
-                        result.get_mut(&dep_name).unwrap()
-                    };
-                if dep_name != pod_name {
-                    dep.parents.insert(pod_name.clone());
+                dep.parents.insert(pod_name.clone());
+                let pod = result.get_mut(&*pod_name).unwrap();
+                pod.children.insert(dep_name.clone());
+                if let Some(r) = requirement {
+                    pod.requirements.insert(dep_name, r);
                 }
             }
         }
         Ok(result)
     }
 
+    // 解析 DEPENDENCIES 里的内容，即 Podfile 里直接声明的依赖
+    // 括号里可能是版本要求，譬如 `(~> 4.0)`，也可能是来源，譬如 (from `./Modules/Foo`)
+    pub(crate) fn dependencies(&self) -> anyhow::Result<Vec<Dependency>> {
+        const SECTION: &str = "DEPENDENCIES";
+        let mut ret = Vec::new();
+        for (i, v) in self.section_vec(SECTION)?.iter().enumerate() {
+            let error = |kind| ParseError::new(kind, SECTION, &[Step::Key(SECTION), Step::Index(i)]);
+            let parse = || -> Result<Dependency, ParseError> {
+                let line = v.as_str().ok_or_else(|| error(parse_failed::UNEXPECTED_TYPE))?;
+                let text_error = || error(parse_failed::UNEXPECTED_TEXT).text(line);
+                let (name, paren) = split_name_and_vers(line).ok_or_else(text_error)?;
+                let (requirement, source) =
+                    match paren {
+                        Some(p) if p.starts_with("from ") => (None, Some(p["from ".len()..].to_owned())),
+                        Some(p) => (Some(Requirement::parse(p).ok_or_else(text_error)?), None),
+                        None => (None, None),
+                    };
+                Ok(Dependency { name: name.to_owned(), requirement, source })
+            };
+            match parse() {
+                Ok(d) => ret.push(d),
+                Err(e) => self.skip(e)?,
+            }
+        }
+        Ok(ret)
+    }
+
+    // 解析 SPEC REPOS 里的内容
+    pub(crate) fn spec_repos(&self) -> anyhow::Result<Vec<SpecRepo>> {
+        const SECTION: &str = "SPEC REPOS";
+        let mut ret = Vec::new();
+        for (i, (key, value)) in self.section_hash(SECTION)?.iter().enumerate() {
+            let name = match key.as_str() {
+                Some(n) => n.to_owned(),
+                None => {
+                    self.skip(ParseError::new(parse_failed::UNEXPECTED_TYPE, SECTION, &[Step::Key(SECTION), Step::KeyAt(i)]))?;
+                    continue
+                },
+            };
+            let items = match value.as_vec() {
+                Some(items) => items,
+                None => {
+                    self.skip(ParseError::new(parse_failed::UNEXPECTED_TYPE, SECTION, &[Step::Key(SECTION), Step::ValueAt(i)]))?;
+                    continue
+                },
+            };
+            let mut pods = Vec::new();
+            for (j, pod) in items.iter().enumerate() {
+                match pod.as_str() {
+                    Some(p) => pods.push(p.to_owned()),
+                    None => {
+                        let path = [Step::Key(SECTION), Step::ValueAt(i), Step::Index(j)];
+                        self.skip(ParseError::new(parse_failed::UNEXPECTED_TYPE, SECTION, &path))?;
+                    },
+                }
+            }
+            ret.push(SpecRepo { name, pods });
+        }
+        Ok(ret)
+    }
+
     // 解析 EXTERNAL SOURCES 里的内容
-    #[allow(dead_code)]
     pub(crate) fn external_sources(&self) -> anyhow::Result<HashMap<String, ExternalSource>> {
-        let sources = &self.root["EXTERNAL SOURCES"];
-        let hash = sources.as_hash().ok_or(anyhow!(parse_failed::UNEXPECTED_TYPE))?;
         let mut ret = HashMap::new();
-        for (key, value) in hash {
-            let name = key.as_str().ok_or(anyhow!(parse_failed::UNEXPECTED_TYPE))?.to_owned();
-            let mut es = ExternalSource { name, path: None };
-            if let Some(path) = value[":path"].as_str() {
-                es.path = Some(path.to_owned());
-            }
+        for (name, value) in self.named_hashes("EXTERNAL SOURCES")? {
+            let field = |key: &str| value[key].as_str().map(|s| s.to_owned());
+            let es = ExternalSource {
+                path: field(":path"),
+                git: field(":git"),
+                branch: field(":branch"),
+                tag: field(":tag"),
+                commit: field(":commit"),
+                podspec: field(":podspec"),
+                name,
+            };
             ret.insert(es.name.clone(), es);
         }
         Ok(ret)
     }
 
-}
\ No newline at end of file
+    // 解析 CHECKOUT OPTIONS 里的内容
+    pub(crate) fn checkout_options(&self) -> anyhow::Result<HashMap<String, CheckoutOption>> {
+        let mut ret = HashMap::new();
+        for (name, value) in self.named_hashes("CHECKOUT OPTIONS")? {
+            let field = |key: &str| value[key].as_str().map(|s| s.to_owned());
+            let co = CheckoutOption {
+                git: field(":git"),
+                branch: field(":branch"),
+                tag: field(":tag"),
+                commit: field(":commit"),
+                name,
+            };
+            ret.insert(co.name.clone(), co);
+        }
+        Ok(ret)
+    }
+
+    // 解析 SPEC CHECKSUMS 里的内容
+    pub(crate) fn spec_checksums(&self) -> anyhow::Result<HashMap<String, String>> {
+        const SECTION: &str = "SPEC CHECKSUMS";
+        let mut ret = HashMap::new();
+        for (i, (key, value)) in self.section_hash(SECTION)?.iter().enumerate() {
+            let (step, entry) = match (key.as_str(), scalar_to_string(value)) {
+                (Some(name), Some(checksum)) => {
+                    ret.insert(name.to_owned(), checksum);
+                    continue
+                },
+                (None, _) => (Step::KeyAt(i), key),
+                (_, None) => (Step::ValueAt(i), value),
+            };
+            let mut e = ParseError::new(parse_failed::UNEXPECTED_TYPE, SECTION, &[Step::Key(SECTION), step]);
+            if let Some(text) = scalar_to_string(entry) {
+                e = e.text(&text);
+            }
+            self.skip(e)?;
+        }
+        Ok(ret)
+    }
+
+    // 解析 PODFILE CHECKSUM
+    pub(crate) fn podfile_checksum(&self) -> Option<String> {
+        self.section("PODFILE CHECKSUM").and_then(scalar_to_string)
+    }
+
+    // 解析 COCOAPODS，即生成 Podfile.lock 的 CocoaPods 版本
+    pub(crate) fn cocoapods_version(&self) -> Option<String> {
+        self.section("COCOAPODS").and_then(scalar_to_string)
+    }
+
+    // 出错的项：默认返回带位置的错误，`--lenient` 时输出警告并跳过
+    fn skip(&self, e: ParseError) -> anyhow::Result<()> {
+        let e = e.locate(self.source.as_ref());
+        if !self.lenient {
+            return Err(e.into())
+        }
+        eprintln!("{} {}, skipped", Style::from(Colour::Yellow).bold().paint("warning:"), e.summary());
+        if let Some(snippet) = &e.snippet {
+            eprintln!("{}", snippet);
+        }
+        Ok(())
+    }
+
+    // 不存在的 section 返回 `None`
+    fn section(&self, key: &str) -> Option<&Yaml> {
+        match &self.root[key] {
+            Yaml::BadValue | Yaml::Null => None,
+            v => Some(v),
+        }
+    }
+
+    // 数组形式的 section，不存在或者类型不对（lenient）时为空
+    fn section_vec(&self, key: &'static str) -> anyhow::Result<&[Yaml]> {
+        match self.section(key).map(|v| v.as_vec()) {
+            Some(Some(v)) => Ok(v),
+            Some(None) => {
+                self.skip(ParseError::new(parse_failed::UNEXPECTED_TYPE, key, &[Step::Key(key)]))?;
+                Ok(&[])
+            },
+            None => Ok(&[]),
+        }
+    }
+
+    // hash 形式的 section，不存在或者类型不对（lenient）时为空
+    fn section_hash(&self, key: &'static str) -> anyhow::Result<Vec<(&Yaml, &Yaml)>> {
+        match self.section(key).map(|v| v.as_hash()) {
+            Some(Some(h)) => Ok(h.iter().collect()),
+            Some(None) => {
+                self.skip(ParseError::new(parse_failed::UNEXPECTED_TYPE, key, &[Step::Key(key)]))?;
+                Ok(Vec::new())
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // 形如 `Name: { :key: value }` 的 section
+    fn named_hashes(&self, key: &'static str) -> anyhow::Result<Vec<(String, &Yaml)>> {
+        let mut ret = Vec::new();
+        for (i, (name, value)) in self.section_hash(key)?.into_iter().enumerate() {
+            let step = match (name.as_str(), value.as_hash()) {
+                (Some(name), Some(_)) => {
+                    ret.push((name.to_owned(), value));
+                    continue
+                },
+                (None, _) => Step::KeyAt(i),
+                (_, None) => Step::ValueAt(i),
+            };
+            let mut e = ParseError::new(parse_failed::UNEXPECTED_TYPE, key, &[Step::Key(key), step]);
+            if let Some(name) = name.as_str() {
+                e = e.text(name);
+            }
+            self.skip(e)?;
+        }
+        Ok(ret)
+    }
+}
+
+// PODS 里的一项：`Name (1.0)` 或者 `{ Name (1.0): [Dep (~> 1.0), ...] }`
+#[allow(clippy::type_complexity)]
+fn pod_entry(index: usize, v: &Yaml) -> Result<((&str, Option<&str>), Option<&[Yaml]>), ParseError> {
+    let error = |kind, steps: &[Step]| {
+        let mut path = vec![Step::Key("PODS"), Step::Index(index)];
+        path.extend_from_slice(steps);
+        ParseError::new(kind, "PODS", &path)
+    };
+    let (line, deps, steps): (&str, Option<&[Yaml]>, &[Step]) =
+        match v {
+            Yaml::String(s) => (s.as_str(), None, &[]),
+            Yaml::Hash(h) => {
+                if h.len() != 1 {
+                    return Err(error(parse_failed::UNEXPECTED_LENGTH, &[]))
+                }
+                let (k, deps) = h.iter().next().unwrap();
+                let line = k.as_str().ok_or_else(|| error(parse_failed::UNEXPECTED_TYPE, &[Step::KeyAt(0)]))?;
+                let deps = deps.as_vec().ok_or_else(|| error(parse_failed::UNEXPECTED_TYPE, &[Step::ValueAt(0)]))?;
+                (line, Some(deps.as_slice()), &[Step::KeyAt(0)])
+            },
+            _ => {
+                let e = error(parse_failed::UNEXPECTED_TYPE, &[]);
+                return Err(match scalar_to_string(v) {
+                    Some(text) => e.text(&text),
+                    None => e,
+                })
+            },
+        };
+    let pod = split_name_and_vers(line)
+        .ok_or_else(|| error(parse_failed::UNEXPECTED_TEXT, steps).text(line))?;
+    Ok((pod, deps))
+}
+
+// PODS 里某一项下的依赖：`Dep (~> 1.0)`
+fn dep_line(index: usize, dep_index: usize, v: &Yaml) -> Result<(&str, Option<Requirement>), ParseError> {
+    let path = [Step::Key("PODS"), Step::Index(index), Step::ValueAt(0), Step::Index(dep_index)];
+    let line = v.as_str().ok_or_else(|| ParseError::new(parse_failed::UNEXPECTED_TYPE, "PODS", &path))?;
+    let error = || ParseError::new(parse_failed::UNEXPECTED_TEXT, "PODS", &path).text(line);
+    let (name, requirement) = split_name_and_vers(line).ok_or_else(error)?;
+    let requirement = match requirement {
+        Some(r) => Some(Requirement::parse(r).ok_or_else(error)?),
+        None => None,
+    };
+    Ok((name, requirement))
+}
+
+// 纯数字的 checksum、`1.11` 这样的版本号会被 YAML 解析成数字
+fn scalar_to_string(v: &Yaml) -> Option<String> {
+    match v {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.to_owned()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        _ => None,
+    }
+}
+
+// PODS 和 DEPENDENCIES 的每一行都要匹配，只编译一次
+static NAME_AND_VERS_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new("^(?P<name>(?:\\s?[^\\s(])+)(?: \\((?P<vers>.+)\\))?$").unwrap()
+});
+
+// `AFNetworking (4.0.1)` -> (`AFNetworking`, Some(`4.0.1`))
+// `AFNetworking/Security (~> 4.0)` -> (`AFNetworking/Security`, Some(`~> 4.0`))
+pub(crate) fn name_and_vers_from_str(s: &str) -> anyhow::Result<(String, Option<String>)> {
+    let (name, vers) = split_name_and_vers(s)
+        .ok_or_else(|| anyhow!("{}: `{}`", parse_failed::UNEXPECTED_TEXT, s))?;
+    Ok((name.to_owned(), vers.map(|v| v.to_owned())))
+}
+
+fn split_name_and_vers(s: &str) -> Option<(&str, Option<&str>)> {
+    let caps = NAME_AND_VERS_RE.captures(s)?;
+    let name = caps.name("name")?.as_str();
+    let vers = caps.name("vers").map(|v| v.as_str());
+    Some((name, vers))
+}
+
+// 同一个名字只分配一次，边上的 pod 名都指向同一份
+#[derive(Default)]
+pub(crate) struct Interner {
+    names: HashSet<Name>,
+}
+
+impl Interner {
+    pub(crate) fn intern(&mut self, name: &str) -> Name {
+        if let Some(n) = self.names.get(name) {
+            return n.clone()
+        }
+        let n = Name::from(name);
+        self.names.insert(n.clone());
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::lib::pod::located::Source;
+    use super::{collapse_subspecs, root_name, Lockfile, Name};
+
+    const LOCKFILE: &str = "
+PODS:
+  - AFNetworking (4.0.1):
+    - AFNetworking/NSURLSession (= 4.0.1)
+    - AFNetworking/UIKit (= 4.0.1)
+  - AFNetworking/NSURLSession (4.0.1):
+    - AFNetworking/Reachability
+  - AFNetworking/Reachability (4.0.1)
+  - AFNetworking/UIKit (4.0.1):
+    - AFNetworking/NSURLSession
+  - Firebase/Analytics (8.0.0):
+    - Firebase/Core
+  - Firebase/Core (8.0.0):
+    - FirebaseCore (~> 8.0)
+  - FirebaseCore (8.0.0)
+  - RBFoundation (0.1.0):
+    - AFNetworking/UIKit (~> 4.0)
+    - Firebase/Analytics
+
+COCOAPODS: 1.11.0
+";
+
+    #[test]
+    fn subspec_names() {
+        assert_eq!(root_name("AFNetworking/UIKit"), "AFNetworking");
+        assert_eq!(root_name("AFNetworking"), "AFNetworking");
+        let pods = Lockfile::from_yaml_str(LOCKFILE).unwrap().pods().unwrap();
+        assert_eq!(pods["AFNetworking/UIKit"].subspec_name(), Some("UIKit"));
+        assert!(!pods["AFNetworking"].is_subspec());
+    }
+
+    #[test]
+    fn collapse_merges_edges_into_root_specs() {
+        let pods = collapse_subspecs(&Lockfile::from_yaml_str(LOCKFILE).unwrap().pods().unwrap());
+        let mut names = pods.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+        assert_eq!(names, ["AFNetworking", "Firebase", "FirebaseCore", "RBFoundation"]);
+
+        let sorted = |set: &HashSet<Name>| {
+            let mut v = set.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+            v.sort();
+            v
+        };
+        // subspec 之间的边不再保留
+        assert!(pods["AFNetworking"].children.is_empty());
+        assert_eq!(sorted(&pods["AFNetworking"].parents), ["RBFoundation"]);
+        assert_eq!(sorted(&pods["RBFoundation"].children), ["AFNetworking", "Firebase"]);
+        assert_eq!(pods["RBFoundation"].requirements["AFNetworking"].to_string(), "~> 4.0");
+        assert_eq!(sorted(&pods["Firebase"].children), ["FirebaseCore"]);
+        assert_eq!(sorted(&pods["FirebaseCore"].parents), ["Firebase"]);
+    }
+
+    #[test]
+    fn collapse_takes_version_of_subspecs_without_root() {
+        let pods = collapse_subspecs(&Lockfile::from_yaml_str(LOCKFILE).unwrap().pods().unwrap());
+        assert_eq!(pods["AFNetworking"].vers.as_deref(), Some("4.0.1"));
+        assert_eq!(pods["Firebase"].vers.as_deref(), Some("8.0.0"));
+        assert_eq!(pods["Firebase"].name, "Firebase");
+    }
+
+    // PODS、DEPENDENCIES、SPEC CHECKSUMS 里各有一个出错的项
+    const MALFORMED: &str = "
+PODS:
+  - A (1.0.0):
+    - B (>=)
+    - C
+  - B (1.0.0)
+  - C (1.0.0)
+  - [D]
+
+DEPENDENCIES:
+  - A (~> 1.0)
+  - B (>=)
+
+SPEC CHECKSUMS:
+  A: aaa
+  B: [bbb]
+
+COCOAPODS: 1.11.0
+";
+
+    fn malformed(lenient: bool) -> Lockfile {
+        let source = Source { name: "Podfile.lock".to_string(), text: MALFORMED.to_string() };
+        Lockfile::from_source(source, lenient).unwrap()
+    }
+
+    #[test]
+    fn strict_errors_are_located() {
+        let lockfile = malformed(false);
+        let e = lockfile.pods().err().unwrap().to_string();
+        assert!(e.starts_with("unexpected text in PODS (entry 1): `B (>=)`\n --> Podfile.lock:4:7\n"), "{}", e);
+        let e = lockfile.dependencies().err().unwrap().to_string();
+        assert!(e.starts_with("unexpected text in DEPENDENCIES (entry 2): `B (>=)`\n  --> Podfile.lock:12:5\n"), "{}", e);
+        let e = lockfile.spec_checksums().err().unwrap().to_string();
+        assert!(e.starts_with("unexpected type in SPEC CHECKSUMS (entry 2)\n  --> Podfile.lock:16:6\n"), "{}", e);
+    }
+
+    #[test]
+    fn lenient_skips_malformed_entries() {
+        let lockfile = malformed(true);
+        let pods = lockfile.pods().unwrap();
+        let mut names = pods.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+        assert_eq!(names, ["A", "B", "C"]);
+        // 出错的边被跳过，其余的边不受影响
+        assert_eq!(pods["A"].children.iter().map(|c| c.to_string()).collect::<Vec<String>>(), ["C"]);
+        assert!(pods["B"].parents.is_empty());
+
+        let deps = lockfile.dependencies().unwrap();
+        assert_eq!(deps.iter().map(|d| d.name.as_str()).collect::<Vec<&str>>(), ["A"]);
+        let checksums = lockfile.spec_checksums().unwrap();
+        assert_eq!(checksums.len(), 1);
+        assert_eq!(checksums["A"], "aaa");
+    }
+}
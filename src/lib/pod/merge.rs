@@ -0,0 +1,359 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use yaml_rust::{Yaml, YamlLoader};
+use yaml_rust::yaml::Hash;
+use crate::lib::pod::lockfile::{name_and_vers_from_str, Lockfile};
+use crate::lib::pod::version::Version;
+use crate::lib::pod::writer::KEY_ORDER;
+
+// pod merge
+// Podfile.lock 的三方合并，可以注册为 git 的 merge driver：
+//     git config merge.podfile-lock.driver "rubo pod merge %O %A %B"
+//     echo "Podfile.lock merge=podfile-lock" >> .gitattributes
+// 按 section 里的每一项合并，只有同一项两边改得不一样才算冲突
+
+pub(crate) struct Conflict {
+    pub(crate) section: String,
+    // pod 名，整个 section 冲突时为 `None`
+    pub(crate) name: Option<String>,
+    pub(crate) base: Option<String>,
+    pub(crate) ours: Option<String>,
+    pub(crate) theirs: Option<String>,
+}
+
+pub(crate) struct MergeResult {
+    // 冲突的项保留 ours 的内容
+    pub(crate) lockfile: Lockfile,
+    pub(crate) conflicts: Vec<Conflict>,
+}
+
+// git 在双方都新增文件时传入空的 base
+pub(crate) fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Yaml> {
+    let content = std::fs::read_to_string(path)?;
+    let root = YamlLoader::load_from_str(&content)?
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| Yaml::Hash(Hash::new()));
+    Ok(root)
+}
+
+pub(crate) fn merge(base: &Yaml, ours: &Yaml, theirs: &Yaml) -> anyhow::Result<MergeResult> {
+    let mut keys = BTreeSet::new();
+    for root in [base, ours, theirs] {
+        if let Some(h) = root.as_hash() {
+            keys.extend(h.keys().filter_map(|k| k.as_str()).map(|k| k.to_string()));
+        }
+    }
+    let mut conflicts = Vec::new();
+    let mut merged = Hash::new();
+    for key in keys {
+        let section = |root: &Yaml| match &root[key.as_str()] {
+            Yaml::BadValue => None,
+            v => Some(v.clone()),
+        };
+        let (b, o, t) = (section(base), section(ours), section(theirs));
+        let value = match key.as_str() {
+            // 数组，每一项以 pod 名区分
+            "PODS" | "DEPENDENCIES" => {
+                let entries = |v: &Option<Yaml>| -> anyhow::Result<BTreeMap<String, Yaml>> {
+                    let mut ret = BTreeMap::new();
+                    for item in v.as_ref().and_then(|v| v.as_vec()).into_iter().flatten() {
+                        ret.insert(entry_name(item)?, item.clone());
+                    }
+                    Ok(ret)
+                };
+                let items = merge_entries(&key, &entries(&b)?, &entries(&o)?, &entries(&t)?, &mut conflicts);
+                non_empty(Yaml::Array(items.into_values().collect()))
+            },
+            // hash，每一项以 key 区分
+            "SPEC CHECKSUMS" | "EXTERNAL SOURCES" | "CHECKOUT OPTIONS" => {
+                let entries = |v: &Option<Yaml>| -> BTreeMap<String, Yaml> {
+                    v.as_ref()
+                        .and_then(|v| v.as_hash())
+                        .map(|h| {
+                            h.iter()
+                                .filter_map(|(k, v)| k.as_str().map(|k| (k.to_string(), v.clone())))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                let items = merge_entries(&key, &entries(&b), &entries(&o), &entries(&t), &mut conflicts);
+                non_empty(Yaml::Hash(items.into_iter().map(|(k, v)| (Yaml::String(k), v)).collect()))
+            },
+            "SPEC REPOS" => merge_spec_repos(&b, &o, &t),
+            // 新版本的 CocoaPods 生成的 Podfile.lock 也能被旧版本读取，取较高的版本
+            "COCOAPODS" if o != t && o != b && t != b => {
+                let version = |v: &Option<Yaml>| {
+                    v.as_ref().map(text).and_then(|s| Version::parse(&s))
+                };
+                if version(&t) > version(&o) { t } else { o }
+            },
+            _ => merge_value(&key, None, b, o, t, &mut conflicts),
+        };
+        if let Some(v) = value {
+            merged.insert(Yaml::String(key), v);
+        }
+    }
+    conflicts.sort_by_key(|c| {
+        let index = KEY_ORDER.iter().position(|k| *k == c.section).unwrap_or(KEY_ORDER.len());
+        (index, c.name.clone())
+    });
+    Ok(MergeResult { lockfile: Lockfile::from_yaml(Yaml::Hash(merged)), conflicts })
+}
+
+// PODS 里为 `Name (1.0)` 或者 `{ Name (1.0): [...] }`，DEPENDENCIES 里为 `Name (~> 1.0)`
+fn entry_name(item: &Yaml) -> anyhow::Result<String> {
+    let line = match item {
+        Yaml::Hash(h) => h.keys().next().and_then(|k| k.as_str()),
+        _ => item.as_str(),
+    };
+    let line = line.ok_or_else(|| anyhow::anyhow!("unexpected entry: {:?}", item))?;
+    Ok(name_and_vers_from_str(line)?.0)
+}
+
+fn merge_entries(
+    section: &str,
+    base: &BTreeMap<String, Yaml>,
+    ours: &BTreeMap<String, Yaml>,
+    theirs: &BTreeMap<String, Yaml>,
+    conflicts: &mut Vec<Conflict>
+) -> BTreeMap<String, Yaml> {
+    let names = base.keys().chain(ours.keys()).chain(theirs.keys()).collect::<BTreeSet<&String>>();
+    let mut ret = BTreeMap::new();
+    for name in names {
+        let value = merge_value(
+            section,
+            Some(name),
+            base.get(name).cloned(),
+            ours.get(name).cloned(),
+            theirs.get(name).cloned(),
+            conflicts
+        );
+        if let Some(v) = value {
+            ret.insert(name.clone(), v);
+        }
+    }
+    ret
+}
+
+// 一项的三方合并，冲突时保留 ours
+fn merge_value(
+    section: &str,
+    name: Option<&str>,
+    base: Option<Yaml>,
+    ours: Option<Yaml>,
+    theirs: Option<Yaml>,
+    conflicts: &mut Vec<Conflict>
+) -> Option<Yaml> {
+    if ours == theirs || theirs == base {
+        return ours
+    }
+    if ours == base {
+        return theirs
+    }
+    // PODS 里版本不同时只显示 `Name (1.0)`，版本相同再把依赖列表也显示出来
+    let short = |v: &Option<Yaml>| v.as_ref().map(|v| match v {
+        Yaml::Hash(h) if h.len() == 1 => h.keys().next().map(text).unwrap_or_default(),
+        _ => text(v),
+    });
+    let describe: &dyn Fn(&Option<Yaml>) -> Option<String> =
+        if short(&ours) != short(&theirs) { &short } else { &|v| v.as_ref().map(text) };
+    conflicts.push(Conflict {
+        section: section.to_string(),
+        name: name.map(|n| n.to_string()),
+        base: describe(&base),
+        ours: describe(&ours),
+        theirs: describe(&theirs),
+    });
+    ours
+}
+
+// 每个 repo 下的 pod 分别合并，不会冲突
+fn merge_spec_repos(base: &Option<Yaml>, ours: &Option<Yaml>, theirs: &Option<Yaml>) -> Option<Yaml> {
+    let repos = |v: &Option<Yaml>| -> BTreeMap<String, BTreeSet<String>> {
+        let mut ret = BTreeMap::new();
+        for (k, v) in v.as_ref().and_then(|v| v.as_hash()).into_iter().flatten() {
+            let pods = v.as_vec()
+                .map(|pods| pods.iter().filter_map(|p| p.as_str()).map(|p| p.to_string()).collect())
+                .unwrap_or_default();
+            ret.insert(text(k), pods);
+        }
+        ret
+    };
+    let (base, ours, theirs) = (repos(base), repos(ours), repos(theirs));
+    let empty = BTreeSet::new();
+    let names = base.keys().chain(ours.keys()).chain(theirs.keys()).collect::<BTreeSet<&String>>();
+    let mut merged = Hash::new();
+    for name in names {
+        let (b, o, t) = (
+            base.get(name).unwrap_or(&empty),
+            ours.get(name).unwrap_or(&empty),
+            theirs.get(name).unwrap_or(&empty),
+        );
+        let pods = b.iter().chain(o).chain(t)
+            .filter(|p| {
+                let (in_b, in_o, in_t) = (b.contains(*p), o.contains(*p), t.contains(*p));
+                if in_o != in_b { in_o } else { in_t }
+            })
+            .collect::<BTreeSet<&String>>();
+        if !pods.is_empty() {
+            let pods = pods.into_iter().map(|p| Yaml::String(p.clone())).collect();
+            merged.insert(Yaml::String(name.clone()), Yaml::Array(pods));
+        }
+    }
+    non_empty(Yaml::Hash(merged))
+}
+
+// 合并后为空的 section 直接去掉
+fn non_empty(v: Yaml) -> Option<Yaml> {
+    match &v {
+        Yaml::Array(a) if a.is_empty() => None,
+        Yaml::Hash(h) if h.is_empty() => None,
+        _ => Some(v),
+    }
+}
+
+// 冲突信息里的单行描述
+fn text(v: &Yaml) -> String {
+    match v {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        Yaml::Array(items) => format!("[{}]", items.iter().map(text).collect::<Vec<String>>().join(", ")),
+        Yaml::Hash(h) => {
+            let fields = h.iter()
+                .map(|(k, v)| format!("{} {}", text(k), text(v)))
+                .collect::<Vec<String>>();
+            fields.join(", ")
+        },
+        _ => "~".to_string(),
+    }
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::Conflict;
+
+    // 作为 merge driver 时标准输出可能就是合并结果，所以输出到标准错误
+    pub(crate) fn print_conflicts(conflicts: &[Conflict]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let side = |v: &Option<String>| v.clone().unwrap_or_else(|| "(removed)".to_string());
+        for c in conflicts {
+            let title = match &c.name {
+                Some(n) => format!("{} {}", c.section, n),
+                None => c.section.clone(),
+            };
+            eprintln!("{} {}", Style::from(Colour::Red).bold().paint("✗"), Style::from(Colour::Yellow).paint(title));
+            if c.base.is_some() {
+                eprintln!("    {} {}", Style::from(light_gray).paint("base  "), side(&c.base));
+            }
+            eprintln!("    {} {}", Style::from(light_gray).paint("ours  "), side(&c.ours));
+            eprintln!("    {} {}", Style::from(light_gray).paint("theirs"), side(&c.theirs));
+        }
+        if !conflicts.is_empty() {
+            let summary = format!(
+                "{} {}, ours is kept for each of them",
+                conflicts.len(),
+                if conflicts.len() == 1 { "conflict" } else { "conflicts" }
+            );
+            eprintln!("{}", Style::from(light_gray).paint(summary));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yaml_rust::{Yaml, YamlLoader};
+    use super::merge;
+
+    const BASE: &str = "
+PODS:
+  - AFNetworking (4.0.0)
+  - Masonry (1.1.0)
+  - SDWebImage (5.0.0)
+
+DEPENDENCIES:
+  - AFNetworking (~> 4.0)
+  - Masonry
+  - SDWebImage
+
+SPEC CHECKSUMS:
+  AFNetworking: aaa
+  Masonry: mmm
+  SDWebImage: sss
+
+PODFILE CHECKSUM: base
+
+COCOAPODS: 1.11.0
+";
+
+    fn yaml(text: &str) -> Yaml {
+        YamlLoader::load_from_str(text).unwrap().remove(0)
+    }
+
+    fn versions(root: &Yaml) -> Vec<String> {
+        root["PODS"].as_vec().unwrap().iter().map(|p| p.as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn clean_merge_takes_changes_from_both_sides() {
+        let ours = BASE.replace("AFNetworking (4.0.0)", "AFNetworking (4.0.1)")
+            .replace("AFNetworking: aaa", "AFNetworking: aaa2");
+        let theirs = BASE.replace("SDWebImage (5.0.0)", "SDWebImage (5.1.0)")
+            .replace("SDWebImage: sss", "SDWebImage: sss2")
+            .replace("COCOAPODS: 1.11.0", "COCOAPODS: 1.12.1");
+        let result = merge(&yaml(BASE), &yaml(&ours), &yaml(&theirs)).unwrap();
+        assert!(result.conflicts.is_empty());
+        let root = result.lockfile.root();
+        assert_eq!(versions(root), ["AFNetworking (4.0.1)", "Masonry (1.1.0)", "SDWebImage (5.1.0)"]);
+        assert_eq!(root["SPEC CHECKSUMS"]["AFNetworking"].as_str(), Some("aaa2"));
+        assert_eq!(root["SPEC CHECKSUMS"]["SDWebImage"].as_str(), Some("sss2"));
+        assert_eq!(root["COCOAPODS"].as_str(), Some("1.12.1"));
+    }
+
+    #[test]
+    fn version_changed_on_both_sides_conflicts() {
+        let ours = BASE.replace("Masonry (1.1.0)", "Masonry (1.1.1)");
+        let theirs = BASE.replace("Masonry (1.1.0)", "Masonry (1.2.0)");
+        let result = merge(&yaml(BASE), &yaml(&ours), &yaml(&theirs)).unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        let c = &result.conflicts[0];
+        assert_eq!(c.section, "PODS");
+        assert_eq!(c.name.as_deref(), Some("Masonry"));
+        assert_eq!(c.base.as_deref(), Some("Masonry (1.1.0)"));
+        assert_eq!(c.ours.as_deref(), Some("Masonry (1.1.1)"));
+        assert_eq!(c.theirs.as_deref(), Some("Masonry (1.2.0)"));
+        // 冲突的项保留 ours
+        assert!(versions(result.lockfile.root()).contains(&"Masonry (1.1.1)".to_string()));
+    }
+
+    #[test]
+    fn same_change_on_both_sides_does_not_conflict() {
+        let ours = BASE.replace("Masonry (1.1.0)", "Masonry (1.2.0)");
+        let result = merge(&yaml(BASE), &yaml(&ours), &yaml(&ours)).unwrap();
+        assert!(result.conflicts.is_empty());
+        assert!(versions(result.lockfile.root()).contains(&"Masonry (1.2.0)".to_string()));
+    }
+
+    #[test]
+    fn one_sided_delete_is_applied() {
+        let theirs = BASE.replace("  - Masonry (1.1.0)\n", "")
+            .replace("  - Masonry\n", "")
+            .replace("  Masonry: mmm\n", "");
+        let result = merge(&yaml(BASE), &yaml(BASE), &yaml(&theirs)).unwrap();
+        assert!(result.conflicts.is_empty());
+        let root = result.lockfile.root();
+        assert_eq!(versions(root), ["AFNetworking (4.0.0)", "SDWebImage (5.0.0)"]);
+        assert!(root["SPEC CHECKSUMS"]["Masonry"].is_badvalue());
+        assert_eq!(root["DEPENDENCIES"].as_vec().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn delete_against_modify_conflicts() {
+        let ours = BASE.replace("Masonry (1.1.0)", "Masonry (1.2.0)");
+        let theirs = BASE.replace("  - Masonry (1.1.0)\n", "");
+        let result = merge(&yaml(BASE), &yaml(&ours), &yaml(&theirs)).unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].theirs, None);
+    }
+}
@@ -1,3 +1,25 @@
 pub(crate) mod lockfile;
+pub(crate) mod located;
+pub(crate) mod writer;
+pub(crate) mod cache;
+pub(crate) mod version;
 pub(crate) mod dep;
+pub(crate) mod pattern;
 pub(crate) mod search;
+pub(crate) mod clean;
+pub(crate) mod diff;
+pub(crate) mod info;
+pub(crate) mod check;
+pub(crate) mod graph;
+pub(crate) mod stats;
+pub(crate) mod path;
+pub(crate) mod affected;
+pub(crate) mod merge;
+pub(crate) mod status;
+pub(crate) mod size;
+pub(crate) mod licenses;
+pub(crate) mod lint;
+pub(crate) mod imports;
+pub(crate) mod unused;
+pub(crate) mod spm;
+pub(crate) mod project;
@@ -0,0 +1,249 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use crate::lib::pod::dep::{rdep_closure, Hop};
+use crate::lib::pod::lockfile::{collapse_subspecs, Lockfile, PodItem};
+use crate::lib::pod::pattern::match_pods;
+
+// pod path
+// 回答“A 是否（间接）依赖 B，经过了哪些 pod”，A、B 都可以是 glob 或正则
+
+// 从 `from` 出发，到达任一 `targets` 的最短路径
+pub(crate) fn shortest_path(
+    pods: &HashMap<String, PodItem>,
+    from: &str,
+    targets: &HashSet<String>
+) -> Option<Vec<String>> {
+    let mut prev = HashMap::<&str, &str>::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(name) = queue.pop_front() {
+        let mut children = pods[name].children.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
+        children.sort();
+        for c in children {
+            if c == from || prev.contains_key(c) || !pods.contains_key(c) {
+                continue
+            }
+            prev.insert(c, name);
+            if targets.contains(c) {
+                let mut path = vec![c.to_string()];
+                let mut current = c;
+                while current != from {
+                    current = prev[current];
+                    path.push(current.to_string());
+                }
+                path.reverse();
+                return Some(path)
+            }
+            queue.push_back(c);
+        }
+    }
+    None
+}
+
+// 从 `from` 出发，到达任一 `targets` 的所有简单路径，`max_len` 限制路径的边数
+pub(crate) fn all_paths(
+    pods: &HashMap<String, PodItem>,
+    from: &str,
+    targets: &HashSet<String>,
+    max_len: usize
+) -> anyhow::Result<Vec<Vec<String>>> {
+    // 只沿着能到达 targets 的 pod 往下走
+    let mut reachable = HashSet::new();
+    for t in targets {
+        reachable.extend(rdep_closure(pods, t)?);
+    }
+    let mut ret = Vec::new();
+    let mut stack = vec![from.to_string()];
+    collect_paths(pods, &reachable, targets, max_len, &mut stack, &mut ret);
+    Ok(ret)
+}
+
+fn collect_paths(
+    pods: &HashMap<String, PodItem>,
+    reachable: &HashSet<String>,
+    targets: &HashSet<String>,
+    max_len: usize,
+    stack: &mut Vec<String>,
+    result: &mut Vec<Vec<String>>
+) {
+    if stack.len() > 1 && targets.contains(stack.last().unwrap()) {
+        result.push(stack.clone());
+        return
+    }
+    if stack.len() > max_len {
+        return
+    }
+    let mut children = pods[stack.last().unwrap()].children.iter()
+        .filter(|c| reachable.contains(c.as_ref()) && pods.contains_key(c.as_ref()))
+        .filter(|c| !stack.iter().any(|s| s.as_str() == c.as_ref()))
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+    children.sort();
+    for c in children {
+        stack.push(c);
+        collect_paths(pods, reachable, targets, max_len, stack, result);
+        stack.pop();
+    }
+}
+
+// `all` 为 `Some(max_len)` 时列出所有简单路径，否则每个起点只列出最短路径
+// 返回是否找到了路径
+pub(crate) fn print_paths<P: AsRef<Path>>(
+    path: P,
+    lenient: bool,
+    from: &str,
+    to: &str,
+    all: Option<usize>,
+    collapse: bool
+) -> anyhow::Result<bool> {
+    let lockfile = Lockfile::from_file(path, lenient)?;
+    let mut pods = lockfile.pods()?;
+    if collapse {
+        pods = collapse_subspecs(&pods);
+    }
+    let froms = match_pods(&pods, &[from.to_string()], collapse)?;
+    let targets = match_pods(&pods, &[to.to_string()], collapse)?
+        .into_iter()
+        .collect::<HashSet<String>>();
+
+    let mut paths = Vec::new();
+    for f in &froms {
+        match all {
+            Some(max_len) => paths.extend(all_paths(&pods, f, &targets, max_len)?),
+            None => paths.extend(shortest_path(&pods, f, &targets)),
+        }
+    }
+    let paths = paths.into_iter()
+        .map(|p| {
+            p.iter()
+                .enumerate()
+                .map(|(i, name)| Hop {
+                    name: name.clone(),
+                    vers: pods[name].vers.clone(),
+                    requirement: if i == 0 { None } else {
+                        pods[&p[i - 1]].requirements.get(name.as_str()).map(|r| r.to_string())
+                    },
+                })
+                .collect::<Vec<Hop>>()
+        })
+        .collect::<Vec<Vec<Hop>>>();
+    printer::print_paths(from, to, &paths);
+    Ok(!paths.is_empty())
+}
+
+mod printer {
+    use ansi_term::{Colour, Style};
+    use crate::lib::pod::dep::Hop;
+
+    pub(super) fn print_paths(from: &str, to: &str, paths: &[Vec<Hop>]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if paths.is_empty() {
+            println!("{}", Style::from(light_gray).paint(format!("No path from {} to {}", from, to)));
+            return
+        }
+        for path in paths {
+            let text = path.iter()
+                .map(|hop| {
+                    let mut text = Style::from(Colour::Yellow).paint(&hop.name).to_string();
+                    if let Some(v) = &hop.vers {
+                        text.push_str(&format!(" {}", v));
+                    }
+                    if let Some(r) = &hop.requirement {
+                        text.push_str(&Style::from(light_gray).paint(format!(" ({})", r)).to_string());
+                    }
+                    text
+                })
+                .collect::<Vec<String>>()
+                .join(&Style::from(light_gray).paint(" -> ").to_string());
+            println!("{}", text);
+        }
+        let shortest = paths.iter().map(|p| p.len() - 1).min().unwrap_or(0);
+        let summary = format!(
+            "\n{} {} from {} to {}, shortest has {} {}",
+            paths.len(),
+            if paths.len() == 1 { "path" } else { "paths" },
+            from,
+            to,
+            shortest,
+            if shortest == 1 { "hop" } else { "hops" }
+        );
+        println!("{}", Style::from(light_gray).paint(summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use crate::lib::pod::lockfile::{Lockfile, PodItem};
+    use super::{all_paths, shortest_path};
+
+    // A -> B -> D -> E
+    // A -> C -> D
+    // A -> E
+    // E -> B 构成环 B -> D -> E -> B
+    const LOCKFILE: &str = "
+PODS:
+  - A (1.0.0):
+    - B
+    - C
+    - E
+  - B (1.0.0):
+    - D
+  - C (1.0.0):
+    - D (~> 2.0)
+  - D (2.0.0):
+    - E
+  - E (3.0.0):
+    - B
+  - F (1.0.0)
+
+DEPENDENCIES:
+  - A
+  - F
+
+COCOAPODS: 1.11.0
+";
+
+    fn pods() -> HashMap<String, PodItem> {
+        Lockfile::from_yaml_str(LOCKFILE).unwrap().pods().unwrap()
+    }
+
+    fn targets(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn shortest_path_takes_fewest_hops() {
+        let pods = pods();
+        assert_eq!(shortest_path(&pods, "A", &targets(&["E"])).unwrap(), ["A", "E"]);
+        // 同样长度时按名字取第一条
+        assert_eq!(shortest_path(&pods, "A", &targets(&["D"])).unwrap(), ["A", "B", "D"]);
+        // 多个目标时到达最近的一个
+        assert_eq!(shortest_path(&pods, "C", &targets(&["B", "E"])).unwrap(), ["C", "D", "E"]);
+        assert_eq!(shortest_path(&pods, "B", &targets(&["B"])), None);
+        assert_eq!(shortest_path(&pods, "A", &targets(&["F"])), None);
+    }
+
+    #[test]
+    fn all_paths_are_simple() {
+        let pods = pods();
+        let paths = all_paths(&pods, "A", &targets(&["E"]), 10).unwrap();
+        assert_eq!(paths, [
+            vec!["A", "B", "D", "E"],
+            vec!["A", "C", "D", "E"],
+            vec!["A", "E"],
+        ]);
+        // 环上的 pod 不会重复出现
+        let paths = all_paths(&pods, "D", &targets(&["B"]), 10).unwrap();
+        assert_eq!(paths, [vec!["D", "E", "B"]]);
+        assert!(all_paths(&pods, "F", &targets(&["E"]), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn all_paths_respects_max_length() {
+        let pods = pods();
+        let paths = all_paths(&pods, "A", &targets(&["E"]), 2).unwrap();
+        assert_eq!(paths, [vec!["A", "E"]]);
+        let paths = all_paths(&pods, "A", &targets(&["E"]), 3).unwrap();
+        assert_eq!(paths.len(), 3);
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use crate::lib::dep::DepGraph;
+use crate::lib::dep::pattern::{match_names, Pattern};
+use crate::lib::pod::lockfile::{root_name, PodItem};
+
+// 在 Podfile.lock 里挑选 pod，规则见 `Pattern`
+// `collapse` 为 true 时 pods 已经合并了 subspec，精确的名字也换成 root spec；glob 和正则原样匹配合并后的名字
+pub(crate) fn match_pods(
+    pods: &HashMap<String, PodItem>,
+    patterns: &[String],
+    collapse: bool
+) -> anyhow::Result<Vec<String>> {
+    let mut texts = Vec::new();
+    for p in patterns {
+        let is_name = matches!(Pattern::parse(p)?, Pattern::Name(_));
+        texts.push(if collapse && is_name { root_name(p) } else { p.as_str() });
+    }
+    match_names(&pods.names(), &texts, "Podfile.lock")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::{collapse_subspecs, Lockfile, PodItem};
+    use super::match_pods;
+
+    fn pods() -> HashMap<String, PodItem> {
+        Lockfile::from_yaml_str("
+PODS:
+  - AFNetworking (4.0.1):
+    - AFNetworking/NSURLSession (= 4.0.1)
+  - AFNetworking/NSURLSession (4.0.1)
+  - Firebase/Analytics (8.0.0)
+  - FirebaseAnalytics (8.0.0)
+  - FirebaseCore (8.0.0)
+  - Masonry (1.1.0)
+
+COCOAPODS: 1.11.0
+").unwrap().pods().unwrap()
+    }
+
+    fn patterns(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn globs_and_regexes_keep_order_without_duplicates() {
+        let matched = match_pods(&pods(), &patterns(&["Masonry", "Firebase*", "/Core$/"]), false).unwrap();
+        assert_eq!(matched, ["Masonry", "Firebase/Analytics", "FirebaseAnalytics", "FirebaseCore"]);
+        let matched = match_pods(&pods(), &patterns(&["/^AF/", "AFNetworking"]), false).unwrap();
+        assert_eq!(matched, ["AFNetworking", "AFNetworking/NSURLSession"]);
+    }
+
+    #[test]
+    fn collapse_only_rewrites_names() {
+        let pods = collapse_subspecs(&pods());
+        let matched = match_pods(&pods, &patterns(&["AFNetworking/NSURLSession"]), true).unwrap();
+        assert_eq!(matched, ["AFNetworking"]);
+        // glob 里的 `/` 不能被当成 subspec 截断
+        let matched = match_pods(&pods, &patterns(&["Fire*/Analytics", "Firebase*"]), true);
+        assert!(matched.is_err());
+        let matched = match_pods(&pods, &patterns(&["Firebase*"]), true).unwrap();
+        assert_eq!(matched, ["Firebase", "FirebaseAnalytics", "FirebaseCore"]);
+    }
+
+    #[test]
+    fn unknown_name_suggests_similar_pods() {
+        let err = match_pods(&pods(), &patterns(&["Masnory"]), false).unwrap_err().to_string();
+        assert_eq!(err, "Cannot find Masnory in Podfile.lock, did you mean: Masonry?");
+        let err = match_pods(&pods(), &patterns(&["Alamofire"]), false).unwrap_err().to_string();
+        assert_eq!(err, "Cannot find Alamofire in Podfile.lock");
+        let err = match_pods(&pods(), &patterns(&["Alamo*"]), false).unwrap_err().to_string();
+        assert_eq!(err, "Nothing in Podfile.lock matches Alamo*");
+    }
+}
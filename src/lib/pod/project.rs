@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::lib::pod::lockfile::{Dependency, ExternalSource, Lockfile, Name, PodItem};
+use crate::lib::pod::spm;
+
+// CocoaPods 和 SwiftPM 混合的项目
+// Podfile.lock 和 Package.resolved、Package.swift 合并成一张依赖图，dep、rdep、graph 都基于它
+
+pub(crate) struct Project {
+    pub(crate) pods: HashMap<String, PodItem>,
+    // Podfile.lock 的 DEPENDENCIES 加上 SwiftPM 的根依赖
+    pub(crate) dependencies: Vec<Dependency>,
+    pub(crate) external_sources: HashMap<String, ExternalSource>,
+}
+
+impl Project {
+    pub(crate) fn load(
+        lockfile: Option<&Path>,
+        lenient: bool,
+        resolved: Option<&Path>,
+        manifests: &[PathBuf]
+    ) -> anyhow::Result<Self> {
+        let mut project = Project { pods: HashMap::new(), dependencies: Vec::new(), external_sources: HashMap::new() };
+        if let Some(path) = lockfile {
+            let lockfile = Lockfile::from_file(path, lenient)?;
+            project.pods = lockfile.pods()?;
+            project.dependencies = lockfile.dependencies()?;
+            project.external_sources = lockfile.external_sources()?;
+        }
+        if resolved.is_some() || !manifests.is_empty() {
+            let packages = spm::packages(resolved, manifests)?;
+            project.merge(packages);
+        }
+        Ok(project)
+    }
+
+    // 和 pod 重名的包加上 `spm:` 前缀，边上的名字也一并替换
+    fn merge(&mut self, packages: spm::Packages) {
+        let renamed = packages.pods.keys()
+            .filter(|n| self.pods.contains_key(*n))
+            .map(|n| (n.clone(), format!("spm:{}", n)))
+            .collect::<HashMap<String, String>>();
+        let rename = |n: &str| -> String {
+            renamed.get(n).cloned().unwrap_or_else(|| n.to_string())
+        };
+        let rename_edge = |n: &Name| -> Name {
+            match renamed.get(n.as_ref()) {
+                Some(r) => Name::from(r.as_str()),
+                None => n.clone(),
+            }
+        };
+        for (name, mut pod) in packages.pods {
+            pod.name = rename(&name);
+            pod.parents = pod.parents.iter().map(rename_edge).collect();
+            pod.children = pod.children.iter().map(rename_edge).collect();
+            pod.requirements = pod.requirements.into_iter().map(|(k, v)| (rename_edge(&k), v)).collect();
+            self.pods.insert(pod.name.clone(), pod);
+        }
+        for mut dep in packages.roots {
+            dep.name = rename(&dep.name);
+            self.dependencies.push(dep);
+        }
+    }
+}
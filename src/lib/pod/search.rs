@@ -1,2 +1,319 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use ansi_term::{Colour, Style};
+use regex::Regex;
+use crate::lib::pod::lockfile::root_name;
+use crate::lib::util::walker::{Walker, WakerEntry};
 
-// pod search
\ No newline at end of file
+// pod search
+// 在 Pods/<PodName>/ 下搜索文本，结果按 pod 分组
+
+// Pods/ 下不属于某个 pod 的目录
+pub(crate) const NON_POD_DIRS: [&str; 4] = ["Headers", "Local Podspecs", "Target Support Files", "Pods.xcodeproj"];
+
+pub(crate) struct Line {
+    // 行号（从 `1` 开始）
+    pub(crate) number: usize,
+    pub(crate) text: String,
+    // 是匹配行还是上下文行
+    pub(crate) is_match: bool,
+}
+
+pub(crate) struct FileMatches {
+    // 相对于 Pods/ 的路径
+    pub(crate) path: PathBuf,
+    pub(crate) lines: Vec<Line>,
+}
+
+impl FileMatches {
+    pub(crate) fn match_count(&self) -> usize {
+        self.lines.iter().filter(|l| l.is_match).count()
+    }
+}
+
+pub(crate) struct PodMatches {
+    pub(crate) pod: String,
+    pub(crate) files: Vec<FileMatches>,
+}
+
+impl PodMatches {
+    pub(crate) fn match_count(&self) -> usize {
+        self.files.iter().map(|f| f.match_count()).sum()
+    }
+}
+
+pub(crate) struct Searcher {
+    // Pods/ 目录
+    root: PathBuf,
+    pattern: Regex,
+    excludes: HashSet<String>,
+    // 只搜索这些 pod，`None` 表示不限制
+    includes: Option<HashSet<String>>,
+    before_context: usize,
+    after_context: usize,
+}
+
+impl Searcher {
+    pub(crate) fn new<P: AsRef<Path>>(root: P, pattern: Regex) -> Self {
+        Searcher {
+            root: root.as_ref().to_path_buf(),
+            pattern,
+            excludes: HashSet::new(),
+            includes: None,
+            before_context: 0,
+            after_context: 0,
+        }
+    }
+
+    // 名字可以是 subspec，譬如 `AFNetworking/UIKit`，会被归到 `AFNetworking`
+    pub(crate) fn excludes<I, S>(&mut self, names: I) -> &mut Self
+        where I: IntoIterator<Item = S>, S: AsRef<str> {
+        self.excludes = names.into_iter().map(|n| root_name(n.as_ref()).to_string()).collect();
+        self
+    }
+
+    pub(crate) fn includes<I, S>(&mut self, names: Option<I>) -> &mut Self
+        where I: IntoIterator<Item = S>, S: AsRef<str> {
+        self.includes = names.map(|ns| {
+            ns.into_iter().map(|n| root_name(n.as_ref()).to_string()).collect()
+        });
+        self
+    }
+
+    pub(crate) fn context(&mut self, before: usize, after: usize) -> &mut Self {
+        self.before_context = before;
+        self.after_context = after;
+        self
+    }
+
+    // 列出需要搜索的 pod 目录名
+    fn pod_names(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || NON_POD_DIRS.contains(&name.as_str()) {
+                continue
+            }
+            if self.excludes.contains(&name) {
+                continue
+            }
+            if let Some(includes) = &self.includes {
+                if !includes.contains(&name) {
+                    continue
+                }
+            }
+            names.push(name);
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub(crate) fn search(&self) -> anyhow::Result<Vec<PodMatches>> {
+        let mut ret = Vec::new();
+        for pod in self.pod_names()? {
+            let pod_dir = self.root.join(&pod);
+            let files = RefCell::new(Vec::<PathBuf>::new());
+            let handle = |entry: WakerEntry| {
+                let path = entry.path();
+                let hidden = path.strip_prefix(&pod_dir)
+                    .map(|p| p.iter().any(|c| c.to_string_lossy().starts_with('.')))
+                    .unwrap_or(false);
+                let is_file = entry.file_type().map(|ft| ft.is_file()).unwrap_or(false);
+                if is_file && !hidden {
+                    files.borrow_mut().push(path);
+                }
+            };
+            // Pods/ 通常在 .gitignore 里，不能按 .gitignore 过滤
+            let mut walker = Walker::new(&pod_dir);
+            walker.hide_ignore(false);
+            walker.start(&handle)?;
+
+            let mut pod_matches = PodMatches { pod, files: vec![] };
+            for file in files.into_inner() {
+                let path = file.strip_prefix(&self.root).unwrap_or(&file).to_path_buf();
+                // 读不了的文件（权限、断开的链接等）不影响其他文件
+                match self.search_file(&file) {
+                    Ok(Some(lines)) => pod_matches.files.push(FileMatches { path, lines }),
+                    Ok(None) => {},
+                    Err(e) => eprintln!(
+                        "{} {}: {}, skipped",
+                        Style::from(Colour::Yellow).bold().paint("warning:"),
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+            if !pod_matches.files.is_empty() {
+                ret.push(pod_matches);
+            }
+        }
+        Ok(ret)
+    }
+
+    fn search_file(&self, path: &Path) -> io::Result<Option<Vec<Line>>> {
+        let bytes = std::fs::read(path)?;
+        // 跳过二进制文件（.a、framework 等）
+        if bytes.iter().take(8192).any(|b| *b == 0) {
+            return Ok(None)
+        }
+        let content = String::from_utf8_lossy(&bytes);
+        let lines = content.lines().collect::<Vec<&str>>();
+        let mut picked = BTreeSet::new();
+        let mut matched = HashSet::new();
+        for (i, line) in lines.iter().enumerate() {
+            if self.pattern.is_match(line) {
+                matched.insert(i);
+                let from = i.saturating_sub(self.before_context);
+                let to = (i + self.after_context).min(lines.len() - 1);
+                picked.extend(from..=to);
+            }
+        }
+        if matched.is_empty() {
+            return Ok(None)
+        }
+        let ret = picked.into_iter()
+            .map(|i| Line {
+                number: i + 1,
+                text: lines[i].to_string(),
+                is_match: matched.contains(&i),
+            })
+            .collect();
+        Ok(Some(ret))
+    }
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use regex::Regex;
+    use super::PodMatches;
+
+    pub(crate) fn print_matches(matches: &[PodMatches], pattern: &Regex, name_only: bool) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        for (i, pod) in matches.iter().enumerate() {
+            let count = pod.match_count();
+            let summary = format!(
+                " ({} {})",
+                count,
+                if count == 1 { "match" } else { "matches" }
+            );
+            if name_only {
+                println!("{}{}", Style::from(Colour::Green).bold().paint(&pod.pod), Style::from(light_gray).paint(summary));
+                continue
+            }
+            if i > 0 {
+                println!();
+            }
+            println!("{}{}", Style::from(Colour::Green).bold().paint(&pod.pod), Style::from(light_gray).paint(summary));
+            for file in &pod.files {
+                println!("{}", Style::from(Colour::Purple).paint(file.path.to_string_lossy()));
+                let mut last: Option<usize> = None;
+                for line in &file.lines {
+                    if let Some(l) = last {
+                        if line.number > l + 1 {
+                            println!("{}", Style::from(light_gray).paint("--"));
+                        }
+                    }
+                    last = Some(line.number);
+                    let sep = if line.is_match { ':' } else { '-' };
+                    let text =
+                        if line.is_match {
+                            highlight(&line.text, pattern)
+                        } else {
+                            line.text.clone()
+                        };
+                    println!("{}{}{}", Style::from(Colour::Green).paint(line.number.to_string()), sep, text);
+                }
+            }
+        }
+    }
+
+    fn highlight(text: &str, pattern: &Regex) -> String {
+        let mut ret = String::new();
+        let mut last = 0;
+        for m in pattern.find_iter(text) {
+            ret.push_str(&text[last..m.start()]);
+            ret.push_str(&Style::from(Colour::Red).bold().paint(m.as_str()).to_string());
+            last = m.end();
+        }
+        ret.push_str(&text[last..]);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use crate::lib::util::testing::TempDir;
+    use super::Searcher;
+
+    fn pods() -> TempDir {
+        let dir = TempDir::new();
+        dir.write("AFNetworking/AFNetworking/AFURLSessionManager.m", "a\nb\ntimeout\nc\nd\ne\nf\ntimeout\ng\n");
+        dir.write("AFNetworking/UIKit/UIImageView+AFNetworking.m", "timeout = 10\n");
+        dir.write("AFNetworking/.git/config", "timeout\n");
+        dir.write("Masonry/Masonry/View.m", "no match here\n");
+        dir.write("SDWebImage/SDWebImage/SDWebImageDownloader.m", "timeout\n");
+        dir.write("SDWebImage/libwebp.a", b"\0\0timeout\n");
+        dir.write("Headers/Public/AFNetworking/AFNetworking.h", "timeout\n");
+        dir.write("Target Support Files/AFNetworking/AFNetworking.xcconfig", "timeout\n");
+        dir
+    }
+
+    #[test]
+    fn groups_matches_by_pod() {
+        let dir = pods();
+        let result = Searcher::new(dir.path(), Regex::new("timeout").unwrap()).search().unwrap();
+        let names = result.iter().map(|p| p.pod.as_str()).collect::<Vec<&str>>();
+        // Headers/、Target Support Files/、没有匹配的 pod 都不出现
+        assert_eq!(names, ["AFNetworking", "SDWebImage"]);
+        let af = &result[0];
+        assert_eq!(af.match_count(), 3);
+        let paths = af.files.iter().map(|f| f.path.to_string_lossy().to_string()).collect::<Vec<String>>();
+        // 隐藏目录跳过
+        assert_eq!(paths, ["AFNetworking/AFNetworking/AFURLSessionManager.m", "AFNetworking/UIKit/UIImageView+AFNetworking.m"]);
+        // 二进制文件跳过
+        assert_eq!(result[1].files.len(), 1);
+    }
+
+    #[test]
+    fn includes_and_excludes_take_subspecs() {
+        let dir = pods();
+        let pattern = Regex::new("timeout").unwrap();
+        let mut searcher = Searcher::new(dir.path(), pattern.clone());
+        searcher.includes(Some(["AFNetworking/UIKit", "SDWebImage"]));
+        searcher.excludes(["SDWebImage/Core"]);
+        let result = searcher.search().unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].pod, "AFNetworking");
+    }
+
+    #[test]
+    fn context_lines_are_merged() {
+        let dir = pods();
+        let mut searcher = Searcher::new(dir.path(), Regex::new("timeout").unwrap());
+        searcher.includes(Some(["AFNetworking"])).context(1, 2);
+        let result = searcher.search().unwrap();
+        let lines = &result[0].files[0].lines;
+        let numbers = lines.iter().map(|l| l.number).collect::<Vec<usize>>();
+        assert_eq!(numbers, [2, 3, 4, 5, 7, 8, 9]);
+        let matched = lines.iter().filter(|l| l.is_match).map(|l| l.number).collect::<Vec<usize>>();
+        assert_eq!(matched, [3, 8]);
+        assert_eq!(lines[1].text, "timeout");
+    }
+
+    #[test]
+    fn context_stops_at_file_edges() {
+        let dir = pods();
+        let mut searcher = Searcher::new(dir.path(), Regex::new("timeout").unwrap());
+        searcher.includes(Some(["SDWebImage"])).context(3, 3);
+        let result = searcher.search().unwrap();
+        let numbers = result[0].files[0].lines.iter().map(|l| l.number).collect::<Vec<usize>>();
+        assert_eq!(numbers, [1]);
+    }
+}
@@ -0,0 +1,279 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use serde_json::{json, Value};
+use crate::lib::pod::lockfile::{collapse_subspecs, root_name, Lockfile, PodItem};
+use crate::lib::util::walker::{Walker, WakerEntry};
+
+// pod size
+// 统计 Pods/<name> 的磁盘占用，subspec 和 root spec 共用一个目录，所以按 root spec 统计
+// - own：Pods/<name> 自身的大小
+// - transitive：依赖闭包的大小，共享的依赖只算一次
+// - exclusive：只去掉这个 pod 时能省下的大小，即它自身加上只有经过它才会被引入的依赖
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortKey { Own, Transitive, Exclusive }
+
+impl SortKey {
+    pub(crate) const NAMES: [&'static str; 3] = ["transitive", "exclusive", "own"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "own" => Some(SortKey::Own),
+            "transitive" => Some(SortKey::Transitive),
+            "exclusive" => Some(SortKey::Exclusive),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct PodSize {
+    pub(crate) name: String,
+    pub(crate) vers: Option<String>,
+    // Pods/ 下没有目录时为 `None`，譬如 `:path` 引入的开发 pod
+    pub(crate) own: Option<u64>,
+    pub(crate) transitive: u64,
+    pub(crate) exclusive: u64,
+    // 依赖闭包里的 pod 数，不包含自身
+    pub(crate) dep_count: usize,
+}
+
+pub(crate) struct Sizes {
+    pub(crate) pods: Vec<PodSize>,
+    // 所有 pod 目录的大小之和
+    pub(crate) total: u64,
+}
+
+pub(crate) fn sizes(lockfile: &Lockfile, pods_dir: &Path) -> anyhow::Result<Sizes> {
+    let pods = collapse_subspecs(&lockfile.pods()?);
+    let mut own = HashMap::new();
+    for name in pods.keys() {
+        let dir = pods_dir.join(name);
+        if dir.is_dir() {
+            own.insert(name.as_str(), dir_size(&dir)?);
+        }
+    }
+    let size_of = |names: &HashSet<&str>| -> u64 {
+        names.iter().filter_map(|n| own.get(n)).sum()
+    };
+
+    // 从 DEPENDENCIES 出发，没有 DEPENDENCIES 时从没有被依赖的 pod 出发
+    let mut roots = lockfile.dependencies()?
+        .iter()
+        .map(|d| root_name(&d.name).to_string())
+        .filter(|n| pods.contains_key(n))
+        .collect::<HashSet<String>>();
+    if roots.is_empty() {
+        roots = pods.values()
+            .filter(|p| p.parents.is_empty())
+            .map(|p| p.name.clone())
+            .collect();
+    }
+    let roots = roots.iter().map(|n| n.as_str()).collect::<Vec<&str>>();
+
+    let mut ret = Vec::new();
+    for pod in pods.values() {
+        let closure = reach(&pods, &[pod.name.as_str()], None);
+        // 去掉这个 pod 后仍然能被引入的 pod
+        let kept = reach(&pods, &roots, Some(pod.name.as_str()));
+        let freed = closure.iter()
+            .filter(|n| !kept.contains(*n))
+            .copied()
+            .collect::<HashSet<&str>>();
+        ret.push(PodSize {
+            name: pod.name.clone(),
+            vers: pod.vers.clone(),
+            own: own.get(pod.name.as_str()).copied(),
+            transitive: size_of(&closure),
+            exclusive: size_of(&freed),
+            dep_count: closure.len() - 1,
+        });
+    }
+    let total = own.values().sum();
+    Ok(Sizes { pods: ret, total })
+}
+
+// `starts` 能到达的所有 pod（包含自身），不经过 `skip`
+fn reach<'a>(
+    pods: &'a HashMap<String, PodItem>,
+    starts: &[&'a str],
+    skip: Option<&str>
+) -> HashSet<&'a str> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    for s in starts {
+        if Some(*s) != skip && visited.insert(*s) {
+            queue.push_back(*s);
+        }
+    }
+    while let Some(name) = queue.pop_front() {
+        for c in pods.get(name).map(|p| &p.children).into_iter().flatten() {
+            let c = c.as_ref();
+            if Some(c) != skip && pods.contains_key(c) && visited.insert(c) {
+                queue.push_back(c);
+            }
+        }
+    }
+    visited
+}
+
+// 目录下所有文件的大小，不跟随 symbolic link
+fn dir_size(dir: &Path) -> anyhow::Result<u64> {
+    let size = Cell::new(0u64);
+    let handle = |entry: WakerEntry| {
+        if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
+            size.set(size.get() + entry.size().unwrap_or(0));
+        }
+    };
+    let mut walker = Walker::new(dir);
+    walker.hide_ignore(false);
+    walker.start(&handle)?;
+    Ok(size.get())
+}
+
+pub(crate) fn sort(sizes: &mut Sizes, key: SortKey) {
+    let value = |p: &PodSize| match key {
+        SortKey::Own => p.own.unwrap_or(0),
+        SortKey::Transitive => p.transitive,
+        SortKey::Exclusive => p.exclusive,
+    };
+    sizes.pods.sort_by(|a, b| value(b).cmp(&value(a)).then(a.name.cmp(&b.name)));
+}
+
+pub(crate) fn to_json(sizes: &Sizes) -> Value {
+    let pods = sizes.pods.iter()
+        .map(|p| json!({
+            "name": p.name,
+            "version": p.vers,
+            "own": p.own,
+            "transitive": p.transitive,
+            "exclusive": p.exclusive,
+            "dependencies": p.dep_count,
+        }))
+        .collect::<Vec<Value>>();
+    json!({
+        "total": sizes.total,
+        "pods": pods,
+    })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use bytesize::ByteSize;
+    use super::Sizes;
+
+    pub(crate) fn print_sizes(sizes: &Sizes, top: usize) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        println!(
+            "{} {}",
+            Style::from(Colour::White).bold().paint("Pods/"),
+            Style::from(light_gray).paint(format!("({} pods, {})", sizes.pods.len(), ByteSize(sizes.total)))
+        );
+        let shown = sizes.pods.iter().take(top).collect::<Vec<_>>();
+        let width = shown.iter().map(|p| p.name.len()).max().unwrap_or(0).max(4);
+        println!(
+            "    {}",
+            Style::from(light_gray).paint(format!(
+                "{:width$}  {:>10}  {:>10}  {:>10}",
+                "name", "own", "transitive", "exclusive", width = width
+            ))
+        );
+        for p in shown {
+            let own = p.own.map(|s| ByteSize(s).to_string()).unwrap_or_else(|| "-".to_string());
+            let deps = if p.dep_count == 0 {
+                String::new()
+            } else {
+                format!("  ({} {})", p.dep_count, if p.dep_count == 1 { "dependency" } else { "dependencies" })
+            };
+            println!(
+                "    {}  {:>10}  {:>10}  {:>10}{}",
+                Style::from(Colour::Yellow).paint(format!("{:width$}", p.name, width = width)),
+                own,
+                ByteSize(p.transitive).to_string(),
+                ByteSize(p.exclusive).to_string(),
+                Style::from(light_gray).paint(deps)
+            );
+        }
+        if sizes.pods.len() > top {
+            println!("    {}", Style::from(light_gray).paint(format!("... {} more", sizes.pods.len() - top)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::pod::lockfile::Lockfile;
+    use crate::lib::util::testing::TempDir;
+    use super::{sizes, sort, SortKey, Sizes};
+
+    // A、B 共用 C，D 和 E 只经过 A 引入，Dev 是没有目录的开发 pod
+    const LOCKFILE: &str = "
+PODS:
+  - A/Core (1.0.0):
+    - C
+    - D
+  - B (1.0.0):
+    - C
+  - C (1.0.0)
+  - D (1.0.0):
+    - E
+  - Dev (0.1.0):
+    - E
+  - E (1.0.0)
+
+DEPENDENCIES:
+  - A/Core
+  - B
+  - Dev (from `./Dev`)
+
+EXTERNAL SOURCES:
+  Dev:
+    :path: \"./Dev\"
+
+COCOAPODS: 1.11.0
+";
+
+    fn pods_dir() -> TempDir {
+        let tmp = TempDir::new();
+        for (name, size) in [("A", 100), ("B", 10), ("C", 1000), ("D", 50), ("E", 5)] {
+            tmp.write(format!("{}/{}.swift", name, name), "x".repeat(size));
+        }
+        // 隐藏文件也占空间
+        tmp.write("A/.hidden", "x".repeat(20));
+        tmp
+    }
+
+    fn rows(sizes: &Sizes) -> Vec<String> {
+        sizes.pods.iter()
+            .map(|p| format!("{} {:?} {} {} {}", p.name, p.own, p.transitive, p.exclusive, p.dep_count))
+            .collect()
+    }
+
+    #[test]
+    fn own_transitive_and_exclusive() {
+        let tmp = pods_dir();
+        let mut result = sizes(&Lockfile::from_yaml_str(LOCKFILE).unwrap(), tmp.path()).unwrap();
+        assert_eq!(result.total, 1185);
+        sort(&mut result, SortKey::Transitive);
+        assert_eq!(rows(&result), [
+            // 共享的 C 不算在 A、B 的 exclusive 里；E 还会被 Dev 引入，也不算在 A、D 里
+            "A Some(120) 1175 170 3",
+            "B Some(10) 1010 10 1",
+            "C Some(1000) 1000 1000 0",
+            "D Some(50) 55 50 1",
+            "Dev None 5 0 1",
+            "E Some(5) 5 5 0",
+        ]);
+    }
+
+    #[test]
+    fn sort_keys() {
+        let tmp = pods_dir();
+        let mut result = sizes(&Lockfile::from_yaml_str(LOCKFILE).unwrap(), tmp.path()).unwrap();
+        let names = |sizes: &Sizes| sizes.pods.iter().map(|p| p.name.clone()).collect::<Vec<String>>();
+        sort(&mut result, SortKey::Exclusive);
+        assert_eq!(names(&result), ["C", "A", "D", "B", "E", "Dev"]);
+        sort(&mut result, SortKey::Own);
+        assert_eq!(names(&result), ["C", "A", "D", "B", "E", "Dev"]);
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use crate::lib::dep::Ecosystem;
+use crate::lib::pod::lockfile::{Dependency, Interner, PodItem};
+use crate::lib::pod::version::Requirement;
+
+// Swift Package Manager
+// - Package.resolved：解析出的版本，支持 v1、v2、v3 三种格式，但不包含包之间的依赖
+// - Package.swift：只在文本上提取 `.package(...)`，不执行 manifest，用于补上本地包的依赖边
+// 包按 SwiftPM 的 identity 命名，即 url 或路径的最后一段，小写并去掉 `.git`
+
+pub(crate) const MANIFEST: &str = "Package.swift";
+
+// Package.resolved 里的一项
+pub(crate) struct Pin {
+    pub(crate) identity: String,
+    pub(crate) version: Option<String>,
+    pub(crate) branch: Option<String>,
+    pub(crate) revision: Option<String>,
+}
+
+impl Pin {
+    // 没有 version 时用 branch，再没有就用 revision 的前 7 位
+    pub(crate) fn display_version(&self) -> Option<String> {
+        self.version.clone()
+            .or_else(|| self.branch.clone())
+            .or_else(|| self.revision.as_ref().map(|r| r.chars().take(7).collect()))
+    }
+}
+
+// Package.swift 里的一个 `.package(...)`
+pub(crate) struct PackageDep {
+    pub(crate) identity: String,
+    // url、路径或者 registry 的 id，原样保留
+    pub(crate) location: String,
+    // 本地包的目录，已经相对 manifest 所在目录展开
+    pub(crate) path: Option<PathBuf>,
+    // `from:`、`exact:` 和 `"1.0.0"..<"2.0.0"` 换算成 CocoaPods 的写法，branch 和 revision 为 `None`
+    pub(crate) requirement: Option<Requirement>,
+}
+
+pub(crate) struct Manifest {
+    pub(crate) identity: String,
+    pub(crate) dependencies: Vec<PackageDep>,
+}
+
+pub(crate) fn read_resolved(path: &Path) -> anyhow::Result<Vec<Pin>> {
+    let text = std::fs::read_to_string(path)?;
+    let value = serde_json::from_str::<Value>(&text)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    parse_resolved(&value).map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+// v1 的 pins 在 `object` 下，用 `repositoryURL`；v2、v3 直接给出 `identity` 和 `location`
+fn parse_resolved(value: &Value) -> anyhow::Result<Vec<Pin>> {
+    let (pins, v1) = match value["version"].as_u64() {
+        Some(1) => (&value["object"]["pins"], true),
+        Some(2) | Some(3) => (&value["pins"], false),
+        Some(v) => bail!("unsupported Package.resolved version {}", v),
+        None => bail!("missing version"),
+    };
+    let pins = pins.as_array().ok_or_else(|| anyhow!("missing pins"))?;
+    let field = |v: &Value, key: &str| v[key].as_str().map(|s| s.to_string());
+    let mut ret = Vec::new();
+    for (i, pin) in pins.iter().enumerate() {
+        let location = field(pin, if v1 { "repositoryURL" } else { "location" })
+            .ok_or_else(|| anyhow!("missing location of pin {}", i + 1))?;
+        let identity = if v1 { None } else { field(pin, "identity") }
+            .unwrap_or_else(|| identity_of(&location));
+        let state = &pin["state"];
+        ret.push(Pin {
+            identity,
+            version: field(state, "version"),
+            branch: field(state, "branch"),
+            revision: field(state, "revision"),
+        });
+    }
+    Ok(ret)
+}
+
+// `https://github.com/Alamofire/Alamofire.git` -> `alamofire`
+pub(crate) fn identity_of(location: &str) -> String {
+    let location = location.trim_end_matches('/');
+    let last = location.rsplit(['/', ':']).next().unwrap_or(location);
+    last.strip_suffix(".git").unwrap_or(last).to_lowercase()
+}
+
+// `path` 可以是 Package.swift，也可以是它所在的目录
+pub(crate) fn read_manifest(path: &Path) -> anyhow::Result<Manifest> {
+    let path = if path.is_dir() { path.join(MANIFEST) } else { path.to_path_buf() };
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    let dir = path.parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let identity = dir.file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    Ok(Manifest { identity, dependencies: parse_manifest(&text, &dir) })
+}
+
+static PACKAGE_CALL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.package\s*\(").unwrap());
+static LABELED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\w+)\s*:\s*"([^"]*)""#).unwrap());
+static RANGE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""([^"]+)"\s*(\.\.<|\.\.\.)\s*"([^"]+)""#).unwrap());
+static EXACT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\.exact\s*\(\s*"([^"]+)""#).unwrap());
+
+fn parse_manifest(text: &str, dir: &Path) -> Vec<PackageDep> {
+    let text = strip_comments(text);
+    let mut ret = Vec::new();
+    for m in PACKAGE_CALL_RE.find_iter(&text) {
+        let args = match balanced(&text[m.end()..]) {
+            Some(a) => a,
+            None => continue,
+        };
+        let mut labels = HashMap::new();
+        for c in LABELED_RE.captures_iter(args) {
+            labels.entry(c.get(1).unwrap().as_str()).or_insert(c.get(2).unwrap().as_str());
+        }
+        let (identity, location, path) =
+            if let Some(url) = labels.get("url") {
+                (identity_of(url), url.to_string(), None)
+            } else if let Some(p) = labels.get("path") {
+                let path = dir.join(p);
+                let path = std::fs::canonicalize(&path).unwrap_or(path);
+                let identity = path.file_name()
+                    .map(|n| n.to_string_lossy().to_lowercase())
+                    .unwrap_or_else(|| identity_of(p));
+                (identity, p.to_string(), Some(path))
+            } else if let Some(id) = labels.get("id") {
+                (id.to_lowercase(), id.to_string(), None)
+            } else {
+                continue
+            };
+        ret.push(PackageDep { identity, location, path, requirement: requirement(args, &labels) });
+    }
+    ret
+}
+
+fn requirement(args: &str, labels: &HashMap<&str, &str>) -> Option<Requirement> {
+    let text =
+        if let Some(c) = RANGE_RE.captures(args) {
+            let op = if &c[2] == "..<" { "<" } else { "<=" };
+            format!(">= {}, {} {}", &c[1], op, &c[3])
+        } else if let Some(v) = labels.get("exact").copied().or_else(|| EXACT_RE.captures(args).map(|c| c.get(1).unwrap().as_str())) {
+            format!("= {}", v)
+        } else if let Some(v) = labels.get("from") {
+            // `from:` 和 `.upToNextMajor(from:)` 到下一个主版本，`.upToNextMinor(from:)` 到下一个次版本
+            format!(">= {}, < {}", v, next_version(v, args.contains("upToNextMinor"))?)
+        } else {
+            return None
+        };
+    Requirement::parse(&text)
+}
+
+fn next_version(v: &str, minor: bool) -> Option<String> {
+    let mut nums = v.split(['.', '-', '+']).map(|n| n.parse::<u64>().ok());
+    let major = nums.next()??;
+    let next = if minor {
+        format!("{}.{}.0", major, nums.next().flatten().unwrap_or(0) + 1)
+    } else {
+        format!("{}.0.0", major + 1)
+    };
+    Some(next)
+}
+
+// 去掉 `//` 和 `/* */` 注释，字符串里的内容原样保留
+fn strip_comments(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            ret.push(c);
+            match c {
+                '\\' => ret.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {},
+            }
+            continue
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.peek().map(|c| *c != '\n').unwrap_or(false) {
+                    chars.next();
+                }
+            },
+            ('/', Some('*')) => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break
+                    }
+                    last = c;
+                }
+                ret.push(' ');
+            },
+            _ => {
+                in_string = c == '"';
+                ret.push(c);
+            },
+        }
+    }
+    ret
+}
+
+// `text` 紧跟在 `(` 之后，返回到配对的 `)` 之前的内容
+fn balanced(text: &str) -> Option<&str> {
+    let mut depth = 1;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {},
+            }
+            continue
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[..i])
+                }
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+// 合并到依赖图里的 SwiftPM 包
+pub(crate) struct Packages {
+    pub(crate) pods: HashMap<String, PodItem>,
+    // 相当于 Podfile.lock 的 DEPENDENCIES：根 manifest 声明的依赖，以及直接指定的本地包
+    pub(crate) roots: Vec<Dependency>,
+}
+
+// `resolved` 提供版本，manifest 提供边
+// `resolved` 同目录下的 Package.swift 为根 manifest，即项目本身，不作为节点
+// `manifests` 为本地包，`.package(path:)` 引入的本地包也会继续读取
+pub(crate) fn packages(resolved: Option<&Path>, manifests: &[PathBuf]) -> anyhow::Result<Packages> {
+    let mut pods = HashMap::<String, PodItem>::new();
+    let mut roots = Vec::new();
+    if let Some(path) = resolved {
+        for pin in read_resolved(path)? {
+            let vers = pin.display_version();
+            pods.insert(pin.identity.clone(), item(&pin.identity, vers));
+        }
+    }
+
+    // (manifest, 是否为根 manifest)
+    let mut queue = Vec::<(PathBuf, bool)>::new();
+    let root = resolved
+        .and_then(|p| p.parent())
+        .map(|d| d.join(MANIFEST))
+        .filter(|p| p.is_file());
+    queue.extend(root.map(|p| (p, true)));
+    queue.extend(manifests.iter().rev().map(|p| (p.clone(), false)));
+    let mut visited = HashSet::new();
+    let mut names = Interner::default();
+    while let Some((path, is_root)) = queue.pop() {
+        let key = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if !visited.insert(key) {
+            continue
+        }
+        let manifest = read_manifest(&path)?;
+        for d in &manifest.dependencies {
+            if let Some(p) = d.path.as_ref().map(|p| p.join(MANIFEST)).filter(|p| p.is_file()) {
+                queue.push((p, false));
+            }
+        }
+        if is_root {
+            roots.extend(manifest.dependencies.iter().map(|d| Dependency {
+                name: d.identity.clone(),
+                requirement: d.requirement.clone(),
+                source: Some(d.location.clone()),
+            }));
+            continue
+        }
+        if manifests.contains(&path) {
+            roots.push(Dependency {
+                name: manifest.identity.clone(),
+                requirement: None,
+                source: Some(path.display().to_string()),
+            });
+        }
+        let parent = names.intern(&manifest.identity);
+        pods.entry(manifest.identity.clone()).or_insert_with(|| item(&manifest.identity, None));
+        for d in manifest.dependencies {
+            if d.identity == manifest.identity {
+                continue
+            }
+            let child = names.intern(&d.identity);
+            pods.entry(d.identity.clone())
+                .or_insert_with(|| item(&d.identity, None))
+                .parents
+                .insert(parent.clone());
+            let pod = pods.get_mut(&manifest.identity).unwrap();
+            pod.children.insert(child.clone());
+            if let Some(r) = d.requirement {
+                pod.requirements.insert(child, r);
+            }
+        }
+    }
+    Ok(Packages { pods, roots })
+}
+
+fn item(name: &str, vers: Option<String>) -> PodItem {
+    let mut item = PodItem::new(name.to_string(), vers);
+    item.ecosystem = Ecosystem::SwiftPM;
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use serde_json::json;
+    use crate::lib::util::testing::TempDir;
+    use super::{packages, parse_manifest, parse_resolved, Pin};
+
+    fn describe(pins: &[Pin]) -> Vec<String> {
+        pins.iter()
+            .map(|p| format!("{} {}", p.identity, p.display_version().unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn resolved_v1() {
+        let value = json!({
+            "object": {
+                "pins": [
+                    {
+                        "package": "Alamofire",
+                        "repositoryURL": "https://github.com/Alamofire/Alamofire.git",
+                        "state": { "branch": null, "revision": "f96b619bcb2383b43d898402283924b80e2c4bae", "version": "5.4.4" }
+                    },
+                    {
+                        "package": "SnapKit",
+                        "repositoryURL": "git@github.com:SnapKit/SnapKit",
+                        "state": { "branch": "develop", "revision": "8f5e6b4ee5c2b2d1d9f1e3a4b5c6d7e8f9a0b1c2", "version": null }
+                    }
+                ]
+            },
+            "version": 1
+        });
+        let pins = parse_resolved(&value).unwrap();
+        assert_eq!(describe(&pins), ["alamofire 5.4.4", "snapkit develop"]);
+    }
+
+    #[test]
+    fn resolved_v2_and_v3() {
+        let pins = json!([
+            {
+                "identity": "swift-collections",
+                "kind": "remoteSourceControl",
+                "location": "https://github.com/apple/swift-collections.git",
+                "state": { "revision": "937e904258d22af6e447a0b72c0bc67583ef64a2", "version": "1.0.4" }
+            },
+            {
+                "identity": "kingfisher",
+                "kind": "remoteSourceControl",
+                "location": "https://github.com/onevcat/Kingfisher.git",
+                "state": { "revision": "3ec0ab0bca4feb56e8b33e289c9496e89059dd08" }
+            }
+        ]);
+        let v2 = json!({ "pins": pins, "version": 2 });
+        assert_eq!(describe(&parse_resolved(&v2).unwrap()), ["swift-collections 1.0.4", "kingfisher 3ec0ab0"]);
+        let v3 = json!({ "originHash": "abc", "pins": pins, "version": 3 });
+        assert_eq!(describe(&parse_resolved(&v3).unwrap()), ["swift-collections 1.0.4", "kingfisher 3ec0ab0"]);
+
+        assert!(parse_resolved(&json!({ "pins": [], "version": 4 })).is_err());
+        assert!(parse_resolved(&json!({ "pins": [{ "identity": "x" }], "version": 2 })).is_err());
+    }
+
+    #[test]
+    fn manifest_dependencies() {
+        let text = r#"
+// swift-tools-version:5.7
+import PackageDescription
+
+let package = Package(
+    name: "App",
+    dependencies: [
+        .package(url: "https://github.com/Alamofire/Alamofire.git", from: "5.4.0"),
+        .package(url: "https://github.com/SnapKit/SnapKit.git", .upToNextMinor(from: "5.6.0")),
+        .package(url: "https://github.com/onevcat/Kingfisher.git", exact: "7.0.0"),
+        .package(url: "https://github.com/apple/swift-collections", "1.0.0"..<"1.1.0"),
+        .package(url: "https://github.com/realm/SwiftLint.git", branch: "main"),
+        // .package(url: "https://github.com/commented/Out.git", from: "1.0.0"),
+        /* .package(url: "https://github.com/blocked/Out.git", from: "1.0.0"), */
+        .package(id: "mona.LinkedList", .exact("1.2.0")),
+        .package(path: "../Modules/Core"),
+    ]
+)
+"#;
+        let deps = parse_manifest(text, Path::new("/tmp/App"));
+        let describe = deps.iter()
+            .map(|d| format!("{} {}", d.identity, d.requirement.as_ref().map(|r| r.to_string()).unwrap_or_default()))
+            .collect::<Vec<String>>();
+        assert_eq!(describe, [
+            "alamofire >= 5.4.0, < 6.0.0",
+            "snapkit >= 5.6.0, < 5.7.0",
+            "kingfisher = 7.0.0",
+            "swift-collections >= 1.0.0, < 1.1.0",
+            "swiftlint ",
+            "mona.linkedlist = 1.2.0",
+            "core ",
+        ]);
+        assert_eq!(deps[5].location, "mona.LinkedList");
+        assert_eq!(deps[6].location, "../Modules/Core");
+        assert_eq!(deps[6].path.as_deref(), Some(Path::new("/tmp/App/../Modules/Core")));
+    }
+
+    #[test]
+    fn packages_from_resolved_and_manifests() {
+        let tmp = TempDir::new();
+        let resolved = tmp.write("App/Package.resolved", json!({
+            "pins": [
+                {
+                    "identity": "alamofire",
+                    "location": "https://github.com/Alamofire/Alamofire.git",
+                    "state": { "version": "5.4.4" }
+                }
+            ],
+            "version": 2
+        }).to_string());
+        // 根 manifest 引入本地包 Core，Core 依赖 Alamofire
+        tmp.write("App/Package.swift", r#"
+let package = Package(dependencies: [.package(path: "../Core")])
+"#);
+        tmp.write("Core/Package.swift", r#"
+let package = Package(dependencies: [
+    .package(url: "https://github.com/Alamofire/Alamofire.git", from: "5.0.0"),
+])
+"#);
+        let packages = packages(Some(&resolved), &[]).unwrap();
+        let roots = packages.roots.iter().map(|d| d.name.as_str()).collect::<Vec<&str>>();
+        assert_eq!(roots, ["core"]);
+        let mut names = packages.pods.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+        assert_eq!(names, ["alamofire", "core"]);
+        let core = &packages.pods["core"];
+        assert_eq!(core.children.iter().map(|c| c.to_string()).collect::<Vec<String>>(), ["alamofire"]);
+        assert_eq!(core.requirements["alamofire"].to_string(), ">= 5.0.0, < 6.0.0");
+        let alamofire = &packages.pods["alamofire"];
+        assert_eq!(alamofire.vers.as_deref(), Some("5.4.4"));
+        assert_eq!(alamofire.parents.iter().map(|c| c.to_string()).collect::<Vec<String>>(), ["core"]);
+    }
+}
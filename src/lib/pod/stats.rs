@@ -0,0 +1,455 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use serde_json::{json, Value};
+use crate::lib::pod::lockfile::PodItem;
+
+// pod stats
+// 依赖图的健康度指标：环（强连通分量）、fan-in/fan-out、叶子节点、最长依赖链
+
+pub(crate) struct Metric {
+    pub(crate) name: String,
+    pub(crate) direct_in: usize,
+    pub(crate) direct_out: usize,
+    pub(crate) transitive_in: usize,
+    pub(crate) transitive_out: usize,
+}
+
+pub(crate) struct Cycle {
+    // 强连通分量里的所有 pod
+    pub(crate) members: Vec<String>,
+    // 分量里经过最小 pod 的一个环，首尾相同
+    pub(crate) path: Vec<String>,
+}
+
+pub(crate) struct Stats {
+    pub(crate) pod_count: usize,
+    pub(crate) edge_count: usize,
+    // 包含不止一个 pod 的强连通分量，即依赖环
+    pub(crate) cycles: Vec<Cycle>,
+    pub(crate) metrics: Vec<Metric>,
+    // 不依赖任何 pod
+    pub(crate) leaves: Vec<String>,
+    // 没有被任何 pod 依赖
+    pub(crate) roots: Vec<String>,
+    pub(crate) longest_chain: Vec<String>,
+}
+
+pub(crate) fn stats(pods: &HashMap<String, PodItem>) -> Stats {
+    let mut names = pods.keys().map(|n| n.as_str()).collect::<Vec<&str>>();
+    names.sort();
+
+    let sccs = Tarjan::new(pods).run(&names);
+    let cycles = sccs.iter()
+        .filter(|c| c.len() > 1)
+        .map(|c| {
+            let mut members = c.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+            members.sort();
+            let path = cycle_path(pods, c);
+            Cycle { members, path }
+        })
+        .collect();
+
+    let reach = |start: &str, reverse: bool| -> usize {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(name) = queue.pop_front() {
+            let pod = &pods[name];
+            for n in if reverse { &pod.parents } else { &pod.children } {
+                let n = n.as_ref();
+                if n != start && pods.contains_key(n) && visited.insert(n) {
+                    queue.push_back(n);
+                }
+            }
+        }
+        visited.len()
+    };
+    let metrics = names.iter()
+        .map(|n| {
+            let pod = &pods[*n];
+            Metric {
+                name: n.to_string(),
+                direct_in: pod.parents.len(),
+                direct_out: pod.children.len(),
+                transitive_in: reach(n, true),
+                transitive_out: reach(n, false),
+            }
+        })
+        .collect();
+
+    let leaves = names.iter()
+        .filter(|n| pods[**n].children.is_empty())
+        .map(|n| n.to_string())
+        .collect();
+    let roots = names.iter()
+        .filter(|n| pods[**n].parents.is_empty())
+        .map(|n| n.to_string())
+        .collect();
+    let edge_count = pods.values().map(|p| p.children.len()).sum();
+
+    Stats {
+        pod_count: pods.len(),
+        edge_count,
+        cycles,
+        metrics,
+        leaves,
+        roots,
+        longest_chain: longest_chain(pods, &sccs),
+    }
+}
+
+// 在分量内 BFS，找一条从最小 pod 出发又回到它的路径
+fn cycle_path(pods: &HashMap<String, PodItem>, component: &[&str]) -> Vec<String> {
+    let members = component.iter().cloned().collect::<HashSet<&str>>();
+    let start = *component.iter().min().unwrap();
+    let mut prev = HashMap::<&str, &str>::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(name) = queue.pop_front() {
+        let mut children = pods[name].children.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
+        children.sort();
+        for c in children {
+            if c == start {
+                let mut path = vec![start.to_string()];
+                let mut current = name;
+                while current != start {
+                    path.push(current.to_string());
+                    current = prev[current];
+                }
+                path.push(start.to_string());
+                path.reverse();
+                return path
+            }
+            if members.contains(c) && !prev.contains_key(c) {
+                prev.insert(c, name);
+                queue.push_back(c);
+            }
+        }
+    }
+    vec![]
+}
+
+// 在强连通分量缩点后的 DAG 上求最长路径，经过环时沿着环内的边从入口走到出口
+fn longest_chain(pods: &HashMap<String, PodItem>, sccs: &[Vec<&str>]) -> Vec<String> {
+    let mut component = HashMap::new();
+    for (i, c) in sccs.iter().enumerate() {
+        for n in c {
+            component.insert(*n, i);
+        }
+    }
+    // Tarjan 输出的分量是逆拓扑序：被依赖的分量先输出，所以按顺序 DP 即可
+    // best[i]: 从分量 i 出发的最长链长度，以及离开分量 i 的那条边
+    let mut best: Vec<(usize, Option<(&str, &str)>)> = vec![(1, None); sccs.len()];
+    for (i, c) in sccs.iter().enumerate() {
+        let mut c = c.clone();
+        c.sort();
+        for n in c {
+            let mut children = pods[n].children.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
+            children.sort();
+            for child in children {
+                let j = match component.get(child) {
+                    Some(j) if *j != i => *j,
+                    _ => continue,
+                };
+                if best[j].0 + 1 > best[i].0 {
+                    best[i] = (best[j].0 + 1, Some((n, child)));
+                }
+            }
+        }
+    }
+    let start = (0..sccs.len())
+        .max_by(|a, b| best[*a].0.cmp(&best[*b].0).then(b.cmp(a)));
+    let mut chain = Vec::new();
+    let mut current = start.map(|i| best[i].1.map(|(from, _)| from).unwrap_or(sccs[i][0]));
+    while let Some(name) = current {
+        chain.push(name.to_string());
+        current = match best[component[name]].1 {
+            // 环内沿着真实的边绕到出口再离开
+            Some((from, child)) if from != name => {
+                let members = sccs[component[name]].iter().cloned().collect::<HashSet<&str>>();
+                chain.extend(path_within(pods, &members, name, from));
+                Some(child)
+            },
+            Some((_, child)) => Some(child),
+            None => None,
+        };
+    }
+    chain
+}
+
+// 分量内从 `from` 到 `to` 的最短路径，不含 `from`
+fn path_within<'a>(
+    pods: &'a HashMap<String, PodItem>,
+    members: &HashSet<&str>,
+    from: &'a str,
+    to: &'a str
+) -> Vec<String> {
+    let mut prev = HashMap::<&str, &str>::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(name) = queue.pop_front() {
+        if name == to {
+            break
+        }
+        let mut children = pods[name].children.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
+        children.sort();
+        for c in children {
+            if c != from && members.contains(c) && !prev.contains_key(c) {
+                prev.insert(c, name);
+                queue.push_back(c);
+            }
+        }
+    }
+    let mut path = Vec::new();
+    let mut current = to;
+    while current != from {
+        path.push(current.to_string());
+        current = match prev.get(current) {
+            Some(p) => p,
+            // 同一个强连通分量内一定可达
+            None => break,
+        };
+    }
+    path.reverse();
+    path
+}
+
+struct Tarjan<'a> {
+    pods: &'a HashMap<String, PodItem>,
+    index: usize,
+    indexes: HashMap<&'a str, usize>,
+    lowlinks: HashMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: HashSet<&'a str>,
+    result: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(pods: &'a HashMap<String, PodItem>) -> Self {
+        Tarjan {
+            pods,
+            index: 0,
+            indexes: HashMap::new(),
+            lowlinks: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            result: Vec::new(),
+        }
+    }
+
+    fn run(mut self, names: &[&'a str]) -> Vec<Vec<&'a str>> {
+        for n in names {
+            if !self.indexes.contains_key(n) {
+                self.connect(n);
+            }
+        }
+        self.result
+    }
+
+    fn connect(&mut self, v: &'a str) {
+        self.indexes.insert(v, self.index);
+        self.lowlinks.insert(v, self.index);
+        self.index += 1;
+        self.stack.push(v);
+        self.on_stack.insert(v);
+
+        let pods = self.pods;
+        let mut children = pods[v].children.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
+        children.sort();
+        for w in children {
+            if !pods.contains_key(w) {
+                continue
+            }
+            if !self.indexes.contains_key(w) {
+                self.connect(w);
+                let low = self.lowlinks[v].min(self.lowlinks[w]);
+                self.lowlinks.insert(v, low);
+            } else if self.on_stack.contains(w) {
+                let low = self.lowlinks[v].min(self.indexes[w]);
+                self.lowlinks.insert(v, low);
+            }
+        }
+
+        if self.lowlinks[v] == self.indexes[v] {
+            let mut component = Vec::new();
+            while let Some(w) = self.stack.pop() {
+                self.on_stack.remove(w);
+                component.push(w);
+                if w == v {
+                    break
+                }
+            }
+            self.result.push(component);
+        }
+    }
+}
+
+pub(crate) fn to_json(stats: &Stats) -> Value {
+    let metrics = stats.metrics.iter()
+        .map(|m| json!({
+            "name": m.name,
+            "direct_in": m.direct_in,
+            "direct_out": m.direct_out,
+            "transitive_in": m.transitive_in,
+            "transitive_out": m.transitive_out,
+        }))
+        .collect::<Vec<Value>>();
+    json!({
+        "pods": stats.pod_count,
+        "edges": stats.edge_count,
+        "cycles": stats.cycles.iter()
+            .map(|c| json!({ "members": c.members, "path": c.path }))
+            .collect::<Vec<Value>>(),
+        "metrics": metrics,
+        "leaves": stats.leaves,
+        "roots": stats.roots,
+        "longest_chain": stats.longest_chain,
+    })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::{Metric, Stats};
+
+    pub(crate) fn print_stats(stats: &Stats, top: usize) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let title = |s: &str| Style::from(Colour::White).bold().paint(s.to_string());
+
+        println!(
+            "{} {}",
+            title("Overview"),
+            Style::from(light_gray).paint(format!("({} pods, {} edges)", stats.pod_count, stats.edge_count))
+        );
+
+        println!("\n{}", title("Cycles"));
+        if stats.cycles.is_empty() {
+            println!("    {}", Style::from(Colour::Green).paint("none"));
+        }
+        for c in &stats.cycles {
+            let mut text = Style::from(Colour::Red).paint(c.path.join(" -> ")).to_string();
+            if c.members.len() + 1 > c.path.len() {
+                let more = format!(" ({} pods in this cycle group)", c.members.len());
+                text.push_str(&Style::from(light_gray).paint(more).to_string());
+            }
+            println!("    {} {}", Style::from(Colour::Red).bold().paint("↻"), text);
+        }
+
+        let print_ranking = |name: &str, key: fn(&Metric) -> (usize, usize)| {
+            println!("\n{} {}", title(name), Style::from(light_gray).paint("(transitive / direct)"));
+            let mut ranked = stats.metrics.iter().collect::<Vec<&Metric>>();
+            ranked.sort_by(|a, b| key(b).cmp(&key(a)).then(a.name.cmp(&b.name)));
+            let width = ranked.iter().take(top).map(|m| m.name.len()).max().unwrap_or(0);
+            for m in ranked.into_iter().take(top) {
+                let (transitive, direct) = key(m);
+                println!(
+                    "    {}  {:>5} / {}",
+                    Style::from(Colour::Yellow).paint(format!("{:width$}", m.name, width = width)),
+                    transitive,
+                    direct
+                );
+            }
+        };
+        print_ranking("Fan-in", |m| (m.transitive_in, m.direct_in));
+        print_ranking("Fan-out", |m| (m.transitive_out, m.direct_out));
+
+        println!("\n{} {}", title("Leaves"), Style::from(light_gray).paint(format!("({})", stats.leaves.len())));
+        println!("    {}", stats.leaves.join(", "));
+        println!("\n{} {}", title("Not depended on"), Style::from(light_gray).paint(format!("({})", stats.roots.len())));
+        println!("    {}", stats.roots.join(", "));
+
+        println!(
+            "\n{} {}",
+            title("Longest chain"),
+            Style::from(light_gray).paint(format!("({} pods)", stats.longest_chain.len()))
+        );
+        println!("    {}", stats.longest_chain.join(" -> "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::{Lockfile, PodItem};
+    use super::stats;
+
+    fn pods(edges: &[(&str, &[&str])]) -> HashMap<String, PodItem> {
+        let mut text = "PODS:\n".to_string();
+        for (name, children) in edges {
+            text.push_str(&format!("  - {} (1.0.0)", name));
+            if children.is_empty() {
+                text.push('\n');
+                continue
+            }
+            text.push_str(":\n");
+            for c in *children {
+                text.push_str(&format!("    - {}\n", c));
+            }
+        }
+        text.push_str("\nCOCOAPODS: 1.11.0\n");
+        Lockfile::from_yaml_str(&text).unwrap().pods().unwrap()
+    }
+
+    #[test]
+    fn cycles_are_strongly_connected_components() {
+        // B -> C -> D -> B 是一个环，E <-> F 是另一个
+        let pods = pods(&[
+            ("A", &["B", "G"]),
+            ("B", &["C"]),
+            ("C", &["D"]),
+            ("D", &["B", "E"]),
+            ("E", &["F"]),
+            ("F", &["E"]),
+            ("G", &[]),
+        ]);
+        let result = stats(&pods);
+        let mut cycles = result.cycles.iter().map(|c| c.members.join(",")).collect::<Vec<String>>();
+        cycles.sort();
+        assert_eq!(cycles, ["B,C,D", "E,F"]);
+        let bcd = result.cycles.iter().find(|c| c.members[0] == "B").unwrap();
+        assert_eq!(bcd.path, ["B", "C", "D", "B"]);
+        assert_eq!(result.pod_count, 7);
+        assert_eq!(result.edge_count, 8);
+        assert_eq!(result.roots, ["A"]);
+        assert_eq!(result.leaves, ["G"]);
+    }
+
+    #[test]
+    fn longest_chain_walks_through_cycles_on_real_edges() {
+        // 从 B 进入环，从 D 离开，链上每一步都是真实的边
+        let pods = pods(&[
+            ("A", &["B", "G"]),
+            ("B", &["C"]),
+            ("C", &["D"]),
+            ("D", &["B", "E"]),
+            ("E", &[]),
+            ("G", &[]),
+        ]);
+        let chain = stats(&pods).longest_chain;
+        assert_eq!(chain, ["A", "B", "C", "D", "E"]);
+        for pair in chain.windows(2) {
+            assert!(pods[&pair[0]].children.contains(pair[1].as_str()), "{} -> {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn longest_chain_of_dag() {
+        let pods = pods(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &[]),
+            ("X", &["C"]),
+        ]);
+        assert_eq!(stats(&pods).longest_chain, ["A", "B", "C"]);
+    }
+
+    #[test]
+    fn metrics_count_transitive_neighbours() {
+        let pods = pods(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &["A"]),
+            ("D", &["A"]),
+        ]);
+        let result = stats(&pods);
+        let a = result.metrics.iter().find(|m| m.name == "A").unwrap();
+        assert_eq!((a.direct_in, a.direct_out), (2, 1));
+        // 环里的自己不算
+        assert_eq!((a.transitive_in, a.transitive_out), (3, 2));
+    }
+}
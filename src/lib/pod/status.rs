@@ -0,0 +1,333 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use anyhow::bail;
+use crate::lib::pod::lockfile::{collapse_subspecs, Lockfile};
+use crate::lib::pod::search::NON_POD_DIRS;
+
+// pod status
+// 比较 Podfile.lock 和 Pods/Manifest.lock，提前发现 "The sandbox is not in sync with the Podfile.lock"
+
+pub(crate) const MANIFEST: &str = "Manifest.lock";
+
+// 同一个 pod 在两边不一致的地方
+pub(crate) struct Mismatch {
+    // root spec
+    pub(crate) name: String,
+    // `version`、`checksum`、`external source` 或 `checkout`
+    pub(crate) field: &'static str,
+    pub(crate) lockfile: Option<String>,
+    pub(crate) manifest: Option<String>,
+}
+
+pub(crate) struct Status {
+    pub(crate) mismatches: Vec<Mismatch>,
+    // PODFILE CHECKSUM 不同，(Podfile.lock, Manifest.lock)
+    pub(crate) podfile_checksum: Option<(Option<String>, Option<String>)>,
+    // Podfile.lock 里有，但 Pods/ 下没有目录的 pod，不包含 `:path` 引入的开发 pod
+    pub(crate) missing_dirs: Vec<String>,
+    // Pods/ 下已经不在 Podfile.lock 里的目录
+    pub(crate) stale_dirs: Vec<String>,
+}
+
+impl Status {
+    pub(crate) fn is_in_sync(&self) -> bool {
+        self.mismatches.is_empty()
+            && self.podfile_checksum.is_none()
+            && self.missing_dirs.is_empty()
+            && self.stale_dirs.is_empty()
+    }
+
+    pub(crate) fn problem_count(&self) -> usize {
+        self.mismatches.len()
+            + self.podfile_checksum.iter().count()
+            + self.missing_dirs.len()
+            + self.stale_dirs.len()
+    }
+}
+
+pub(crate) fn status(lockfile: &Lockfile, manifest: &Lockfile, pods_dir: &Path) -> anyhow::Result<Status> {
+    // SPEC CHECKSUMS、EXTERNAL SOURCES 等都以 root spec 为 key，版本也按 root spec 比较
+    let versions = |l: &Lockfile| -> anyhow::Result<HashMap<String, Option<String>>> {
+        let ret = collapse_subspecs(&l.pods()?)
+            .into_values()
+            .map(|p| (p.name, p.vers))
+            .collect();
+        Ok(ret)
+    };
+    let (lock_vers, manifest_vers) = (versions(lockfile)?, versions(manifest)?);
+    let (lock_sums, manifest_sums) = (lockfile.spec_checksums()?, manifest.spec_checksums()?);
+    let describe = |fields: Vec<(&str, &str)>| -> String {
+        fields.into_iter().map(|(k, v)| format!("{} {}", k, v)).collect::<Vec<String>>().join(", ")
+    };
+    let external = |l: &Lockfile| -> anyhow::Result<HashMap<String, String>> {
+        let ret = l.external_sources()?
+            .into_values()
+            .map(|es| (es.name.clone(), describe(es.fields())))
+            .collect();
+        Ok(ret)
+    };
+    let checkout = |l: &Lockfile| -> anyhow::Result<HashMap<String, String>> {
+        let ret = l.checkout_options()?
+            .into_values()
+            .map(|co| (co.name.clone(), describe(co.fields())))
+            .collect();
+        Ok(ret)
+    };
+    let (lock_external, manifest_external) = (external(lockfile)?, external(manifest)?);
+    let (lock_checkout, manifest_checkout) = (checkout(lockfile)?, checkout(manifest)?);
+
+    let names = lock_vers.keys().chain(manifest_vers.keys()).cloned().collect::<BTreeSet<String>>();
+    let mut mismatches = Vec::new();
+    for name in &names {
+        let fields: [(&'static str, Option<String>, Option<String>); 4] = [
+            ("version", lock_vers.get(name).cloned().flatten(), manifest_vers.get(name).cloned().flatten()),
+            ("checksum", lock_sums.get(name).cloned(), manifest_sums.get(name).cloned()),
+            ("external source", lock_external.get(name).cloned(), manifest_external.get(name).cloned()),
+            ("checkout", lock_checkout.get(name).cloned(), manifest_checkout.get(name).cloned()),
+        ];
+        // 一边没有这个 pod 时只报版本
+        let in_both = lock_vers.contains_key(name) && manifest_vers.contains_key(name);
+        for (field, l, m) in fields {
+            if l != m && (in_both || field == "version") {
+                mismatches.push(Mismatch { name: name.clone(), field, lockfile: l, manifest: m });
+            }
+        }
+    }
+
+    let podfile_checksum = Some((lockfile.podfile_checksum(), manifest.podfile_checksum()))
+        .filter(|(l, m)| l != m);
+
+    let mut dirs = BTreeSet::new();
+    for entry in std::fs::read_dir(pods_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type()?.is_dir() && !name.starts_with('.') && !NON_POD_DIRS.contains(&name.as_str()) {
+            dirs.insert(name);
+        }
+    }
+    // `:path` 引入的开发 pod 不会被拷贝到 Pods/ 下
+    let is_development = |name: &str| {
+        lock_external.get(name).map(|es| es.starts_with(":path ")).unwrap_or(false)
+    };
+    let mut missing_dirs = lock_vers.keys()
+        .filter(|n| !dirs.contains(*n) && !is_development(n))
+        .cloned()
+        .collect::<Vec<String>>();
+    missing_dirs.sort();
+    let stale_dirs = dirs.into_iter()
+        .filter(|d| !lock_vers.contains_key(d))
+        .collect();
+    Ok(Status { mismatches, podfile_checksum, missing_dirs, stale_dirs })
+}
+
+// `pods_dir` 为 Pods/ 目录，Manifest.lock 在它下面
+// 返回是否一致
+pub(crate) fn print_status<P: AsRef<Path>>(path: P, pods_dir: &Path, lenient: bool) -> anyhow::Result<bool> {
+    let manifest_path = pods_dir.join(MANIFEST);
+    if !manifest_path.exists() {
+        bail!("{:?} is not exists, run `pod install` first", manifest_path);
+    }
+    let lockfile = Lockfile::from_file(path, lenient)?;
+    let manifest = Lockfile::from_file(&manifest_path, lenient)?;
+    let result = status(&lockfile, &manifest, pods_dir)?;
+    printer::print_status(&result);
+    Ok(result.is_in_sync())
+}
+
+mod printer {
+    use ansi_term::{Colour, Style};
+    use super::{Mismatch, Status};
+    use std::collections::BTreeMap;
+
+    pub(super) fn print_status(status: &Status) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if status.is_in_sync() {
+            println!("{}", Style::from(Colour::Green).paint("Podfile.lock and Pods/Manifest.lock are in sync"));
+            return
+        }
+        let title = |s: &str, count: usize| {
+            println!(
+                "{} {}",
+                Style::from(Colour::White).bold().paint(s),
+                Style::from(light_gray).paint(format!("({})", count))
+            );
+        };
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "none".to_string());
+        let mut printed = false;
+        if !status.mismatches.is_empty() || status.podfile_checksum.is_some() {
+            printed = true;
+            title("Podfile.lock vs Manifest.lock", status.mismatches.len() + status.podfile_checksum.iter().count());
+            let mut by_name = BTreeMap::<&str, Vec<&Mismatch>>::new();
+            for m in &status.mismatches {
+                by_name.entry(m.name.as_str()).or_default().push(m);
+            }
+            for (name, mismatches) in by_name {
+                println!("    {} {}", Style::from(Colour::Red).bold().paint("~"), Style::from(Colour::Yellow).paint(name));
+                for m in mismatches {
+                    println!(
+                        "        {} {} {}",
+                        Style::from(light_gray).paint(format!("{:<15}", m.field)),
+                        value(&m.lockfile),
+                        Style::from(light_gray).paint(format!("(Manifest.lock: {})", value(&m.manifest)))
+                    );
+                }
+            }
+            if let Some((l, m)) = &status.podfile_checksum {
+                println!(
+                    "    {} {} {} {}",
+                    Style::from(Colour::Red).bold().paint("~"),
+                    Style::from(Colour::Yellow).paint("PODFILE CHECKSUM"),
+                    value(l),
+                    Style::from(light_gray).paint(format!("(Manifest.lock: {})", value(m)))
+                );
+            }
+        }
+        let mut print_dirs = |name: &str, bullet: &str, dirs: &[String]| {
+            if dirs.is_empty() {
+                return
+            }
+            if printed {
+                println!();
+            }
+            printed = true;
+            title(name, dirs.len());
+            for d in dirs {
+                println!("    {} {}", Style::from(Colour::Red).bold().paint(bullet), d);
+            }
+        };
+        print_dirs("Missing in Pods/", "-", &status.missing_dirs);
+        print_dirs("Stale in Pods/", "+", &status.stale_dirs);
+
+        let count = status.problem_count();
+        eprintln!(
+            "\n{}",
+            Style::from(light_gray).paint(format!(
+                "The sandbox is not in sync with the Podfile.lock ({} {})",
+                count,
+                if count == 1 { "problem" } else { "problems" }
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::pod::lockfile::Lockfile;
+    use crate::lib::util::testing::TempDir;
+    use super::status;
+
+    const LOCKFILE: &str = "
+PODS:
+  - AFNetworking (4.0.1):
+    - AFNetworking/Core (= 4.0.1)
+  - AFNetworking/Core (4.0.1)
+  - Kingfisher (7.0.0)
+  - Masonry (1.1.0)
+  - RBFoundation (0.1.0)
+
+DEPENDENCIES:
+  - AFNetworking
+  - Kingfisher (from `https://github.com/onevcat/Kingfisher.git`, tag `7.0.0`)
+  - Masonry
+  - RBFoundation (from `./Modules/RBFoundation`)
+
+EXTERNAL SOURCES:
+  Kingfisher:
+    :git: https://github.com/onevcat/Kingfisher.git
+    :tag: 7.0.0
+  RBFoundation:
+    :path: \"./Modules/RBFoundation\"
+
+SPEC CHECKSUMS:
+  AFNetworking: aaa
+  Kingfisher: kkk
+  Masonry: mmm
+  RBFoundation: rrr
+
+PODFILE CHECKSUM: ppp
+
+COCOAPODS: 1.11.3
+";
+
+    fn lockfile(text: &str) -> Lockfile {
+        Lockfile::from_yaml_str(text).unwrap()
+    }
+
+    // Pods/ 下放好 Podfile.lock 里的 pod（不包含开发 pod）
+    fn pods_dir(dirs: &[&str]) -> TempDir {
+        let tmp = TempDir::new();
+        tmp.write("Manifest.lock", "");
+        tmp.write("Target Support Files/Pods-App/Pods-App.xcconfig", "");
+        tmp.write("Pods.xcodeproj/project.pbxproj", "");
+        for d in dirs {
+            tmp.write(format!("{}/README.md", d), "");
+        }
+        tmp
+    }
+
+    #[test]
+    fn in_sync() {
+        let tmp = pods_dir(&["AFNetworking", "Kingfisher", "Masonry"]);
+        let result = status(&lockfile(LOCKFILE), &lockfile(LOCKFILE), tmp.path()).unwrap();
+        assert!(result.is_in_sync());
+        assert_eq!(result.problem_count(), 0);
+    }
+
+    #[test]
+    fn version_checksum_and_external_source_mismatches() {
+        let manifest = LOCKFILE
+            .replace("AFNetworking/Core (4.0.1)", "AFNetworking/Core (4.0.0)")
+            .replace("AFNetworking (4.0.1)", "AFNetworking (4.0.0)")
+            .replace("Masonry: mmm", "Masonry: xxx")
+            .replace(":tag: 7.0.0", ":tag: 6.0.0")
+            .replace("PODFILE CHECKSUM: ppp", "PODFILE CHECKSUM: qqq");
+        let tmp = pods_dir(&["AFNetworking", "Kingfisher", "Masonry"]);
+        let result = status(&lockfile(LOCKFILE), &lockfile(&manifest), tmp.path()).unwrap();
+        let mismatches = result.mismatches.iter()
+            .map(|m| format!(
+                "{} {}: {} vs {}",
+                m.name,
+                m.field,
+                m.lockfile.as_deref().unwrap_or("none"),
+                m.manifest.as_deref().unwrap_or("none")
+            ))
+            .collect::<Vec<String>>();
+        assert_eq!(mismatches, [
+            "AFNetworking version: 4.0.1 vs 4.0.0",
+            "Kingfisher external source: :git https://github.com/onevcat/Kingfisher.git, :tag 7.0.0 vs :git https://github.com/onevcat/Kingfisher.git, :tag 6.0.0",
+            "Masonry checksum: mmm vs xxx",
+        ]);
+        assert_eq!(result.podfile_checksum, Some((Some("ppp".to_string()), Some("qqq".to_string()))));
+        assert_eq!(result.problem_count(), 4);
+    }
+
+    #[test]
+    fn pod_only_on_one_side_reports_version() {
+        let manifest = LOCKFILE.replace("  - Masonry (1.1.0)\n", "");
+        let tmp = pods_dir(&["AFNetworking", "Kingfisher", "Masonry"]);
+        let result = status(&lockfile(LOCKFILE), &lockfile(&manifest), tmp.path()).unwrap();
+        let mismatches = result.mismatches.iter()
+            .map(|m| format!("{} {}: {:?}", m.name, m.field, m.manifest))
+            .collect::<Vec<String>>();
+        assert_eq!(mismatches, ["Masonry version: None"]);
+    }
+
+    #[test]
+    fn development_pods_are_not_missing() {
+        let tmp = pods_dir(&["AFNetworking"]);
+        let result = status(&lockfile(LOCKFILE), &lockfile(LOCKFILE), tmp.path()).unwrap();
+        // RBFoundation 由 `:path` 引入，不在 Pods/ 下
+        assert_eq!(result.missing_dirs, ["Kingfisher", "Masonry"]);
+        assert!(result.stale_dirs.is_empty());
+    }
+
+    #[test]
+    fn stale_dirs() {
+        let tmp = pods_dir(&["AFNetworking", "Kingfisher", "Masonry", "SDWebImage"]);
+        tmp.write(".hidden/file", "");
+        let result = status(&lockfile(LOCKFILE), &lockfile(LOCKFILE), tmp.path()).unwrap();
+        assert!(result.missing_dirs.is_empty());
+        assert_eq!(result.stale_dirs, ["SDWebImage"]);
+        assert!(!result.is_in_sync());
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use anyhow::bail;
+use crate::lib::pod::affected::normalize;
+use crate::lib::pod::imports::{module_map, scan, source_files, Location};
+use crate::lib::pod::lockfile::{collapse_subspecs, root_name, Lockfile, PodItem};
+
+// pod unused
+// 扫描 app 的源码目录，收集 import 的模块和头文件，找出既没有被 import、也不是被 import 的 pod 的（间接）依赖的 pod
+// 按 root spec 统计，import 了一个 pod 就认为它的所有 subspec 都在用
+// 用到的 `:path` 开发 pod 的源码也会扫描，它们 import 的 pod 同样算在用
+
+// 仍然需要的 pod 是怎么被用到的
+pub(crate) enum Reference {
+    // 源码里直接 import，给出第一次 import 的位置，可能在用到的开发 pod 里
+    Imported(Location),
+    // 被 import 的 pod 的依赖，链从被 import 的 pod 开始，以这个 pod 结束
+    Required(Vec<String>),
+}
+
+pub(crate) struct UsedPod {
+    pub(crate) name: String,
+    pub(crate) reference: Reference,
+}
+
+pub(crate) struct UnusedPod {
+    pub(crate) name: String,
+    pub(crate) vers: Option<String>,
+    // 在 Podfile 里直接声明，删掉这些，其余的会跟着消失
+    pub(crate) in_podfile: bool,
+}
+
+pub(crate) struct Unused {
+    // 扫描的源文件数量
+    pub(crate) files: usize,
+    pub(crate) used: Vec<UsedPod>,
+    pub(crate) unused: Vec<UnusedPod>,
+}
+
+// `roots` 为 app 的源码目录，`dir` 为 Podfile.lock 所在的目录，`:path` 相对于它
+// import 的位置相对于 `base`
+// `modules` 为模块名到 pod 的映射，见 `imports::module_map`
+pub(crate) fn unused(
+    lockfile: &Lockfile,
+    roots: &[PathBuf],
+    dir: &Path,
+    base: &Path,
+    modules: &HashMap<String, String>
+) -> anyhow::Result<Unused> {
+    let pods = collapse_subspecs(&lockfile.pods()?);
+    let module_map = module_map(&pods, modules);
+    let mut dev_pods = lockfile.external_sources()?
+        .into_values()
+        .filter_map(|es| es.path.map(|p| (es.name, normalize(&dir.join(p)))))
+        .collect::<Vec<(String, PathBuf)>>();
+    dev_pods.sort();
+    let mut files = Vec::new();
+    for root in roots {
+        files.extend(source_files(root, &[])?);
+    }
+    files.sort();
+    files.dedup();
+    let mut imported = scan(&files, &module_map, base);
+
+    // 用到的开发 pod 里 import 的 pod 也在用，直到没有新的开发 pod 用到为止
+    let mut scanned = BTreeSet::<&str>::new();
+    loop {
+        let prev = reach(&pods, &imported);
+        let next = dev_pods.iter().find(|(n, _)| {
+            !scanned.contains(n.as_str()) && (imported.contains_key(n) || prev.contains_key(n.as_str()))
+        });
+        let (name, pod_dir) = match next {
+            Some(p) => p,
+            None => break,
+        };
+        scanned.insert(name);
+        if !pod_dir.is_dir() {
+            bail!("{:?} is not exists, it is the path of {}", pod_dir, name);
+        }
+        // 嵌套在里面的其他开发 pod 不算
+        let nested = dev_pods.iter()
+            .filter(|(n, p)| n != name && p.starts_with(pod_dir))
+            .map(|(_, p)| p.clone())
+            .collect::<Vec<PathBuf>>();
+        let pod_files = source_files(pod_dir, &nested)?;
+        for (pod, location) in scan(&pod_files, &module_map, base) {
+            if &pod != name {
+                imported.entry(pod).or_insert(location);
+            }
+        }
+        files.extend(pod_files);
+    }
+    files.sort();
+    files.dedup();
+    let prev = reach(&pods, &imported);
+
+    let in_podfile = lockfile.dependencies()?
+        .into_iter()
+        .map(|d| root_name(&d.name).to_string())
+        .collect::<BTreeSet<String>>();
+    let mut names = pods.keys().map(|n| n.as_str()).collect::<Vec<&str>>();
+    names.sort();
+    let mut used = Vec::new();
+    let mut unused = Vec::new();
+    for name in names {
+        if let Some(location) = imported.remove(name) {
+            used.push(UsedPod { name: name.to_string(), reference: Reference::Imported(location) });
+        } else if prev.contains_key(name) {
+            let mut chain = vec![name.to_string()];
+            let mut current = name;
+            while let Some(p) = prev.get(current) {
+                current = p;
+                chain.push(current.to_string());
+            }
+            chain.reverse();
+            used.push(UsedPod { name: name.to_string(), reference: Reference::Required(chain) });
+        } else {
+            unused.push(UnusedPod {
+                name: name.to_string(),
+                vers: pods[name].vers.clone(),
+                in_podfile: in_podfile.contains(name),
+            });
+        }
+    }
+    Ok(Unused { files: files.len(), used, unused })
+}
+
+// 从 import 的 pod 出发广度优先，记下每个 pod 是从哪里到达的，得到最短的链
+fn reach<'a>(
+    pods: &'a HashMap<String, PodItem>,
+    imported: &BTreeMap<String, Location>
+) -> HashMap<&'a str, &'a str> {
+    let mut prev = HashMap::<&str, &str>::new();
+    let mut queue = imported.keys()
+        .filter_map(|n| pods.get_key_value(n.as_str()))
+        .map(|(n, _)| n.as_str())
+        .collect::<VecDeque<&str>>();
+    while let Some(name) = queue.pop_front() {
+        let mut children = pods[name].children.iter()
+            .map(|c| c.as_ref())
+            .filter(|c| pods.contains_key(*c))
+            .collect::<Vec<&str>>();
+        children.sort();
+        for c in children {
+            if imported.contains_key(c) || prev.contains_key(c) {
+                continue
+            }
+            prev.insert(c, name);
+            queue.push_back(c);
+        }
+    }
+    prev
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::{Reference, Unused};
+
+    pub(crate) fn print_unused(result: &Unused) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        if !result.used.is_empty() {
+            println!("{}", Style::from(Colour::White).bold().paint("Used"));
+        }
+        for pod in &result.used {
+            let reference = match &pod.reference {
+                Reference::Imported(loc) => format!("imported at {}:{}", loc.file.display(), loc.line),
+                Reference::Required(chain) => format!("required by {}", chain.join(" -> ")),
+            };
+            println!("    {}  {}", pod.name, Style::from(light_gray).paint(reference));
+        }
+        if !result.unused.is_empty() {
+            if !result.used.is_empty() {
+                println!();
+            }
+            println!("{}", Style::from(Colour::White).bold().paint("Unused"));
+        }
+        for pod in &result.unused {
+            let mut text = Style::from(Colour::Red).paint(&pod.name).to_string();
+            if let Some(v) = &pod.vers {
+                text.push_str(&format!(" {}", Style::from(Colour::Yellow).paint(v)));
+            }
+            if pod.in_podfile {
+                text.push_str(&Style::from(light_gray).paint(" (in Podfile)").to_string());
+            }
+            println!("    {}", text);
+        }
+        eprintln!(
+            "{}",
+            Style::from(light_gray).paint(format!(
+                "{} files scanned, {} pods used, {} pods unused",
+                result.files,
+                result.used.len(),
+                result.unused.len()
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::lib::pod::lockfile::Lockfile;
+    use crate::lib::util::testing::TempDir;
+    use super::{unused, Reference, Unused};
+
+    // App import 了 Moya 和开发 pod RBHome；RBHome 又 import 了 Kingfisher
+    // Moya 依赖 Alamofire；SnapKit、RBLegacy 没人用，RBLegacy 依赖的 Masonry 也跟着没人用
+    const LOCKFILE: &str = "
+PODS:
+  - Alamofire (5.4.4)
+  - Kingfisher (7.0.0)
+  - Masonry (1.1.0)
+  - Moya/Core (15.0.0):
+    - Alamofire
+  - RBHome (0.1.0)
+  - RBLegacy (0.1.0):
+    - Masonry
+  - SnapKit (5.6.0)
+
+DEPENDENCIES:
+  - Moya/Core
+  - RBHome (from `./Modules/RBHome`)
+  - RBLegacy (from `./Modules/RBLegacy`)
+  - SnapKit
+
+EXTERNAL SOURCES:
+  RBHome:
+    :path: \"./Modules/RBHome\"
+  RBLegacy:
+    :path: \"./Modules/RBLegacy\"
+
+COCOAPODS: 1.11.0
+";
+
+    fn run(tmp: &TempDir) -> Unused {
+        tmp.write("App/AppDelegate.swift", "import UIKit\nimport Moya\nimport RBHome\n");
+        tmp.write("Modules/RBHome/Home.swift", "import Kingfisher\n");
+        // 没有用到的开发 pod 里的 import 不算
+        tmp.write("Modules/RBLegacy/Legacy.m", "#import <SnapKit/SnapKit.h>\n");
+        let lockfile = Lockfile::from_yaml_str(LOCKFILE).unwrap();
+        unused(&lockfile, &[tmp.path().join("App")], tmp.path(), tmp.path(), &HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn used_pods_are_imported_or_required() {
+        let tmp = TempDir::new();
+        let result = run(&tmp);
+        let used = result.used.iter()
+            .map(|p| match &p.reference {
+                Reference::Imported(l) => format!("{} imported at {}:{}", p.name, l.file.display(), l.line),
+                Reference::Required(chain) => format!("{} required by {}", p.name, chain.join(" -> ")),
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(used, [
+            "Alamofire required by Moya -> Alamofire",
+            "Kingfisher imported at Modules/RBHome/Home.swift:1",
+            "Moya imported at App/AppDelegate.swift:2",
+            "RBHome imported at App/AppDelegate.swift:3",
+        ]);
+        // App 下一个文件，加上 RBHome 的一个
+        assert_eq!(result.files, 2);
+    }
+
+    #[test]
+    fn unused_pods_mark_podfile_entries() {
+        let tmp = TempDir::new();
+        let result = run(&tmp);
+        let unused = result.unused.iter()
+            .map(|p| format!("{}{}", p.name, if p.in_podfile { " (in Podfile)" } else { "" }))
+            .collect::<Vec<String>>();
+        assert_eq!(unused, ["Masonry", "RBLegacy (in Podfile)", "SnapKit (in Podfile)"]);
+    }
+}
@@ -0,0 +1,268 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// pod 版本号，比较规则与 cocoapods-core 的 Pod::Version（即 Gem::Version）一致：
+// - 版本号拆成数字段和字母段，`1.0.0-beta.2` -> [1, 0, 0, beta, 2]
+// - 逐段比较，缺失的段视为 `0`，字母段小于数字段
+// - 含字母段即为 prerelease，`1.0.a` < `1.0`
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Segment {
+    Num(u64),
+    Str(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Version {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+impl Version {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let raw = s.trim();
+        if raw.is_empty() {
+            return None
+        }
+        let segments = split_segments(raw)
+            .into_iter()
+            .map(|t| {
+                t.parse::<u64>()
+                    .map(Segment::Num)
+                    .unwrap_or_else(|_| Segment::Str(t.to_lowercase()))
+            })
+            .collect::<Vec<Segment>>();
+        if segments.is_empty() {
+            return None
+        }
+        Some(Version { raw: raw.to_string(), segments })
+    }
+
+    pub(crate) fn is_prerelease(&self) -> bool {
+        self.segments.iter().any(|s| matches!(s, Segment::Str(_)))
+    }
+
+    // `~>` 的上界：去掉 prerelease 部分和最后一段，再把末段加一
+    // `2.0.3` -> `2.1`，`2.0` -> `3`，`2` -> `3`
+    fn bump(&self) -> Version {
+        let mut numbers = self.segments.iter()
+            .take_while(|s| matches!(s, Segment::Num(_)))
+            .map(|s| match s { Segment::Num(n) => *n, Segment::Str(_) => 0 })
+            .collect::<Vec<u64>>();
+        if numbers.len() > 1 {
+            numbers.pop();
+        }
+        if let Some(last) = numbers.last_mut() {
+            *last += 1;
+        }
+        let raw = numbers.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(".");
+        Version { raw, segments: numbers.into_iter().map(Segment::Num).collect() }
+    }
+
+    // 去掉 prerelease 部分，`1.2.0.beta.1` -> `1.2.0`
+    fn release(&self) -> Version {
+        let segments = self.segments.iter()
+            .take_while(|s| matches!(s, Segment::Num(_)))
+            .cloned()
+            .collect::<Vec<Segment>>();
+        Version { raw: self.raw.clone(), segments }
+    }
+}
+
+// 连续的数字或者连续的字母为一段，其余字符是分隔符，等价于正则 `[0-9]+|[A-Za-z]+`
+// 每个依赖的版本要求都要解析，手写比正则快
+fn split_segments(raw: &str) -> Vec<&str> {
+    let bytes = raw.as_bytes();
+    let mut ret = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i].is_ascii_digit();
+        if !digit && !bytes[i].is_ascii_alphabetic() {
+            i += 1;
+            continue
+        }
+        let start = i;
+        while i < bytes.len() && if digit { bytes[i].is_ascii_digit() } else { bytes[i].is_ascii_alphabetic() } {
+            i += 1;
+        }
+        ret.push(&raw[start..i]);
+    }
+    ret
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let zero = Segment::Num(0);
+        let len = self.segments.len().max(other.segments.len());
+        for i in 0..len {
+            let l = self.segments.get(i).unwrap_or(&zero);
+            let r = other.segments.get(i).unwrap_or(&zero);
+            let ord = match (l, r) {
+                (Segment::Num(a), Segment::Num(b)) => a.cmp(b),
+                (Segment::Str(a), Segment::Str(b)) => a.cmp(b),
+                (Segment::Str(_), Segment::Num(_)) => Ordering::Less,
+                (Segment::Num(_), Segment::Str(_)) => Ordering::Greater,
+            };
+            if ord != Ordering::Equal {
+                return ord
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op { Eq, Ne, Gt, Lt, Ge, Le, Pessimistic }
+
+// 版本要求，譬如 `~> 2.0`、`>= 1.0, < 2.0`，规则与 Pod::Requirement 一致
+#[derive(Clone, Debug)]
+pub(crate) struct Requirement {
+    clauses: Vec<(Op, Version)>,
+}
+
+impl Requirement {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let mut clauses = Vec::new();
+        for clause in s.split(',') {
+            let clause = clause.trim();
+            let (op, rest) = [
+                ("~>", Op::Pessimistic), (">=", Op::Ge), ("<=", Op::Le), ("!=", Op::Ne),
+                ("=", Op::Eq), (">", Op::Gt), ("<", Op::Lt),
+            ]
+                .iter()
+                .find(|(prefix, _)| clause.starts_with(prefix))
+                .map(|(prefix, op)| (*op, &clause[prefix.len()..]))
+                // 不带操作符即为 `=`
+                .unwrap_or((Op::Eq, clause));
+            clauses.push((op, Version::parse(rest)?));
+        }
+        Some(Requirement { clauses })
+    }
+
+    // 仅比较版本号，不考虑 prerelease 的可选性
+    pub(crate) fn is_satisfied_by(&self, v: &Version) -> bool {
+        self.clauses.iter().all(|(op, r)| match op {
+            Op::Eq => v == r,
+            Op::Ne => v != r,
+            Op::Gt => v > r,
+            Op::Lt => v < r,
+            Op::Ge => v >= r,
+            Op::Le => v <= r,
+            Op::Pessimistic => v >= r && v.release() < r.bump(),
+        })
+    }
+
+    // 要求里出现 prerelease 版本时，才允许解析到 prerelease 版本
+    pub(crate) fn is_prerelease(&self) -> bool {
+        self.clauses.iter().any(|(_, v)| v.is_prerelease())
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self.clauses.iter()
+            .map(|(op, v)| {
+                let op = match op {
+                    Op::Eq => "=", Op::Ne => "!=", Op::Gt => ">", Op::Lt => "<",
+                    Op::Ge => ">=", Op::Le => "<=", Op::Pessimistic => "~>",
+                };
+                format!("{} {}", op, v)
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Requirement, Version};
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn satisfied(req: &str, vers: &str) -> bool {
+        Requirement::parse(req).unwrap().is_satisfied_by(&v(vers))
+    }
+
+    #[test]
+    fn compare_missing_segments_as_zero() {
+        assert_eq!(v("1.0"), v("1.0.0"));
+        assert!(v("1.2") < v("1.10"));
+        assert!(v("2") > v("1.99.99"));
+    }
+
+    #[test]
+    fn prerelease_sorts_before_release() {
+        assert!(v("1.0.a").is_prerelease());
+        assert!(!v("1.0.0").is_prerelease());
+        assert!(v("1.0.a") < v("1.0"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0"));
+        assert!(v("1.0.0-beta.2") > v("1.0.0-beta.1"));
+        assert!(v("1.0.0-alpha") < v("1.0.0-beta"));
+        assert!(v("1.0.0-rc.1") > v("0.9.9"));
+    }
+
+    #[test]
+    fn pessimistic_with_two_segments() {
+        // `~> 1.2` 等价于 `>= 1.2, < 2`
+        assert!(satisfied("~> 1.2", "1.2"));
+        assert!(satisfied("~> 1.2", "1.9.5"));
+        assert!(!satisfied("~> 1.2", "2.0"));
+        assert!(!satisfied("~> 1.2", "1.1.9"));
+    }
+
+    #[test]
+    fn pessimistic_with_three_segments() {
+        // `~> 1.2.0` 等价于 `>= 1.2.0, < 1.3`
+        assert!(satisfied("~> 1.2.0", "1.2.7"));
+        assert!(!satisfied("~> 1.2.0", "1.3.0"));
+        assert!(!satisfied("~> 1.2.0", "1.3.0.beta.1"));
+        assert!(satisfied("~> 1.2.0", "1.2.9.beta.1"));
+    }
+
+    #[test]
+    fn combined_clauses() {
+        assert!(satisfied(">= 1.0, < 2.0", "1.5"));
+        assert!(!satisfied(">= 1.0, < 2.0", "2.0"));
+        assert!(!satisfied(">= 1.0, < 2.0", "0.9"));
+        assert!(satisfied("~> 4.0, != 4.0.1", "4.0.2"));
+        assert!(!satisfied("~> 4.0, != 4.0.1", "4.0.1"));
+        assert_eq!(Requirement::parse(">=1.0,<2").unwrap().to_string(), ">= 1.0, < 2");
+    }
+
+    #[test]
+    fn bare_version_is_exact() {
+        assert!(satisfied("1.2.3", "1.2.3"));
+        assert!(!satisfied("1.2.3", "1.2.4"));
+        assert!(satisfied("= 1.2", "1.2.0"));
+        assert!(Requirement::parse(">= ").is_none());
+    }
+
+    #[test]
+    fn prerelease_requirement() {
+        assert!(Requirement::parse("~> 2.0.0-beta").unwrap().is_prerelease());
+        assert!(!Requirement::parse("~> 2.0").unwrap().is_prerelease());
+    }
+}
@@ -0,0 +1,255 @@
+use yaml_rust::Yaml;
+
+// 按 CocoaPods 的格式输出 Podfile.lock
+// 参考 cocoapods-core 的 YAMLHelper：顶层 key 按固定顺序，其余 key 和数组按小写排序，section 之间空一行
+
+// 顶层 key 的顺序，和 `Pod::Lockfile::HASH_KEY_ORDER` 一致
+pub(crate) const KEY_ORDER: [&str; 8] = [
+    "PODS",
+    "DEPENDENCIES",
+    "SPEC REPOS",
+    "EXTERNAL SOURCES",
+    "CHECKOUT OPTIONS",
+    "SPEC CHECKSUMS",
+    "PODFILE CHECKSUM",
+    "COCOAPODS",
+];
+
+pub(crate) fn to_yaml_string(root: &Yaml) -> String {
+    let hash = match root.as_hash() {
+        Some(h) => h,
+        None => return format!("{}\n", scalar(root)),
+    };
+    let mut keys = hash.keys().collect::<Vec<&Yaml>>();
+    keys.sort_by_key(|k| {
+        let text = sorting_string(k);
+        let index = KEY_ORDER.iter().position(|o| *o == text).unwrap_or(KEY_ORDER.len());
+        (index, text.to_lowercase())
+    });
+    let sections = keys.into_iter()
+        .map(|k| {
+            let mut out = String::new();
+            write_entry(k, &hash[k], 0, &mut out);
+            out
+        })
+        .collect::<Vec<String>>();
+    sections.join("\n")
+}
+
+// `key: value` 或者 `key:` 加上缩进的子节点
+fn write_entry(key: &Yaml, value: &Yaml, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match value {
+        Yaml::Hash(_) | Yaml::Array(_) => {
+            out.push_str(&format!("{}{}:\n", pad, scalar(key)));
+            write_node(value, indent + 2, out);
+        },
+        _ => out.push_str(&format!("{}{}: {}\n", pad, scalar(key), scalar(value))),
+    }
+}
+
+fn write_node(node: &Yaml, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match node {
+        Yaml::Hash(h) => {
+            let mut keys = h.keys().collect::<Vec<&Yaml>>();
+            keys.sort_by_key(|k| sorting_string(k).to_lowercase());
+            for k in keys {
+                write_entry(k, &h[k], indent, out);
+            }
+        },
+        Yaml::Array(items) => {
+            let mut items = items.iter().collect::<Vec<&Yaml>>();
+            // 稳定排序，大小写不同的同名项保持原顺序
+            items.sort_by_key(|i| sorting_string(i).to_lowercase());
+            for item in items {
+                match item {
+                    // PODS 里带依赖的 pod：`- Name (1.0):` 加上缩进的依赖列表
+                    Yaml::Hash(h) => {
+                        for (k, v) in h {
+                            out.push_str(&format!("{}- ", pad));
+                            let mut entry = String::new();
+                            write_entry(k, v, indent, &mut entry);
+                            out.push_str(entry.trim_start());
+                        }
+                    },
+                    _ => out.push_str(&format!("{}- {}\n", pad, scalar(item))),
+                }
+            }
+        },
+        _ => out.push_str(&format!("{}{}\n", pad, scalar(node))),
+    }
+}
+
+// 排序时用到的字符串，hash 取第一个 key
+fn sorting_string(node: &Yaml) -> String {
+    match node {
+        Yaml::Hash(h) => h.keys().next().map(sorting_string).unwrap_or_default(),
+        _ => scalar_text(node),
+    }
+}
+
+fn scalar_text(node: &Yaml) -> String {
+    match node {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+// 标量输出，字符串只在会被 YAML 误解时才加引号
+fn scalar(node: &Yaml) -> String {
+    match node {
+        Yaml::String(s) if needs_quote(s) => {
+            format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+        },
+        Yaml::Null | Yaml::BadValue => "~".to_string(),
+        _ => scalar_text(node),
+    }
+}
+
+fn needs_quote(s: &str) -> bool {
+    if s.is_empty() || s.trim() != s {
+        return true
+    }
+    // 开头是 YAML 的指示符，或者像 `./Modules/Foo` 这样以 `.` 开头的路径
+    if s.starts_with(['.', '-', '?', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`', '~']) {
+        return true
+    }
+    // `:path` 这样的 key 不需要引号，`: ` 才会被当成 hash
+    if s.contains(": ") || s.contains(" #") || s.ends_with(':') {
+        return true
+    }
+    // CocoaPods 给带 `+`、`@` 的字符串也加了引号，譬如 `GoogleUtilities/NSData+zlib`
+    if s.contains(['+', '@']) {
+        return true
+    }
+    // 会被解析成布尔、null 或者数字
+    let lower = s.to_lowercase();
+    if ["true", "false", "yes", "no", "on", "off", "null", "~"].contains(&lower.as_str()) {
+        return true
+    }
+    s.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use yaml_rust::YamlLoader;
+    use super::to_yaml_string;
+
+    // `pod install` 生成的 Podfile.lock，已经是规范格式
+    const LOCKFILE: &str = r#"PODS:
+  - "!ProtoCompiler (3.21.5)":
+    - Protobuf (~> 3.21.5)
+  - Alamofire (5.6.4)
+  - Firebase/CoreOnly (10.3.0):
+    - FirebaseCore (= 10.3.0)
+  - FirebaseCore (10.3.0):
+    - FirebaseCoreInternal (~> 10.0)
+    - GoogleUtilities/Environment (~> 7.8)
+    - GoogleUtilities/Logger (~> 7.8)
+  - FirebaseCoreInternal (10.3.0):
+    - "GoogleUtilities/NSData+zlib (~> 7.8)"
+  - GoogleUtilities/Environment (7.10.0):
+    - PromisesObjC (< 3.0, >= 1.2)
+  - GoogleUtilities/Logger (7.10.0):
+    - GoogleUtilities/Environment
+  - "GoogleUtilities/NSData+zlib (7.10.0)"
+  - Kingfisher (7.4.1)
+  - PromisesObjC (2.1.1)
+  - Protobuf (3.21.12)
+  - RBFoundation (0.1.0):
+    - Alamofire
+  - RNFBApp (16.4.6)
+
+DEPENDENCIES:
+  - Alamofire (~> 5.6)
+  - Firebase/CoreOnly
+  - Kingfisher (from `https://github.com/onevcat/Kingfisher.git`, tag `7.4.1`)
+  - RBFoundation (from `./Modules/RBFoundation`)
+  - "RNFBApp (from `../node_modules/@react-native-firebase/app`)"
+
+SPEC REPOS:
+  trunk:
+    - "!ProtoCompiler"
+    - Alamofire
+    - Firebase
+    - FirebaseCore
+    - FirebaseCoreInternal
+    - GoogleUtilities
+    - PromisesObjC
+    - Protobuf
+
+EXTERNAL SOURCES:
+  Kingfisher:
+    :git: https://github.com/onevcat/Kingfisher.git
+    :tag: 7.4.1
+  RBFoundation:
+    :path: "./Modules/RBFoundation"
+  RNFBApp:
+    :path: "../node_modules/@react-native-firebase/app"
+
+CHECKOUT OPTIONS:
+  Kingfisher:
+    :git: https://github.com/onevcat/Kingfisher.git
+    :tag: 7.4.1
+
+SPEC CHECKSUMS:
+  "!ProtoCompiler": e9d09244ac4c2f8e0b3d3ee8c2ffd1e2d31b7f2b
+  Alamofire: 4e95d97098eacb88856099c4fc79b526a299e48c
+  Firebase: f13680471e0d8ab87be9b0d07eb3d3ac10eb0ac6
+  FirebaseCore: 2082fffcd855f95f883c0a1641133eb9bbe76d40
+  FirebaseCoreInternal: 8845798510aae74703467480f71ac613788d0696
+  GoogleUtilities: bad72cb363809015b1f7f19beb1f1cd23c589f95
+  Kingfisher: 6c5449c6450c5239166510ba04afe374a98afc4f
+  PromisesObjC: 09985d6d70fbe7878040aa746d78236e6946d2ef
+  Protobuf: 02524ba6b2e3d4e7e3e8e5ec6a8dc0d4d9a83b1a
+  RBFoundation: 3c1b4f2e9a0d5c7e8f6a1b2c3d4e5f6a7b8c9d0e
+  RNFBApp: 4a1b2c3d4e5f60718293a4b5c6d7e8f901234567
+
+PODFILE CHECKSUM: 0aa8b2ad44a6b19ddc15f2d8b3a8bd66a4cce3ea
+
+COCOAPODS: 1.11.3
+"#;
+
+    #[test]
+    fn canonical_lockfile_round_trips() {
+        let root = YamlLoader::load_from_str(LOCKFILE).unwrap().remove(0);
+        let text = to_yaml_string(&root);
+        assert_eq!(text, LOCKFILE);
+        // 输出再次解析后内容不变
+        assert_eq!(YamlLoader::load_from_str(&text).unwrap().remove(0), root);
+    }
+
+    #[test]
+    fn keys_and_items_are_sorted() {
+        let text = "
+COCOAPODS: 1.11.3
+SPEC CHECKSUMS:
+  b: '2'
+  A: '1'
+PODS:
+  - b (1.0):
+    - c
+    - A
+  - A (1.0)
+  - c (1.0)
+";
+        let root = YamlLoader::load_from_str(text).unwrap().remove(0);
+        assert_eq!(to_yaml_string(&root), "\
+PODS:
+  - A (1.0)
+  - b (1.0):
+    - A
+    - c
+  - c (1.0)
+
+SPEC CHECKSUMS:
+  A: \"1\"
+  b: \"2\"
+
+COCOAPODS: 1.11.3
+");
+    }
+}
@@ -3,7 +3,6 @@ use std::collections::HashMap;
 
 use std::fs::{FileType, Permissions};
 use std::os::unix::fs::{FileTypeExt, PermissionsExt};
-use std::rc::Rc;
 
 use crate::lib::util::{walker::{Walker, WakerEntry}};
 
@@ -82,6 +81,36 @@ fn get_color_chars(
     Some((map[fg_i].clone(), map[bg_i].clone()))
 }
 
+/// Build box-drawing prefix for tree lines, entries must be fed in pre-order
+#[derive(Default)]
+pub(crate) struct TreePrefix {
+    // 用于记录每一层的 entry 下面是否有兄弟
+    depth_siblings: HashMap<usize, bool>,
+}
+
+impl TreePrefix {
+    pub(crate) fn new() -> Self {
+        TreePrefix::default()
+    }
+
+    /// Return prefix for entry at `depth` (starts from `1`)
+    pub(crate) fn next(&mut self, depth: usize, has_next_sibling: bool) -> String {
+        let mut prefix: Vec<char> = Vec::new();
+        let (nbsp, space) = (char::from(0xa0), ' ');
+        for i in 1..depth {
+            if *self.depth_siblings.get(&i).unwrap_or(&false) {
+                prefix.extend_from_slice(&['│', nbsp, nbsp, space]);
+            } else {
+                prefix.extend_from_slice(&[space; 4]);
+            }
+        }
+        let c = if has_next_sibling { '├' } else { '└' };
+        prefix.extend_from_slice(&[c, '─', '─', space]);
+        self.depth_siblings.insert(depth, has_next_sibling);
+        prefix.into_iter().collect::<String>()
+    }
+}
+
 pub fn walk(walker: Walker) -> anyhow::Result<()> {
     let light_gray = Colour::RGB(94, 94, 94);
     let color_chars: Option<ColorChars> =
@@ -102,23 +131,10 @@ pub fn walk(walker: Walker) -> anyhow::Result<()> {
             None
         };
 
-    // 用于记录 entry 的下面是否有兄弟
-    type DepthSiblings = HashMap<usize, bool>;
-    let depth_siblings= Rc::new(RefCell::new(DepthSiblings::new()));
+    let tree_prefix = RefCell::new(TreePrefix::new());
 
     let displaying_prefix = |entry: &WakerEntry| -> String {
-        let mut prefix: Vec<char> = Vec::new();
-        let (nbsp, space) = (char::from(0xa0), ' ');
-        for i in 1..entry.depth {
-            if *depth_siblings.borrow().get(&i).unwrap_or(&false) {
-                prefix.extend_from_slice(&['│', nbsp, nbsp, space]);
-            } else {
-                prefix.extend_from_slice(&[space; 4]);
-            }
-        }
-        let c = if entry.has_next_sibling { '├' } else { '└' };
-        prefix.extend_from_slice(&[c, '─', '─', space]);
-        let prefix= prefix.into_iter().collect::<String>();
+        let prefix = tree_prefix.borrow_mut().next(entry.depth, entry.has_next_sibling);
         Style::from(light_gray).paint(prefix).to_string()
     };
 
@@ -194,9 +210,6 @@ pub fn walk(walker: Walker) -> anyhow::Result<()> {
             displaying_name(&entry),
             displaying_size(&entry)
         );
-
-        let depths = &mut depth_siblings.borrow_mut();
-        depths.insert(entry.depth, entry.has_next_sibling);
     };
     println!("{}", Style::from(light_gray).paint("."));
     walker.start(&handle)?;
@@ -1 +1,4 @@
 pub(crate) mod walker;
+pub(crate) mod similar;
+#[cfg(test)]
+pub(crate) mod testing;
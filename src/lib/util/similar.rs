@@ -0,0 +1,64 @@
+// 按编辑距离找相近的名字，用于拼写错误时给出提示
+
+// Levenshtein 距离，忽略大小写
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_lowercase().chars().collect::<Vec<char>>();
+    let b = b.to_lowercase().chars().collect::<Vec<char>>();
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current[j] = (prev[j] + 1).min(current[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+    prev[b.len()]
+}
+
+// 从 `candidates` 里挑出和 `name` 相近的最多 `limit` 个，距离近的在前
+pub(crate) fn similar_names<'a, I>(name: &str, candidates: I, limit: usize) -> Vec<&'a str>
+    where I: IntoIterator<Item = &'a str> {
+    let lower = name.to_lowercase();
+    // 长一些的名字允许更多的错误
+    let threshold = (name.chars().count() / 3).max(1);
+    let mut ret = candidates.into_iter()
+        .filter_map(|c| {
+            let distance = edit_distance(name, c);
+            // 大小写不同或者只输入了一部分，也算相近
+            let contains = lower.len() >= 3 && c.to_lowercase().contains(&lower);
+            if distance <= threshold || contains {
+                Some((distance, c))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<(usize, &str)>>();
+    ret.sort();
+    ret.into_iter().take(limit).map(|(_, c)| c).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, similar_names};
+
+    #[test]
+    fn distance_ignores_case() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("Masonry", "masonry"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn similar_names_by_distance_and_substring() {
+        let names = ["AFNetworking", "Alamofire", "FirebaseCore", "FirebaseAnalytics", "Masonry"];
+        assert_eq!(similar_names("AFNetworkng", names, 5), ["AFNetworking"]);
+        // 只输入了一部分
+        assert_eq!(similar_names("firebase", names, 5), ["FirebaseCore", "FirebaseAnalytics"]);
+        assert_eq!(similar_names("firebase", names, 1), ["FirebaseCore"]);
+        // 太短的输入不按子串匹配
+        assert!(similar_names("ma", names, 5).is_empty());
+        assert!(similar_names("SnapKit", names, 5).is_empty());
+    }
+}
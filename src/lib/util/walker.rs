@@ -5,6 +5,7 @@ use std::convert::AsRef;
 use std::ffi::OsString;
 use std::fs::{DirEntry, FileType, Metadata, Permissions};
 use std::iter::{IntoIterator};
+use std::time::SystemTime;
 use std::os::unix::fs::MetadataExt;
 use once_cell::unsync::OnceCell;
 use ignore::gitignore::Gitignore;
@@ -55,6 +56,10 @@ impl WakerEntry {
     pub fn permissions(&self) -> io::Result<Permissions> {
         self.metadata().map(|m| m.permissions())
     }
+
+    pub fn accessed(&self) -> io::Result<SystemTime> {
+        self.metadata().and_then(|m| m.accessed())
+    }
 }
 
 pub struct Walker {
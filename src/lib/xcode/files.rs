@@ -0,0 +1,205 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use crate::lib::util::walker::{Walker, WakerEntry};
+use crate::lib::xcode::project::{BuildFile, PhaseKind, Project, Target};
+
+// xcode files / xcode orphans
+
+// target 引用的一个文件
+pub(crate) struct TargetFile {
+    pub(crate) phase: PhaseKind,
+    pub(crate) name: String,
+    // 相对于 SOURCE_ROOT，source tree 无法映射到目录（譬如 `BUILT_PRODUCTS_DIR`、`SDKROOT`）时为 `None`
+    pub(crate) path: Option<PathBuf>,
+    // `path` 为 `None` 时文件所在的 source tree
+    pub(crate) source_tree: Option<String>,
+    // 有 `path`，但是磁盘上没有这个文件
+    pub(crate) missing: bool,
+    pub(crate) kind: FileKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileKind {
+    File,
+    // Swift package 的 product
+    Product,
+    // Xcode 16 的文件夹，里面的文件都属于 target
+    Folder,
+}
+
+// `phase` 为 `None` 时列出所有 build phase 的文件
+pub(crate) fn target_files(project: &Project, target: &Target, phase: Option<PhaseKind>) -> Vec<TargetFile> {
+    let target_file = |phase: PhaseKind, id: &str, kind: FileKind| -> TargetFile {
+        let path = project.resolve(id);
+        let source_tree = match (&path, project.files.get(id), project.groups.get(id)) {
+            (Some(_), _, _) => None,
+            (None, Some(f), _) => Some(f.source_tree.clone()),
+            (None, _, Some(g)) => Some(g.source_tree.clone()),
+            _ => None,
+        };
+        TargetFile {
+            phase,
+            name: project.display_name(id),
+            missing: path.as_ref().map(|p| !p.exists()).unwrap_or(false),
+            path: path.map(|p| p.strip_prefix(&project.root).map(|p| p.to_path_buf()).unwrap_or(p)),
+            source_tree,
+            kind,
+        }
+    };
+    let mut ret = Vec::new();
+    for p in target.phases.iter().filter(|p| phase.map(|k| k == p.kind).unwrap_or(true)) {
+        for file in &p.files {
+            if let BuildFile::Product(name) = file {
+                ret.push(TargetFile {
+                    phase: p.kind,
+                    name: name.clone(),
+                    path: None,
+                    source_tree: None,
+                    missing: false,
+                    kind: FileKind::Product,
+                });
+                continue
+            }
+            for id in project.build_file_ids(file) {
+                ret.push(target_file(p.kind, id, FileKind::File));
+            }
+        }
+    }
+    if phase.map(|k| k == PhaseKind::Sources).unwrap_or(true) {
+        for g in &target.synchronized_groups {
+            ret.push(target_file(PhaseKind::Sources, g, FileKind::Folder));
+        }
+    }
+    // 文件夹归到已有的 sources phase 下，其余保持 build phase 的顺序
+    let mut order = Vec::new();
+    for f in &ret {
+        if !order.contains(&f.phase) {
+            order.push(f.phase);
+        }
+    }
+    ret.sort_by_key(|f| order.iter().position(|k| *k == f.phase));
+    ret
+}
+
+// 磁盘上没有被任何 target 引用的源文件
+pub(crate) struct Orphan {
+    // 相对于 SOURCE_ROOT
+    pub(crate) path: PathBuf,
+    // 工程里有这个文件，只是没有加到任何 target
+    pub(crate) in_project: bool,
+}
+
+// 这些目录下的文件不属于这个工程
+const SKIPPED_DIRS: [&str; 5] = ["Pods", "Carthage", "DerivedData", "build", "node_modules"];
+const SKIPPED_SUFFIXES: [&str; 5] = [".xcodeproj", ".xcworkspace", ".xcassets", ".framework", ".bundle"];
+
+// 在 SOURCE_ROOT 下查找扩展名在 `extensions` 里的文件，按 .gitignore 过滤
+// Swift package（带有 Package.swift 的目录）由 SwiftPM 管理，也跳过
+pub(crate) fn orphans(project: &Project, extensions: &[String]) -> anyhow::Result<Vec<Orphan>> {
+    let root = std::fs::canonicalize(&project.root)?;
+    let canonical = |p: PathBuf| std::fs::canonicalize(&p).unwrap_or(p);
+    let mut referenced = HashSet::new();
+    let mut folders = Vec::new();
+    for target in &project.targets {
+        for phase in &target.phases {
+            for file in &phase.files {
+                for id in project.build_file_ids(file) {
+                    referenced.extend(project.resolve(id).map(canonical));
+                }
+            }
+        }
+        folders.extend(target.synchronized_groups.iter().filter_map(|g| project.resolve(g)).map(canonical));
+    }
+    let known = project.files.keys()
+        .filter_map(|id| project.resolve(id))
+        .map(canonical)
+        .collect::<HashSet<PathBuf>>();
+
+    let found = RefCell::new(Vec::<PathBuf>::new());
+    let handle = |entry: WakerEntry| {
+        let path = entry.path();
+        let is_file = entry.file_type().map(|ft| ft.is_file()).unwrap_or(false);
+        let matched = path.extension()
+            .map(|e| extensions.iter().any(|x| x.as_str() == e.to_string_lossy()))
+            .unwrap_or(false);
+        if is_file && matched {
+            found.borrow_mut().push(path);
+        }
+    };
+    Walker::new(&root).start(&handle)?;
+
+    let mut packages = HashMap::<PathBuf, bool>::new();
+    let mut in_package = |path: &Path| -> bool {
+        path.ancestors()
+            .skip(1)
+            .take_while(|a| a.starts_with(&root) && *a != root)
+            .any(|a| *packages.entry(a.to_path_buf()).or_insert_with(|| a.join("Package.swift").is_file()))
+    };
+    let mut ret = BTreeSet::new();
+    for path in found.into_inner() {
+        let relative = path.strip_prefix(&root).unwrap_or(&path).to_path_buf();
+        let skipped = relative.parent()
+            .map(|p| p.iter().any(|c| {
+                let c = c.to_string_lossy();
+                SKIPPED_DIRS.contains(&c.as_ref()) || SKIPPED_SUFFIXES.iter().any(|s| c.ends_with(s))
+            }))
+            .unwrap_or(false);
+        if skipped || referenced.contains(&path) || folders.iter().any(|f| path.starts_with(f)) || in_package(&path) {
+            continue
+        }
+        ret.insert((relative, known.contains(&path)));
+    }
+    Ok(ret.into_iter().map(|(path, in_project)| Orphan { path, in_project }).collect())
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::{FileKind, Orphan, TargetFile};
+
+    pub(crate) fn print_files(files: &[TargetFile]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        let mut last = None;
+        for f in files {
+            if last != Some(f.phase) {
+                if last.is_some() {
+                    println!();
+                }
+                println!("{}", Style::from(Colour::White).bold().paint(f.phase.name()));
+                last = Some(f.phase);
+            }
+            let label = |text: &str| Style::from(light_gray).paint(format!("({})", text)).to_string();
+            let mut text = match (&f.path, f.kind) {
+                (_, FileKind::Product) => format!("{} {}", f.name, label("package product")),
+                (Some(p), FileKind::Folder) => format!("{}/ {}", p.display(), label("folder")),
+                (Some(p), _) => p.display().to_string(),
+                (None, _) => match f.source_tree.as_deref() {
+                    Some("BUILT_PRODUCTS_DIR") => format!("{} {}", f.name, label("built product")),
+                    Some(tree) => format!("{} {}", f.name, label(&format!("in {}", tree))),
+                    None => format!("{} {}", f.name, label("unknown reference")),
+                },
+            };
+            if f.missing {
+                text.push_str(&format!(" {}", label("not on disk")));
+            }
+            println!("    {}", text);
+        }
+    }
+
+    pub(crate) fn print_orphans(orphans: &[Orphan]) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        for o in orphans {
+            if o.in_project {
+                println!("{} {}", o.path.display(), Style::from(light_gray).paint("(in project, no target)"));
+            } else {
+                println!("{}", o.path.display());
+            }
+        }
+        let summary = format!(
+            "{} {} not referenced by any target",
+            orphans.len(),
+            if orphans.len() == 1 { "file" } else { "files" }
+        );
+        eprintln!("{}", Style::from(light_gray).paint(summary));
+    }
+}
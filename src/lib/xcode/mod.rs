@@ -0,0 +1,3 @@
+pub(crate) mod plist;
+pub(crate) mod project;
+pub(crate) mod files;
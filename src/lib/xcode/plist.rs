@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use anyhow::bail;
+
+// OpenStep 风格的 ASCII plist，即 project.pbxproj 的格式
+// - dict：`{ key = value; ... }`
+// - array：`( a, b, )`，最后一项后面可以有逗号
+// - string：`"..."`，或者不带引号的 `[A-Za-z0-9_$+/:.-]` 序列
+// - data：`<0fbd77>`，按十六进制文本保留
+// - 注释：`/* ... */` 和 `// ...`
+
+pub(crate) enum Value {
+    String(String),
+    Array(Vec<Value>),
+    Dict(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_dict(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    // dict 里 `key` 对应的字符串
+    pub(crate) fn str_of(&self, key: &str) -> Option<&str> {
+        self.as_dict()?.get(key)?.as_str()
+    }
+
+    // dict 里 `key` 对应的字符串数组，譬如 `children`、`files`
+    pub(crate) fn strs_of(&self, key: &str) -> Vec<&str> {
+        self.as_dict()
+            .and_then(|d| d.get(key))
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) fn parse(text: &str) -> anyhow::Result<Value> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+    let value = parser.value()?;
+    parser.skip_blank();
+    if parser.pos < parser.chars.len() {
+        bail!("{}: unexpected trailing content", parser.location());
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    // 出错时给出 `line L, column C`，都从 1 开始
+    fn location(&self) -> String {
+        let before = &self.chars[..self.pos.min(self.chars.len())];
+        let line = before.iter().filter(|c| **c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|c| **c != '\n').count() + 1;
+        format!("line {}, column {}", line, column)
+    }
+
+    fn skip_blank(&mut self) {
+        loop {
+            match (self.peek(), self.chars.get(self.pos + 1)) {
+                (Some(c), _) if c.is_whitespace() => self.pos += 1,
+                (Some('/'), Some('/')) => {
+                    while self.peek().map(|c| c != '\n').unwrap_or(false) {
+                        self.pos += 1;
+                    }
+                },
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    while self.pos < self.chars.len() && !(self.chars[self.pos] == '*' && self.chars.get(self.pos + 1) == Some(&'/')) {
+                        self.pos += 1;
+                    }
+                    self.pos = (self.pos + 2).min(self.chars.len());
+                },
+                _ => return,
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        self.skip_blank();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            },
+            Some(c) => bail!("{}: expected `{}`, found `{}`", self.location(), expected, c),
+            None => bail!("{}: expected `{}`, found end of file", self.location(), expected),
+        }
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        self.skip_blank();
+        match self.peek() {
+            Some('{') => self.dict(),
+            Some('(') => self.array(),
+            Some('<') => self.data(),
+            Some(_) => Ok(Value::String(self.string()?)),
+            None => bail!("{}: unexpected end of file", self.location()),
+        }
+    }
+
+    fn dict(&mut self) -> anyhow::Result<Value> {
+        self.expect('{')?;
+        let mut ret = BTreeMap::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(Value::Dict(ret))
+            }
+            let key = self.string()?;
+            self.expect('=')?;
+            let value = self.value()?;
+            self.expect(';')?;
+            ret.insert(key, value);
+        }
+    }
+
+    fn array(&mut self) -> anyhow::Result<Value> {
+        self.expect('(')?;
+        let mut ret = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(')') {
+                self.pos += 1;
+                return Ok(Value::Array(ret))
+            }
+            ret.push(self.value()?);
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {},
+                _ => bail!("{}: expected `,` or `)`", self.location()),
+            }
+        }
+    }
+
+    fn data(&mut self) -> anyhow::Result<Value> {
+        self.expect('<')?;
+        let start = self.pos;
+        while self.peek().map(|c| c != '>').unwrap_or(false) {
+            self.pos += 1;
+        }
+        let text = self.chars[start..self.pos].iter().filter(|c| !c.is_whitespace()).collect();
+        self.expect('>')?;
+        Ok(Value::String(text))
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        self.skip_blank();
+        if self.peek() == Some('"') {
+            return self.quoted()
+        }
+        let start = self.pos;
+        while self.peek().map(is_unquoted).unwrap_or(false) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            match self.peek() {
+                Some(c) => bail!("{}: unexpected `{}`", self.location(), c),
+                None => bail!("{}: unexpected end of file", self.location()),
+            }
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn quoted(&mut self) -> anyhow::Result<String> {
+        let start = self.location();
+        self.pos += 1;
+        let mut ret = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '"' => return Ok(ret),
+                '\\' => {
+                    let escaped = self.peek();
+                    self.pos += 1;
+                    match escaped {
+                        Some('n') => ret.push('\n'),
+                        Some('t') => ret.push('\t'),
+                        Some('r') => ret.push('\r'),
+                        Some(e) => ret.push(e),
+                        None => break,
+                    }
+                },
+                _ => ret.push(c),
+            }
+        }
+        bail!("{}: unterminated string", start)
+    }
+}
+
+fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_$+/:.-".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Value};
+
+    fn dict(text: &str) -> Value {
+        let value = parse(text).unwrap();
+        assert!(value.as_dict().is_some());
+        value
+    }
+
+    #[test]
+    fn comments_around_keys_and_values() {
+        let v = dict("// !$*UTF8*$!\n{\n\
+            \t/* Begin PBXBuildFile section */\n\
+            \tB1 /* AppDelegate.swift in Sources */ = {isa = PBXBuildFile; fileRef = F1 /* AppDelegate.swift */; };\n\
+            \tkey /*x*/=/*y*/value // tail\n;\n\
+            }");
+        assert_eq!(v.as_dict().unwrap()["B1"].str_of("fileRef"), Some("F1"));
+        assert_eq!(v.str_of("key"), Some("value"));
+    }
+
+    #[test]
+    fn quoted_strings_and_escapes() {
+        let v = dict(r#"{ a = "say \"hi\"\n"; "b c" = "<group>"; d = "tab\there"; e = "back\\slash"; }"#);
+        assert_eq!(v.str_of("a"), Some("say \"hi\"\n"));
+        assert_eq!(v.str_of("b c"), Some("<group>"));
+        assert_eq!(v.str_of("d"), Some("tab\there"));
+        assert_eq!(v.str_of("e"), Some("back\\slash"));
+    }
+
+    #[test]
+    fn unquoted_strings() {
+        let v = dict("{ path = Sources/App-Info.plist; ver = 5.0; }");
+        assert_eq!(v.str_of("path"), Some("Sources/App-Info.plist"));
+        assert_eq!(v.str_of("ver"), Some("5.0"));
+        // `(` 开始一个数组，`$(SDKROOT)` 不带引号是不合法的
+        assert!(parse("{ sdk = $(SDKROOT); }").is_err());
+    }
+
+    #[test]
+    fn data_values() {
+        let v = dict("{ icon = <0fbd77 1a2b\n 3c>; }");
+        assert_eq!(v.str_of("icon"), Some("0fbd771a2b3c"));
+    }
+
+    #[test]
+    fn arrays_with_and_without_trailing_comma() {
+        let v = dict("{ a = (x, y, ); b = (x, y); c = ( ); d = ((1, 2), {k = v;}, ); }");
+        assert_eq!(v.strs_of("a"), ["x", "y"]);
+        assert_eq!(v.strs_of("b"), ["x", "y"]);
+        assert!(v.strs_of("c").is_empty());
+        let d = v.as_dict().unwrap()["d"].as_array().unwrap();
+        assert_eq!(d.len(), 2);
+        assert_eq!(d[1].str_of("k"), Some("v"));
+    }
+
+    #[test]
+    fn errors_have_locations() {
+        let e = parse("{\n  a = b\n}").err().unwrap();
+        assert_eq!(e.to_string(), "line 3, column 1: expected `;`, found `}`");
+        let e = parse("{ a = \"open; }").err().unwrap();
+        assert_eq!(e.to_string(), "line 1, column 7: unterminated string");
+        let e = parse("{ a = (x y); }").err().unwrap();
+        assert!(e.to_string().starts_with("line 1, column 10:"));
+        assert!(parse("{ a = b; } extra").is_err());
+    }
+}
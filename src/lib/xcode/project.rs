@@ -0,0 +1,411 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail};
+use serde_json::{json, Value as Json};
+use crate::lib::xcode::plist::{self, Value};
+
+// project.pbxproj 的模型
+// `objects` 里所有对象按 id 平铺，这里只挑出 target、build phase、file reference、group 和 build configuration
+
+pub(crate) struct Project {
+    // project.pbxproj 的路径
+    pub(crate) path: PathBuf,
+    // SOURCE_ROOT，即 .xcodeproj 所在的目录
+    pub(crate) root: PathBuf,
+    pub(crate) targets: Vec<Target>,
+    pub(crate) files: HashMap<String, FileRef>,
+    pub(crate) groups: HashMap<String, Group>,
+    // 工程级别的 build configuration
+    pub(crate) configurations: Vec<BuildConfiguration>,
+    // file reference 或 group 的 id -> 所在 group 的 id
+    parents: HashMap<String, String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TargetKind { Native, Aggregate, Legacy }
+
+impl TargetKind {
+    fn from_isa(isa: &str) -> Option<Self> {
+        match isa {
+            "PBXNativeTarget" => Some(TargetKind::Native),
+            "PBXAggregateTarget" => Some(TargetKind::Aggregate),
+            "PBXLegacyTarget" => Some(TargetKind::Legacy),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            TargetKind::Native => "native",
+            TargetKind::Aggregate => "aggregate",
+            TargetKind::Legacy => "legacy",
+        }
+    }
+}
+
+pub(crate) struct Target {
+    pub(crate) name: String,
+    pub(crate) kind: TargetKind,
+    // 譬如 `com.apple.product-type.application`
+    pub(crate) product_type: Option<String>,
+    pub(crate) phases: Vec<BuildPhase>,
+    pub(crate) configurations: Vec<BuildConfiguration>,
+    // 依赖的其他 target 的名字
+    pub(crate) dependencies: Vec<String>,
+    // 依赖的 Swift package product 的名字
+    pub(crate) packages: Vec<String>,
+    // Xcode 16 的文件夹（PBXFileSystemSynchronizedRootGroup），里面的文件都属于这个 target
+    pub(crate) synchronized_groups: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PhaseKind { Sources, Resources, Frameworks, Headers, CopyFiles, ShellScript, Rez }
+
+impl PhaseKind {
+    pub(crate) const NAMES: [&'static str; 7] = ["sources", "resources", "frameworks", "headers", "copy-files", "shell-script", "rez"];
+
+    fn from_isa(isa: &str) -> Option<Self> {
+        match isa {
+            "PBXSourcesBuildPhase" => Some(PhaseKind::Sources),
+            "PBXResourcesBuildPhase" => Some(PhaseKind::Resources),
+            "PBXFrameworksBuildPhase" => Some(PhaseKind::Frameworks),
+            "PBXHeadersBuildPhase" => Some(PhaseKind::Headers),
+            "PBXCopyFilesBuildPhase" => Some(PhaseKind::CopyFiles),
+            "PBXShellScriptBuildPhase" => Some(PhaseKind::ShellScript),
+            "PBXRezBuildPhase" => Some(PhaseKind::Rez),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        PhaseKind::NAMES.iter()
+            .position(|n| *n == name)
+            .map(|i| [
+                PhaseKind::Sources, PhaseKind::Resources, PhaseKind::Frameworks, PhaseKind::Headers,
+                PhaseKind::CopyFiles, PhaseKind::ShellScript, PhaseKind::Rez,
+            ][i])
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            PhaseKind::Sources => "sources",
+            PhaseKind::Resources => "resources",
+            PhaseKind::Frameworks => "frameworks",
+            PhaseKind::Headers => "headers",
+            PhaseKind::CopyFiles => "copy-files",
+            PhaseKind::ShellScript => "shell-script",
+            PhaseKind::Rez => "rez",
+        }
+    }
+}
+
+pub(crate) struct BuildPhase {
+    pub(crate) kind: PhaseKind,
+    // 自定义的名字，譬如 shell script 的 `[CP] Embed Pods Frameworks`
+    pub(crate) name: Option<String>,
+    pub(crate) files: Vec<BuildFile>,
+}
+
+// build phase 里的一项，指向 file reference（或者本地化资源的 variant group），或者 Swift package product
+pub(crate) enum BuildFile {
+    File(String),
+    Product(String),
+}
+
+pub(crate) struct FileRef {
+    pub(crate) name: Option<String>,
+    pub(crate) path: Option<String>,
+    // `<group>`、`SOURCE_ROOT`、`<absolute>`、`BUILT_PRODUCTS_DIR` 等
+    pub(crate) source_tree: String,
+}
+
+pub(crate) struct Group {
+    pub(crate) name: Option<String>,
+    pub(crate) path: Option<String>,
+    pub(crate) source_tree: String,
+    pub(crate) children: Vec<String>,
+}
+
+pub(crate) struct BuildConfiguration {
+    pub(crate) name: String,
+    // `baseConfigurationReference`，通常是 .xcconfig 文件
+    pub(crate) base: Option<String>,
+    // 数组的值按空格连接
+    pub(crate) settings: BTreeMap<String, String>,
+}
+
+impl Project {
+    // `path` 可以是 .xcodeproj 目录，也可以是其中的 project.pbxproj
+    pub(crate) fn open(path: &Path) -> anyhow::Result<Self> {
+        let path = if path.is_dir() { path.join("project.pbxproj") } else { path.to_path_buf() };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        let value = plist::parse(&text).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        let xcodeproj = path.parent().unwrap_or_else(|| Path::new("."));
+        let root = xcodeproj.parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+        Project::from_plist(&value, path, root)
+    }
+
+    fn from_plist(value: &Value, path: PathBuf, root: PathBuf) -> anyhow::Result<Self> {
+        let objects = value.as_dict()
+            .and_then(|d| d.get("objects"))
+            .and_then(|o| o.as_dict())
+            .ok_or_else(|| anyhow!("{}: missing objects", path.display()))?;
+        let isa = |id: &str| objects.get(id).and_then(|o| o.str_of("isa"));
+        let owned = |s: Option<&str>| s.map(|s| s.to_string());
+        let configurations = |list: Option<&str>| -> Vec<BuildConfiguration> {
+            let list = list.and_then(|id| objects.get(id));
+            list.map(|l| l.strs_of("buildConfigurations"))
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| objects.get(id))
+                .map(|c| BuildConfiguration {
+                    name: c.str_of("name").unwrap_or_default().to_string(),
+                    base: owned(c.str_of("baseConfigurationReference")),
+                    settings: c.as_dict()
+                        .and_then(|d| d.get("buildSettings"))
+                        .and_then(|s| s.as_dict())
+                        .map(|s| s.iter().map(|(k, v)| (k.clone(), setting_text(v))).collect())
+                        .unwrap_or_default(),
+                })
+                .collect()
+        };
+
+        let mut project = Project {
+            path: path.clone(),
+            root,
+            targets: Vec::new(),
+            files: HashMap::new(),
+            groups: HashMap::new(),
+            configurations: Vec::new(),
+            parents: HashMap::new(),
+        };
+        for (id, object) in objects {
+            match object.str_of("isa").unwrap_or_default() {
+                "PBXFileReference" | "PBXReferenceProxy" => {
+                    project.files.insert(id.clone(), FileRef {
+                        name: owned(object.str_of("name")),
+                        path: owned(object.str_of("path")),
+                        source_tree: object.str_of("sourceTree").unwrap_or("<group>").to_string(),
+                    });
+                },
+                "PBXGroup" | "PBXVariantGroup" | "XCVersionGroup" | "PBXFileSystemSynchronizedRootGroup" => {
+                    let children = object.strs_of("children");
+                    for c in &children {
+                        project.parents.insert(c.to_string(), id.clone());
+                    }
+                    project.groups.insert(id.clone(), Group {
+                        name: owned(object.str_of("name")),
+                        path: owned(object.str_of("path")),
+                        source_tree: object.str_of("sourceTree").unwrap_or("<group>").to_string(),
+                        children: children.into_iter().map(|c| c.to_string()).collect(),
+                    });
+                },
+                "PBXProject" => {
+                    project.configurations = configurations(object.str_of("buildConfigurationList"));
+                },
+                _ => {},
+            }
+        }
+
+        // 按 PBXProject 里 `targets` 的顺序，即 Xcode 里显示的顺序
+        let project_object = value.as_dict()
+            .and_then(|d| d.get("rootObject"))
+            .and_then(|r| r.as_str())
+            .and_then(|r| objects.get(r))
+            .ok_or_else(|| anyhow!("{}: missing rootObject", path.display()))?;
+        for id in project_object.strs_of("targets") {
+            let object = match objects.get(id) {
+                Some(o) => o,
+                None => bail!("{}: missing target {}", path.display(), id),
+            };
+            let kind = match isa(id).and_then(TargetKind::from_isa) {
+                Some(k) => k,
+                None => continue,
+            };
+            let phases = object.strs_of("buildPhases")
+                .into_iter()
+                .filter_map(|p| objects.get(p))
+                .filter_map(|p| {
+                    let kind = PhaseKind::from_isa(p.str_of("isa")?)?;
+                    let files = p.strs_of("files")
+                        .into_iter()
+                        .filter_map(|f| objects.get(f))
+                        .filter_map(|f| {
+                            if let Some(r) = f.str_of("fileRef") {
+                                return Some(BuildFile::File(r.to_string()))
+                            }
+                            let product = objects.get(f.str_of("productRef")?)?;
+                            Some(BuildFile::Product(product.str_of("productName")?.to_string()))
+                        })
+                        .collect();
+                    Some(BuildPhase { kind, name: owned(p.str_of("name")), files })
+                })
+                .collect();
+            let dependencies = object.strs_of("dependencies")
+                .into_iter()
+                .filter_map(|d| objects.get(d))
+                .filter_map(|d| {
+                    let target = d.str_of("target").and_then(|t| objects.get(t));
+                    owned(target.and_then(|t| t.str_of("name")).or_else(|| d.str_of("name")))
+                })
+                .collect();
+            let packages = object.strs_of("packageProductDependencies")
+                .into_iter()
+                .filter_map(|p| objects.get(p))
+                .filter_map(|p| owned(p.str_of("productName")))
+                .collect();
+            project.targets.push(Target {
+                name: object.str_of("name").unwrap_or(id).to_string(),
+                kind,
+                product_type: owned(object.str_of("productType")),
+                phases,
+                configurations: configurations(object.str_of("buildConfigurationList")),
+                dependencies,
+                packages,
+                synchronized_groups: object.strs_of("fileSystemSynchronizedGroups").into_iter().map(|g| g.to_string()).collect(),
+            });
+        }
+        Ok(project)
+    }
+
+    pub(crate) fn target(&self, name: &str) -> anyhow::Result<&Target> {
+        self.targets.iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| {
+                let names = self.targets.iter().map(|t| t.name.as_str()).collect::<Vec<&str>>();
+                anyhow!("Cannot find target {}, available: {}", name, names.join(", "))
+            })
+    }
+
+    // file reference 或 group 在磁盘上的路径，不对应磁盘上的文件（譬如 `BUILT_PRODUCTS_DIR`）时为 `None`
+    pub(crate) fn resolve(&self, id: &str) -> Option<PathBuf> {
+        let (path, source_tree) = match (self.files.get(id), self.groups.get(id)) {
+            (Some(f), _) => (f.path.as_deref(), f.source_tree.as_str()),
+            (_, Some(g)) => (g.path.as_deref(), g.source_tree.as_str()),
+            _ => return None,
+        };
+        let base = match source_tree {
+            // main group 没有所在的 group，相对于 SOURCE_ROOT
+            "<group>" => match self.parents.get(id) {
+                Some(parent) => self.resolve(parent)?,
+                None => self.root.clone(),
+            },
+            "SOURCE_ROOT" => self.root.clone(),
+            "<absolute>" => PathBuf::from("/"),
+            _ => return None,
+        };
+        Some(match path {
+            Some(p) => base.join(p),
+            None => base,
+        })
+    }
+
+    // 显示用的名字：优先 `name`，其次 `path`
+    pub(crate) fn display_name(&self, id: &str) -> String {
+        let (name, path) = match (self.files.get(id), self.groups.get(id)) {
+            (Some(f), _) => (f.name.as_deref(), f.path.as_deref()),
+            (_, Some(g)) => (g.name.as_deref(), g.path.as_deref()),
+            _ => (None, None),
+        };
+        name.or(path).unwrap_or(id).to_string()
+    }
+
+    // build file 对应的文件，本地化资源的 variant group 展开为各个语言的文件
+    pub(crate) fn build_file_ids<'a>(&'a self, file: &'a BuildFile) -> Vec<&'a str> {
+        match file {
+            BuildFile::File(id) => match self.groups.get(id) {
+                Some(g) => g.children.iter().map(|c| c.as_str()).collect(),
+                None => vec![id.as_str()],
+            },
+            BuildFile::Product(_) => Vec::new(),
+        }
+    }
+}
+
+fn setting_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(setting_text).collect::<Vec<String>>().join(" "),
+        Value::Dict(_) => String::new(),
+    }
+}
+
+pub(crate) fn to_json(project: &Project) -> Json {
+    let configurations = |list: &[BuildConfiguration]| -> Json {
+        list.iter()
+            .map(|c| json!({
+                "name": c.name,
+                "base": c.base.as_ref().map(|b| project.display_name(b)),
+                "settings": c.settings,
+            }))
+            .collect()
+    };
+    let targets = project.targets.iter()
+        .map(|t| {
+            let phases = t.phases.iter()
+                .map(|p| json!({ "kind": p.kind.name(), "name": p.name, "files": p.files.len() }))
+                .collect::<Vec<Json>>();
+            json!({
+                "name": t.name,
+                "kind": t.kind.name(),
+                "productType": t.product_type,
+                "phases": phases,
+                "dependencies": t.dependencies,
+                "packages": t.packages,
+                "configurations": configurations(&t.configurations),
+            })
+        })
+        .collect::<Vec<Json>>();
+    json!({
+        "path": project.path.display().to_string(),
+        "targets": targets,
+        "configurations": configurations(&project.configurations),
+    })
+}
+
+pub(crate) mod printer {
+    use ansi_term::{Colour, Style};
+    use super::Project;
+
+    pub(crate) fn print_targets(project: &Project) {
+        let light_gray = Colour::RGB(94, 94, 94);
+        for (i, t) in project.targets.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            let product = t.product_type.as_deref()
+                .map(|p| p.trim_start_matches("com.apple.product-type.").to_string())
+                .unwrap_or_else(|| t.kind.name().to_string());
+            println!(
+                "{} {}",
+                Style::from(Colour::Green).bold().paint(&t.name),
+                Style::from(light_gray).paint(format!("({})", product))
+            );
+            for p in &t.phases {
+                let name = match &p.name {
+                    Some(n) => format!("{} {}", p.kind.name(), Style::from(light_gray).paint(format!("\"{}\"", n))),
+                    None => p.kind.name().to_string(),
+                };
+                println!("    {} {}", name, Style::from(Colour::Yellow).paint(p.files.len().to_string()));
+            }
+            if !t.synchronized_groups.is_empty() {
+                let names = t.synchronized_groups.iter().map(|g| project.display_name(g)).collect::<Vec<String>>();
+                println!("    {} {}", Style::from(light_gray).paint("folders:"), names.join(", "));
+            }
+            if !t.dependencies.is_empty() {
+                println!("    {} {}", Style::from(light_gray).paint("depends on:"), t.dependencies.join(", "));
+            }
+            if !t.packages.is_empty() {
+                println!("    {} {}", Style::from(light_gray).paint("packages:"), t.packages.join(", "));
+            }
+            let configurations = t.configurations.iter().map(|c| c.name.as_str()).collect::<Vec<&str>>();
+            if !configurations.is_empty() {
+                println!("    {} {}", Style::from(light_gray).paint("configurations:"), configurations.join(", "));
+            }
+        }
+    }
+}